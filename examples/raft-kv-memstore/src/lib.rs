@@ -7,6 +7,7 @@ use actix_web::middleware::Logger;
 use actix_web::web::Data;
 use actix_web::App;
 use actix_web::HttpServer;
+use openraft::storage::Adaptor;
 use openraft::BasicNode;
 use openraft::Config;
 use openraft::Raft;
@@ -32,7 +33,12 @@ openraft::declare_raft_types!(
     pub ExampleTypeConfig: D = ExampleRequest, R = ExampleResponse, NodeId = ExampleNodeId, Node = BasicNode
 );
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, Arc<ExampleStore>>;
+pub type ExampleRaft = Raft<
+    ExampleTypeConfig,
+    ExampleNetwork,
+    Adaptor<ExampleTypeConfig, Arc<ExampleStore>>,
+    Adaptor<ExampleTypeConfig, Arc<ExampleStore>>,
+>;
 
 pub async fn start_example_raft_node(node_id: ExampleNodeId, http_addr: String) -> std::io::Result<()> {
     // Create a configuration for the raft instance.
@@ -53,7 +59,10 @@ pub async fn start_example_raft_node(node_id: ExampleNodeId, http_addr: String)
     let network = ExampleNetwork {};
 
     // Create a local raft instance.
-    let raft = Raft::new(node_id, config.clone(), network, store.clone()).await.unwrap();
+    // Split the store into a log store and a state machine.
+    let (log_store, state_machine) = Adaptor::new(store.clone());
+
+    let raft = Raft::new(node_id, config.clone(), network, log_store, state_machine).await.unwrap();
 
     // Create an application that will store all the instances created above, this will
     // be later used on the actix-web services.
@@ -76,6 +85,8 @@ pub async fn start_example_raft_node(node_id: ExampleNodeId, http_addr: String)
             .service(raft::append)
             .service(raft::snapshot)
             .service(raft::vote)
+            .service(raft::pre_vote)
+            .service(raft::timeout_now)
             // admin API
             .service(management::init)
             .service(management::add_learner)
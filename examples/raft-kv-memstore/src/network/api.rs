@@ -5,6 +5,7 @@ use actix_web::Responder;
 use openraft::error::CheckIsLeaderError;
 use openraft::error::Infallible;
 use openraft::BasicNode;
+use openraft::LogIdOptionExt;
 use web::Json;
 
 use crate::app::ExampleApp;
@@ -38,10 +39,17 @@ pub async fn read(app: Data<ExampleApp>, req: Json<String>) -> actix_web::Result
 
 #[post("/consistent_read")]
 pub async fn consistent_read(app: Data<ExampleApp>, req: Json<String>) -> actix_web::Result<impl Responder> {
-    let ret = app.raft.is_leader().await;
+    let ret = app.raft.read_index().await;
 
     match ret {
-        Ok(_) => {
+        Ok(read_log_id) => {
+            // Wait until the state machine has applied the read index, so that the read is linearizable.
+            app.raft
+                .wait(None)
+                .applied_index_at_least(read_log_id.index(), "consistent_read")
+                .await
+                .map_err(actix_web::error::ErrorServiceUnavailable)?;
+
             let state_machine = app.store.state_machine.read().await;
             let key = req.0;
             let value = state_machine.data.get(&key).cloned();
@@ -4,6 +4,7 @@ use actix_web::web::Data;
 use actix_web::Responder;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::TimeoutNowRequest;
 use openraft::raft::VoteRequest;
 use web::Json;
 
@@ -19,6 +20,24 @@ pub async fn vote(app: Data<ExampleApp>, req: Json<VoteRequest<ExampleNodeId>>)
     Ok(Json(res))
 }
 
+#[post("/raft-pre-vote")]
+pub async fn pre_vote(
+    app: Data<ExampleApp>,
+    req: Json<VoteRequest<ExampleNodeId>>,
+) -> actix_web::Result<impl Responder> {
+    let res = app.raft.pre_vote(req.0).await;
+    Ok(Json(res))
+}
+
+#[post("/raft-timeout-now")]
+pub async fn timeout_now(
+    app: Data<ExampleApp>,
+    req: Json<TimeoutNowRequest<ExampleNodeId>>,
+) -> actix_web::Result<impl Responder> {
+    let res = app.raft.timeout_now(req.0).await;
+    Ok(Json(res))
+}
+
 #[post("/raft-append")]
 pub async fn append(
     app: Data<ExampleApp>,
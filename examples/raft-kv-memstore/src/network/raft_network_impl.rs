@@ -4,11 +4,14 @@ use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::RPCError;
 use openraft::error::RemoteError;
+use openraft::error::TimeoutNowError;
 use openraft::error::VoteError;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
 use openraft::BasicNode;
@@ -108,4 +111,19 @@ impl RaftNetwork<ExampleTypeConfig> for ExampleNetworkConnection {
     ) -> Result<VoteResponse<ExampleNodeId>, RPCError<ExampleNodeId, BasicNode, VoteError<ExampleNodeId>>> {
         self.owner.send_rpc(self.target, &self.target_node, "raft-vote", req).await
     }
+
+    async fn send_pre_vote(
+        &mut self,
+        req: VoteRequest<ExampleNodeId>,
+    ) -> Result<VoteResponse<ExampleNodeId>, RPCError<ExampleNodeId, BasicNode, VoteError<ExampleNodeId>>> {
+        self.owner.send_rpc(self.target, &self.target_node, "raft-pre-vote", req).await
+    }
+
+    async fn send_timeout_now(
+        &mut self,
+        req: TimeoutNowRequest<ExampleNodeId>,
+    ) -> Result<TimeoutNowResponse<ExampleNodeId>, RPCError<ExampleNodeId, BasicNode, TimeoutNowError<ExampleNodeId>>>
+    {
+        self.owner.send_rpc(self.target, &self.target_node, "raft-timeout-now", req).await
+    }
 }
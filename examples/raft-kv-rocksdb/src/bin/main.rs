@@ -1,4 +1,5 @@
 use clap::Parser;
+use openraft::storage::Adaptor;
 use openraft::Raft;
 use raft_kv_rocksdb::network::raft_network_impl::ExampleNetwork;
 use raft_kv_rocksdb::start_example_raft_node;
@@ -6,7 +7,12 @@ use raft_kv_rocksdb::store::ExampleStore;
 use raft_kv_rocksdb::ExampleTypeConfig;
 use tracing_subscriber::EnvFilter;
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, ExampleStore>;
+pub type ExampleRaft = Raft<
+    ExampleTypeConfig,
+    ExampleNetwork,
+    Adaptor<ExampleTypeConfig, ExampleStore>,
+    Adaptor<ExampleTypeConfig, ExampleStore>,
+>;
 
 #[derive(Parser, Clone, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use async_std::net::TcpListener;
 use async_std::task;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Raft;
 
@@ -45,7 +46,12 @@ openraft::declare_raft_types!(
     pub ExampleTypeConfig: D = ExampleRequest, R = ExampleResponse, NodeId = ExampleNodeId, Node = ExampleNode
 );
 
-pub type ExampleRaft = Raft<ExampleTypeConfig, ExampleNetwork, Arc<ExampleStore>>;
+pub type ExampleRaft = Raft<
+    ExampleTypeConfig,
+    ExampleNetwork,
+    Adaptor<ExampleTypeConfig, Arc<ExampleStore>>,
+    Adaptor<ExampleTypeConfig, Arc<ExampleStore>>,
+>;
 type Server = tide::Server<Arc<ExampleApp>>;
 pub async fn start_example_raft_node<P>(
     node_id: ExampleNodeId,
@@ -73,7 +79,10 @@ where
     let network = ExampleNetwork {};
 
     // Create a local raft instance.
-    let raft = Raft::new(node_id, config.clone(), network, store.clone()).await.unwrap();
+    // Split the store into a log store and a state machine.
+    let (log_store, state_machine) = Adaptor::new(store.clone());
+
+    let raft = Raft::new(node_id, config.clone(), network, log_store, state_machine).await.unwrap();
 
     let app = Arc::new(ExampleApp {
         id: node_id,
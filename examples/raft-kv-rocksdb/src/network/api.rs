@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use openraft::error::CheckIsLeaderError;
 use openraft::error::Infallible;
+use openraft::LogIdOptionExt;
 use tide::Body;
 use tide::Request;
 use tide::Response;
@@ -43,10 +44,18 @@ async fn read(mut req: Request<Arc<ExampleApp>>) -> tide::Result {
 }
 
 async fn consistent_read(mut req: Request<Arc<ExampleApp>>) -> tide::Result {
-    let ret = req.state().raft.is_leader().await;
+    let ret = req.state().raft.read_index().await;
 
     match ret {
-        Ok(_) => {
+        Ok(read_log_id) => {
+            // Wait until the state machine has applied the read index, so that the read is linearizable.
+            req.state()
+                .raft
+                .wait(None)
+                .applied_index_at_least(read_log_id.index(), "consistent_read")
+                .await
+                .map_err(|e| tide::Error::new(StatusCode::ServiceUnavailable, e))?;
+
             let key: String = req.body_json().await?;
             let state_machine = req.state().store.state_machine.read().await;
 
@@ -4,6 +4,8 @@ use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
 use toy_rpc::macros::export_impl;
@@ -27,6 +29,14 @@ impl Raft {
         self.app.raft.vote(vote).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
     }
     #[export_method]
+    pub async fn pre_vote(&self, vote: VoteRequest<u64>) -> Result<VoteResponse<u64>, toy_rpc::Error> {
+        self.app.raft.pre_vote(vote).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
+    }
+    #[export_method]
+    pub async fn timeout_now(&self, req: TimeoutNowRequest<u64>) -> Result<TimeoutNowResponse<u64>, toy_rpc::Error> {
+        self.app.raft.timeout_now(req).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
+    }
+    #[export_method]
     pub async fn append(
         &self,
         req: AppendEntriesRequest<ExampleTypeConfig>,
@@ -7,11 +7,14 @@ use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::RPCError;
 use openraft::error::RemoteError;
+use openraft::error::TimeoutNowError;
 use openraft::error::VoteError;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
 use openraft::AnyError;
@@ -122,4 +125,19 @@ impl RaftNetwork<ExampleTypeConfig> for ExampleNetworkConnection {
     ) -> Result<VoteResponse<ExampleNodeId>, RPCError<ExampleNodeId, ExampleNode, VoteError<ExampleNodeId>>> {
         self.c().await?.raft().vote(req).await.map_err(|e| to_error(e, self.target))
     }
+
+    async fn send_pre_vote(
+        &mut self,
+        req: VoteRequest<ExampleNodeId>,
+    ) -> Result<VoteResponse<ExampleNodeId>, RPCError<ExampleNodeId, ExampleNode, VoteError<ExampleNodeId>>> {
+        self.c().await?.raft().pre_vote(req).await.map_err(|e| to_error(e, self.target))
+    }
+
+    async fn send_timeout_now(
+        &mut self,
+        req: TimeoutNowRequest<ExampleNodeId>,
+    ) -> Result<TimeoutNowResponse<ExampleNodeId>, RPCError<ExampleNodeId, ExampleNode, TimeoutNowError<ExampleNodeId>>>
+    {
+        self.c().await?.raft().timeout_now(req).await.map_err(|e| to_error(e, self.target))
+    }
 }
@@ -9,11 +9,13 @@ use std::sync::Arc;
 use std::sync::Mutex;
 
 use openraft::async_trait::async_trait;
+use openraft::error::IncompatibleSnapshot;
 use openraft::storage::LogState;
 use openraft::storage::RaftLogReader;
 use openraft::storage::RaftSnapshotBuilder;
 use openraft::storage::Snapshot;
 use openraft::AnyError;
+use openraft::DataSize;
 use openraft::EffectiveMembership;
 use openraft::Entry;
 use openraft::EntryPayload;
@@ -48,6 +50,34 @@ pub struct ClientRequest {
     pub status: String,
 }
 
+/// Estimates the size of a `ClientRequest`, including the strings it owns.
+pub struct ClientRequestSize;
+
+impl DataSize<ClientRequest> for ClientRequestSize {
+    fn data_size(data: &ClientRequest) -> u64 {
+        (std::mem::size_of::<ClientRequest>() + data.client.len() + data.status.len()) as u64
+    }
+}
+
+/// A snapshot can not be installed because its format version is newer than the one a `MemStore` supports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedSnapshotFormat {
+    pub format_version: u32,
+    pub supported: u32,
+}
+
+impl std::fmt::Display for UnsupportedSnapshotFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "snapshot format version {} is newer than the supported version {}",
+            self.format_version, self.supported
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedSnapshotFormat {}
+
 /// Helper trait to build `ClientRequest` for `MemStore` in generic test code.
 pub trait IntoMemClientRequest<T> {
     fn make_request(client_id: &str, serial: u64) -> T;
@@ -69,15 +99,28 @@ pub struct ClientResponse(Option<String>);
 
 pub type MemNodeId = u64;
 
+/// The format version of the snapshot data built by a `MemStore` by default.
+pub const MEM_SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// The application specific snapshot metadata of `MemStore`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemSnapshotMetadata {
+    /// The format version of the snapshot data.
+    ///
+    /// A `MemStore` does not install a snapshot whose format version is newer than the one it builds.
+    pub format_version: u32,
+}
+
 openraft::declare_raft_types!(
     /// Declare the type configuration for `MemStore`.
-    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = ()
+    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = (),
+        SnapshotMetadata = MemSnapshotMetadata, DataSize = ClientRequestSize
 );
 
 /// The application snapshot type which the `MemStore` works with.
 #[derive(Debug)]
 pub struct MemStoreSnapshot {
-    pub meta: SnapshotMeta<MemNodeId, ()>,
+    pub meta: SnapshotMeta<MemNodeId, (), MemSnapshotMetadata>,
 
     /// The data of the state machine at the time of this snapshot.
     pub data: Vec<u8>,
@@ -111,6 +154,12 @@ pub struct MemStore {
 
     snapshot_idx: Arc<Mutex<u64>>,
 
+    /// The format version of the snapshot this store builds, and the newest one it is able to install.
+    snapshot_format_version: Mutex<u32>,
+
+    /// The number of entries of every `append_to_log()` call, to let tests check how logs are batched.
+    append_batches: Mutex<Vec<usize>>,
+
     /// The current snapshot.
     current_snapshot: RwLock<Option<MemStoreSnapshot>>,
 }
@@ -128,10 +177,27 @@ impl MemStore {
             sm,
             vote: RwLock::new(None),
             snapshot_idx: Arc::new(Mutex::new(0)),
+            snapshot_format_version: Mutex::new(MEM_SNAPSHOT_FORMAT_VERSION),
+            append_batches: Mutex::new(vec![]),
             current_snapshot,
         }
     }
 
+    /// Set the format version of the snapshot this store builds and accepts, to emulate an upgraded or outdated
+    /// application.
+    pub fn set_snapshot_format_version(&self, version: u32) {
+        *self.snapshot_format_version.lock().unwrap() = version;
+    }
+
+    pub fn snapshot_format_version(&self) -> u32 {
+        *self.snapshot_format_version.lock().unwrap()
+    }
+
+    /// Returns the number of entries of every `append_to_log()` call so far.
+    pub fn append_batches(&self) -> Vec<usize> {
+        self.append_batches.lock().unwrap().clone()
+    }
+
     pub async fn new_async() -> Arc<Self> {
         Arc::new(Self::new())
     }
@@ -186,7 +252,9 @@ impl RaftLogReader<Config> for Arc<MemStore> {
 #[async_trait]
 impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn build_snapshot(&mut self) -> Result<Snapshot<MemNodeId, (), Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
+    async fn build_snapshot(
+        &mut self,
+    ) -> Result<Snapshot<MemNodeId, (), Cursor<Vec<u8>>, MemSnapshotMetadata>, StorageError<MemNodeId>> {
         let data;
         let last_applied_log;
         let last_membership;
@@ -219,6 +287,9 @@ impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            metadata: MemSnapshotMetadata {
+                format_version: self.snapshot_format_version(),
+            },
         };
 
         let snapshot = MemStoreSnapshot {
@@ -304,6 +375,8 @@ impl RaftStorage<Config> for Arc<MemStore> {
 
     #[tracing::instrument(level = "trace", skip(self, entries))]
     async fn append_to_log(&mut self, entries: &[&Entry<Config>]) -> Result<(), StorageError<MemNodeId>> {
+        self.append_batches.lock().unwrap().push(entries.len());
+
         let mut log = self.log.write().await;
         for entry in entries {
             log.insert(entry.log_id.index, (*entry).clone());
@@ -352,10 +425,30 @@ impl RaftStorage<Config> for Arc<MemStore> {
         Ok(Box::new(Cursor::new(Vec::new())))
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn check_snapshot_meta(
+        &mut self,
+        meta: &SnapshotMeta<MemNodeId, (), MemSnapshotMetadata>,
+    ) -> Result<(), IncompatibleSnapshot> {
+        let supported = self.snapshot_format_version();
+
+        if meta.metadata.format_version > supported {
+            return Err(IncompatibleSnapshot::new(
+                &meta.snapshot_id,
+                &UnsupportedSnapshotFormat {
+                    format_version: meta.metadata.format_version,
+                    supported,
+                },
+            ));
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "trace", skip(self, snapshot))]
     async fn install_snapshot(
         &mut self,
-        meta: &SnapshotMeta<MemNodeId, ()>,
+        meta: &SnapshotMeta<MemNodeId, (), MemSnapshotMetadata>,
         snapshot: Box<Self::SnapshotData>,
     ) -> Result<(), StorageError<MemNodeId>> {
         tracing::info!(
@@ -397,7 +490,7 @@ impl RaftStorage<Config> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self))]
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<MemNodeId, (), Self::SnapshotData>>, StorageError<MemNodeId>> {
+    ) -> Result<Option<Snapshot<MemNodeId, (), Self::SnapshotData, MemSnapshotMetadata>>, StorageError<MemNodeId>> {
         match &*self.current_snapshot.read().await {
             Some(snapshot) => {
                 let data = snapshot.data.clone();
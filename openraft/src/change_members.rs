@@ -9,6 +9,9 @@ pub enum ChangeMembers<NID: NodeId> {
     Add(BTreeSet<NID>),
     Remove(BTreeSet<NID>),
     Replace(BTreeSet<NID>),
+
+    /// Add nodes as witnesses: voters that receive only log ids and metadata and never become a leader.
+    AddWitness(BTreeSet<NID>),
 }
 
 /// Convert a series of ids to a `Replace` operation.
@@ -29,7 +32,16 @@ impl<NID: NodeId> ChangeMembers<NID> {
         match self {
             ChangeMembers::Replace(c) => c,
             ChangeMembers::Add(add_members) => old.union(&add_members).cloned().collect::<BTreeSet<_>>(),
+            ChangeMembers::AddWitness(add_members) => old.union(&add_members).cloned().collect::<BTreeSet<_>>(),
             ChangeMembers::Remove(remove_members) => old.difference(&remove_members).cloned().collect::<BTreeSet<_>>(),
         }
     }
+
+    /// Returns the node ids this change turns into witnesses.
+    pub fn witnesses(&self) -> BTreeSet<NID> {
+        match self {
+            ChangeMembers::AddWitness(w) => w.clone(),
+            _ => BTreeSet::new(),
+        }
+    }
 }
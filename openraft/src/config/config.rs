@@ -1,6 +1,11 @@
 //! Raft runtime configuration.
 
+use std::any::type_name;
+use std::any::Any;
+use std::any::TypeId;
+use std::fmt;
 use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use std::time::Duration;
 
 use clap::Parser;
@@ -8,6 +13,10 @@ use rand::thread_rng;
 use rand::Rng;
 
 use crate::config::error::ConfigError;
+use crate::metrics::RaftMetrics;
+use crate::storage::MAX_DECODED_CHUNK_SIZE;
+use crate::Node;
+use crate::NodeId;
 
 /// Log compaction and snapshot policy.
 ///
@@ -22,6 +31,177 @@ pub enum SnapshotPolicy {
     /// A snapshot will be generated once the log has grown the specified number of logs since
     /// the last snapshot.
     LogsSinceLast(u64),
+
+    /// A snapshot will be generated once the logs applied since the last snapshot have grown the specified number
+    /// of bytes.
+    ///
+    /// The size of a log is estimated by [`LogStats::log_bytes_since_last`].
+    LogBytesSinceLast(u64),
+
+    /// A snapshot will be generated once the specified time has elapsed since the last snapshot, if any log has been
+    /// applied since then.
+    Interval(Duration),
+
+    /// A snapshot is never generated automatically. It is only generated by [`Raft::trigger_snapshot()`].
+    ///
+    /// [`Raft::trigger_snapshot()`]: `crate::Raft::trigger_snapshot`
+    Never,
+
+    /// A snapshot will be generated when the application defined hook returns `true`.
+    ///
+    /// It is built with [`SnapshotPolicy::custom()`] and can not be parsed from command line or deserialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(SnapshotTrigger),
+}
+
+impl SnapshotPolicy {
+    /// Build a [`SnapshotPolicy::Custom`] policy that generates a snapshot when `f` returns `true`.
+    ///
+    /// `f` is called with the statistics of the logs since the last snapshot and the latest metrics of the Raft node,
+    /// after logs are applied and on every tick.
+    /// The type parameters have to be the `NodeId` and `Node` of the Raft node, otherwise [`Raft::new()`] and
+    /// [`Raft::update_config()`] return a [`ConfigError::SnapshotPolicyTypeMismatch`] error.
+    ///
+    /// [`Raft::new()`]: `crate::Raft::new`
+    /// [`Raft::update_config()`]: `crate::Raft::update_config`
+    pub fn custom<NID, N, F>(f: F) -> Self
+    where
+        NID: NodeId,
+        N: Node,
+        F: Fn(&LogStats, &RaftMetrics<NID, N>) -> bool + Send + Sync + 'static,
+    {
+        let trigger = move |stats: &LogStats, metrics: &dyn Any| match metrics.downcast_ref::<RaftMetrics<NID, N>>() {
+            Some(m) => f(stats, m),
+            None => {
+                tracing::error!("custom snapshot policy is built with a different type of RaftMetrics");
+                false
+            }
+        };
+
+        SnapshotPolicy::Custom(SnapshotTrigger {
+            f: Arc::new(trigger),
+            metrics_type: TypeId::of::<RaftMetrics<NID, N>>(),
+            metrics_type_name: type_name::<RaftMetrics<NID, N>>(),
+        })
+    }
+
+    /// Check that a [`SnapshotPolicy::Custom`] policy is built for the `NodeId` and `Node` of a Raft node.
+    pub(crate) fn check_types<NID, N>(&self) -> Result<(), ConfigError>
+    where
+        NID: NodeId,
+        N: Node,
+    {
+        if let SnapshotPolicy::Custom(trigger) = self {
+            if trigger.metrics_type != TypeId::of::<RaftMetrics<NID, N>>() {
+                return Err(ConfigError::SnapshotPolicyTypeMismatch {
+                    expect: type_name::<RaftMetrics<NID, N>>().to_string(),
+                    got: trigger.metrics_type_name.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether to generate a snapshot.
+    pub(crate) fn should_snapshot<NID, N>(&self, stats: &LogStats, metrics: &RaftMetrics<NID, N>) -> bool
+    where
+        NID: NodeId,
+        N: Node,
+    {
+        match self {
+            SnapshotPolicy::LogsSinceLast(n) => stats.logs_since_last >= *n,
+            SnapshotPolicy::LogBytesSinceLast(n) => stats.log_bytes_since_last >= *n,
+            SnapshotPolicy::Interval(t) => stats.logs_since_last > 0 && stats.since_last >= *t,
+            SnapshotPolicy::Never => false,
+            SnapshotPolicy::Custom(trigger) => (trigger.f)(stats, metrics),
+        }
+    }
+
+    /// Returns whether this policy needs the size of every applied log.
+    pub(crate) fn needs_log_bytes(&self) -> bool {
+        matches!(self, SnapshotPolicy::LogBytesSinceLast(_) | SnapshotPolicy::Custom(_))
+    }
+
+    /// Returns whether this policy decides with the metrics of the Raft node.
+    pub(crate) fn needs_metrics(&self) -> bool {
+        matches!(self, SnapshotPolicy::Custom(_))
+    }
+
+    /// Returns whether this policy has to be checked periodically, not only when logs are applied.
+    pub(crate) fn is_time_based(&self) -> bool {
+        matches!(self, SnapshotPolicy::Interval(_) | SnapshotPolicy::Custom(_))
+    }
+}
+
+/// The application defined hook of [`SnapshotPolicy::Custom`].
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct SnapshotTrigger {
+    f: Arc<dyn Fn(&LogStats, &dyn Any) -> bool + Send + Sync>,
+
+    /// The type of the `RaftMetrics` `f` is built for.
+    metrics_type: TypeId,
+    metrics_type_name: &'static str,
+}
+
+impl fmt::Debug for SnapshotTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SnapshotTrigger")
+    }
+}
+
+/// Two triggers are equal only when they are clones of the same hook.
+impl PartialEq for SnapshotTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.f, &other.f)
+    }
+}
+
+impl Eq for SnapshotTrigger {}
+
+/// Statistics of the logs since the last snapshot, by which a [`SnapshotPolicy`] decides whether to generate a
+/// snapshot.
+#[derive(Clone, Debug, Default)]
+#[derive(PartialEq, Eq)]
+pub struct LogStats {
+    /// The number of logs applied to the state machine since the last snapshot.
+    pub logs_since_last: u64,
+
+    /// The total size in bytes of the logs applied to the state machine since the last snapshot is started.
+    ///
+    /// The size of the application data in a log is estimated by [`RaftTypeConfig::DataSize`].
+    ///
+    /// [`RaftTypeConfig::DataSize`]: crate::RaftTypeConfig::DataSize
+    ///
+    /// It is only counted when the policy is [`SnapshotPolicy::LogBytesSinceLast`] or [`SnapshotPolicy::Custom`],
+    /// otherwise it is always 0.
+    pub log_bytes_since_last: u64,
+
+    /// The time elapsed since the last snapshot is started, or since the Raft node is started if there is none.
+    pub since_last: Duration,
+}
+
+/// The codec to compress snapshot data with, when streaming a snapshot to a follower.
+///
+/// Every chunk is compressed independently.
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SnapshotCompression {
+    /// Send the raw snapshot data.
+    #[default]
+    None,
+
+    /// Compress every chunk with LZ4 block format.
+    Lz4,
+}
+
+impl SnapshotCompression {
+    /// All of the compressions this node is able to decode.
+    pub fn decodable() -> Vec<SnapshotCompression> {
+        vec![SnapshotCompression::None, SnapshotCompression::Lz4]
+    }
 }
 
 /// Parse number with unit such as 5.3 KB
@@ -35,19 +215,34 @@ fn parse_bytes_with_unit(src: &str) -> Result<u64, ConfigError> {
 }
 
 fn parse_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
+    let invalid = || ConfigError::InvalidSnapshotPolicy {
+        syntax: "since_last:<num>|log_bytes_since_last:<bytes>|interval:<ms>|never".to_string(),
+        invalid: src.to_string(),
+    };
+
+    if src == "never" {
+        return Ok(SnapshotPolicy::Never);
+    }
+
     let elts = src.split(':').collect::<Vec<_>>();
     if elts.len() != 2 {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
-            invalid: src.to_string(),
-        });
+        return Err(invalid());
     }
 
-    if elts[0] != "since_last" {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
-            invalid: src.to_string(),
-        });
+    match elts[0] {
+        "since_last" => {}
+        "log_bytes_since_last" => {
+            let n_bytes = parse_bytes_with_unit(elts[1])?;
+            return Ok(SnapshotPolicy::LogBytesSinceLast(n_bytes));
+        }
+        "interval" => {
+            let ms = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
+                invalid: src.to_string(),
+                reason: e.to_string(),
+            })?;
+            return Ok(SnapshotPolicy::Interval(Duration::from_millis(ms)));
+        }
+        _ => return Err(invalid()),
     }
 
     let n_logs = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
@@ -57,6 +252,17 @@ fn parse_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
     Ok(SnapshotPolicy::LogsSinceLast(n_logs))
 }
 
+fn parse_snapshot_compression(src: &str) -> Result<SnapshotCompression, ConfigError> {
+    match src {
+        "none" => Ok(SnapshotCompression::None),
+        "lz4" => Ok(SnapshotCompression::Lz4),
+        _ => Err(ConfigError::InvalidSnapshotCompression {
+            syntax: "none|lz4".to_string(),
+            invalid: src.to_string(),
+        }),
+    }
+}
+
 /// The runtime configuration for a Raft node.
 ///
 /// The default values used by this type should generally work well for Raft clusters which will
@@ -119,6 +325,30 @@ pub struct Config {
     #[clap(long, default_value = "300")]
     pub max_payload_entries: u64,
 
+    /// The maximum number of AppendEntries requests to a target that can be sent without waiting for a response.
+    ///
+    /// With a value greater than 1, a leader pipelines log replication: it keeps sending the next payload before
+    /// the previous one is acknowledged. It improves throughput on a link with a high latency.
+    /// Every inflight request uses its own connection created by `RaftNetworkFactory::new_client()`.
+    #[clap(long, default_value = "1")]
+    pub max_inflight_append_requests: u64,
+
+    /// The maximum number of client write requests a leader appends to its log in a batch.
+    ///
+    /// A leader drains the client write requests that are queued, and appends them with one call to the storage.
+    /// Setting it to 1 disables batching.
+    #[clap(long, default_value = "256")]
+    pub max_write_batch_entries: u64,
+
+    /// The maximum size of client write requests a leader appends to its log in a batch, in bytes.
+    ///
+    /// The size of the application data in a request is estimated by
+    /// [`RaftTypeConfig::DataSize`](crate::RaftTypeConfig::DataSize), which by default does not count the data owned
+    /// by it on the heap, such as a `Vec` or a `String`.
+    /// A single request larger than this is still written, in a batch of its own.
+    #[clap(long, default_value = "1MiB", parse(try_from_str=parse_bytes_with_unit))]
+    pub max_write_batch_bytes: u64,
+
     /// The distance behind in log replication a follower must fall before it is considered lagging
     ///
     /// A follower falls behind this index are replicated with snapshot.
@@ -126,10 +356,15 @@ pub struct Config {
     ///
     /// This value should be greater than snapshot_policy.SnapshotPolicy.LogsSinceLast, otherwise transmitting a
     /// snapshot may not fix the lagging.
+    /// With a policy other than `LogsSinceLast`, a follower is replicated with snapshot once it falls behind this
+    /// value.
     #[clap(long, default_value = "5000")]
     pub replication_lag_threshold: u64,
 
     /// The snapshot policy to use for a Raft node.
+    ///
+    /// Command line syntax: `since_last:<num>`, `log_bytes_since_last:<bytes>`, e.g., `log_bytes_since_last:64MiB`,
+    /// `interval:<ms>` or `never`.
     #[clap(
         long,
         default_value = "since_last:5000",
@@ -141,6 +376,21 @@ pub struct Config {
     #[clap(long, default_value = "3MiB", parse(try_from_str=parse_bytes_with_unit))]
     pub snapshot_max_chunk_size: u64,
 
+    /// The codec a leader compresses snapshot chunks with, when transmitting snapshots: `none` or `lz4`.
+    ///
+    /// A leader sends the first chunk uncompressed, and compresses the following chunks only if the target reports
+    /// in the response that it is able to decode them. Thus it is safe to enable in a cluster with older nodes.
+    ///
+    /// A target rejects a compressed chunk that decodes to more than
+    /// [`MAX_DECODED_CHUNK_SIZE`](crate::storage::MAX_DECODED_CHUNK_SIZE) bytes, thus `snapshot_max_chunk_size` must
+    /// not exceed it when compression is enabled.
+    #[clap(
+        long,
+        default_value = "none",
+        parse(try_from_str=parse_snapshot_compression)
+    )]
+    pub snapshot_compression: SnapshotCompression,
+
     /// The maximum number of logs to keep that are already included in **snapshot**.
     ///
     /// Logs that are not in snapshot will never be purged.
@@ -151,6 +401,12 @@ pub struct Config {
     #[clap(long, default_value = "1")]
     pub purge_batch_size: u64,
 
+    /// The number of events buffered for the subscribers of `Raft::subscribe()`.
+    ///
+    /// A subscriber that falls behind by more than this number of events misses the oldest ones.
+    #[clap(long, default_value = "1024")]
+    pub event_buffer_size: u64,
+
     /// Enable or disable tick.
     ///
     /// If ticking is disabled, timeout based events are all disabled:
@@ -182,6 +438,56 @@ pub struct Config {
            action = clap::ArgAction::Set,
            default_missing_value = "true")]
     pub enable_elect: bool,
+
+    /// Whether a node runs a PreVote round before starting an election.
+    ///
+    /// With PreVote enabled, a node whose election timer fires first asks the voters whether they would grant its
+    /// vote, without anyone persisting a new vote. A real election, which increments the term, only starts if a
+    /// quorum says yes. This prevents a partitioned node from deposing a healthy leader when it rejoins.
+    ///
+    /// Every node in the cluster has to support the PreVote RPC, i.e., `RaftNetwork::send_pre_vote()`.
+    /// The value of this config is evaluated as follow:
+    /// - being absent: false
+    /// - `--enable-pre-vote`: true
+    /// - `--enable-pre-vote=true`: true
+    /// - `--enable-pre-vote=false`: false
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           default_missing_value = "true")]
+    pub enable_pre_vote: bool,
+
+    /// Whether a leader answers `Raft::is_leader()` locally with a leader lease.
+    ///
+    /// With leader lease enabled, a leader does not need to contact a quorum to confirm its leadership, if a quorum
+    /// has acknowledged it within the last [`Config::leader_lease()`]. The lease is `election_timeout_min` shortened
+    /// by `lease_clock_drift`. A follower refuses to vote for another node within `election_timeout_min` since it
+    /// accepted an AppendEntries request from the leader.
+    ///
+    /// The safety of leader lease relies on a bounded clock drift between nodes.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           default_missing_value = "true")]
+    pub enable_leader_lease: bool,
+
+    /// The maximum clock drift in milliseconds between nodes, by which a leader lease is shortened.
+    ///
+    /// It has to be less than `election_timeout_min` if `enable_leader_lease` is true.
+    #[clap(long, default_value = "20")]
+    pub lease_clock_drift: u64,
+
+    /// Whether a leader steps down if it has not been acknowledged by a quorum within an election timeout.
+    ///
+    /// A leader isolated from a quorum then reverts to follower and fails pending client requests with
+    /// `ForwardToLeader`, instead of keeping them waiting.
+    ///
+    /// Heartbeat has to be enabled for an idle cluster, otherwise a leader receives no acknowledgement and steps down.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           default_missing_value = "true")]
+    pub enable_check_quorum: bool,
 }
 
 /// Updatable config for a raft runtime.
@@ -225,6 +531,11 @@ impl Config {
         }
     }
 
+    /// Get the duration of a leader lease, during which a leader is sure no other leader can be elected.
+    pub fn leader_lease(&self) -> Duration {
+        Duration::from_millis(self.election_timeout_min.saturating_sub(self.lease_clock_drift))
+    }
+
     pub fn build(args: &[&str]) -> Result<Config, ConfigError> {
         let config = <Self as Parser>::parse_from(args);
         config.validate()
@@ -250,6 +561,38 @@ impl Config {
             return Err(ConfigError::MaxPayloadIs0);
         }
 
+        if self.max_inflight_append_requests == 0 {
+            return Err(ConfigError::MaxInflightAppendRequestsIs0);
+        }
+
+        if self.max_write_batch_entries == 0 {
+            return Err(ConfigError::MaxWriteBatchEntriesIs0);
+        }
+
+        if self.event_buffer_size == 0 {
+            return Err(ConfigError::EventBufferSizeIs0);
+        }
+
+        if self.snapshot_policy == SnapshotPolicy::Interval(Duration::ZERO) {
+            return Err(ConfigError::SnapshotIntervalIs0);
+        }
+
+        if self.snapshot_compression != SnapshotCompression::None
+            && self.snapshot_max_chunk_size > MAX_DECODED_CHUNK_SIZE
+        {
+            return Err(ConfigError::SnapshotChunkSizeTooLarge {
+                snapshot_max_chunk_size: self.snapshot_max_chunk_size,
+                max: MAX_DECODED_CHUNK_SIZE,
+            });
+        }
+
+        if self.enable_leader_lease && self.lease_clock_drift >= self.election_timeout_min {
+            return Err(ConfigError::LeaseClockDriftGEElectionTimeout {
+                lease_clock_drift: self.lease_clock_drift,
+                election_timeout_min: self.election_timeout_min,
+            });
+        }
+
         Ok(self)
     }
 }
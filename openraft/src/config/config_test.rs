@@ -1,7 +1,14 @@
 use core::time::Duration;
 
+use clap::Parser;
+
 use crate::config::error::ConfigError;
+use crate::metrics::RaftMetrics;
+use crate::storage::MAX_DECODED_CHUNK_SIZE;
+use crate::BasicNode;
 use crate::Config;
+use crate::LogStats;
+use crate::SnapshotCompression;
 use crate::SnapshotPolicy;
 
 #[test]
@@ -13,10 +20,15 @@ fn test_config_defaults() {
 
     assert_eq!(50, cfg.heartbeat_interval);
     assert_eq!(300, cfg.max_payload_entries);
+    assert_eq!(1, cfg.max_inflight_append_requests);
+    assert_eq!(256, cfg.max_write_batch_entries);
+    assert_eq!(1024 * 1024, cfg.max_write_batch_bytes);
+    assert_eq!(1024, cfg.event_buffer_size);
     assert_eq!(5000, cfg.replication_lag_threshold);
 
     assert_eq!(3 * 1024 * 1024, cfg.snapshot_max_chunk_size);
     assert_eq!(SnapshotPolicy::LogsSinceLast(5000), cfg.snapshot_policy);
+    assert_eq!(SnapshotCompression::None, cfg.snapshot_compression);
 }
 
 #[test]
@@ -46,6 +58,91 @@ fn test_invalid_election_timeout_config_produces_expected_error() {
     });
 }
 
+#[test]
+fn test_invalid_lease_clock_drift() -> anyhow::Result<()> {
+    let config = Config {
+        election_timeout_min: 100,
+        election_timeout_max: 200,
+        heartbeat_interval: 50,
+        enable_leader_lease: true,
+        lease_clock_drift: 100,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::LeaseClockDriftGEElectionTimeout {
+        lease_clock_drift: 100,
+        election_timeout_min: 100,
+    });
+
+    // Not checked if leader lease is disabled.
+    let config = Config {
+        election_timeout_min: 100,
+        election_timeout_max: 200,
+        heartbeat_interval: 50,
+        enable_leader_lease: false,
+        lease_clock_drift: 100,
+        ..Default::default()
+    };
+    config.validate()?;
+
+    let config = Config {
+        election_timeout_min: 100,
+        election_timeout_max: 200,
+        heartbeat_interval: 50,
+        enable_leader_lease: true,
+        lease_clock_drift: 30,
+        ..Default::default()
+    };
+    let config = config.validate()?;
+    assert_eq!(Duration::from_millis(70), config.leader_lease());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_max_inflight_append_requests() -> anyhow::Result<()> {
+    let config = Config {
+        max_inflight_append_requests: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::MaxInflightAppendRequestsIs0);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_max_write_batch_entries() -> anyhow::Result<()> {
+    let config = Config {
+        max_write_batch_entries: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::MaxWriteBatchEntriesIs0);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_event_buffer_size() -> anyhow::Result<()> {
+    let config = Config {
+        event_buffer_size: 0,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::EventBufferSizeIs0);
+
+    Ok(())
+}
+
 #[test]
 fn test_build() -> anyhow::Result<()> {
     let config = Config::build(&[
@@ -57,11 +154,16 @@ fn test_build() -> anyhow::Result<()> {
         "--send-snapshot-timeout=199",
         "--install-snapshot-timeout=200",
         "--max-payload-entries=201",
+        "--max-inflight-append-requests=8",
+        "--max-write-batch-entries=9",
+        "--max-write-batch-bytes=10",
         "--snapshot-policy=since_last:202",
         "--replication-lag-threshold=203",
         "--snapshot-max-chunk-size=204",
+        "--snapshot-compression=lz4",
         "--max-in-snapshot-log-to-keep=205",
         "--purge-batch-size=207",
+        "--event-buffer-size=208",
     ])?;
 
     assert_eq!("bar", config.cluster_name);
@@ -71,11 +173,16 @@ fn test_build() -> anyhow::Result<()> {
     assert_eq!(199, config.send_snapshot_timeout);
     assert_eq!(200, config.install_snapshot_timeout);
     assert_eq!(201, config.max_payload_entries);
+    assert_eq!(8, config.max_inflight_append_requests);
+    assert_eq!(9, config.max_write_batch_entries);
+    assert_eq!(10, config.max_write_batch_bytes);
     assert_eq!(SnapshotPolicy::LogsSinceLast(202), config.snapshot_policy);
     assert_eq!(203, config.replication_lag_threshold);
     assert_eq!(204, config.snapshot_max_chunk_size);
+    assert_eq!(SnapshotCompression::Lz4, config.snapshot_compression);
     assert_eq!(205, config.max_in_snapshot_log_to_keep);
     assert_eq!(207, config.purge_batch_size);
+    assert_eq!(208, config.event_buffer_size);
 
     // Test config methods
     {
@@ -143,3 +250,163 @@ fn test_config_enable_elect() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_config_enable_pre_vote() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--enable-pre-vote=false"])?;
+    assert_eq!(false, config.enable_pre_vote);
+
+    let config = Config::build(&["foo", "--enable-pre-vote=true"])?;
+    assert_eq!(true, config.enable_pre_vote);
+
+    let config = Config::build(&["foo", "--enable-pre-vote"])?;
+    assert_eq!(true, config.enable_pre_vote);
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(false, config.enable_pre_vote);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_enable_check_quorum() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--enable-check-quorum=false"])?;
+    assert_eq!(false, config.enable_check_quorum);
+
+    let config = Config::build(&["foo", "--enable-check-quorum=true"])?;
+    assert_eq!(true, config.enable_check_quorum);
+
+    let config = Config::build(&["foo", "--enable-check-quorum"])?;
+    assert_eq!(true, config.enable_check_quorum);
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(false, config.enable_check_quorum);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_snapshot_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--snapshot-policy=log_bytes_since_last:2KiB"])?;
+    assert_eq!(SnapshotPolicy::LogBytesSinceLast(2048), config.snapshot_policy);
+
+    let config = Config::build(&["foo", "--snapshot-policy=interval:3000"])?;
+    assert_eq!(
+        SnapshotPolicy::Interval(Duration::from_millis(3000)),
+        config.snapshot_policy
+    );
+
+    let config = Config::build(&["foo", "--snapshot-policy=never"])?;
+    assert_eq!(SnapshotPolicy::Never, config.snapshot_policy);
+
+    for invalid in ["foo", "since_last", "interval:3s", "never:1", "log_bytes_since_last:x"] {
+        let res = <Config as Parser>::try_parse_from(["foo", &format!("--snapshot-policy={}", invalid)]);
+        assert!(res.is_err(), "{} is invalid", invalid);
+    }
+
+    let res = <Config as Parser>::try_parse_from(["foo", "--snapshot-policy=foo"]);
+    let err = res.unwrap_err().to_string();
+    assert!(
+        err.contains("snapshot policy string is invalid: '\"foo\"' expect: 'since_last:<num>|log_bytes_since_last:<bytes>|interval:<ms>|never'"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_snapshot_interval() -> anyhow::Result<()> {
+    let config = Config {
+        snapshot_policy: SnapshotPolicy::Interval(Duration::ZERO),
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::SnapshotIntervalIs0);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_snapshot_max_chunk_size_with_compression() -> anyhow::Result<()> {
+    let config = Config {
+        snapshot_max_chunk_size: MAX_DECODED_CHUNK_SIZE + 1,
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok(), "no limit without compression");
+
+    let config = Config {
+        snapshot_max_chunk_size: MAX_DECODED_CHUNK_SIZE + 1,
+        snapshot_compression: SnapshotCompression::Lz4,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::SnapshotChunkSizeTooLarge {
+        snapshot_max_chunk_size: MAX_DECODED_CHUNK_SIZE + 1,
+        max: MAX_DECODED_CHUNK_SIZE,
+    });
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_policy_should_snapshot() -> anyhow::Result<()> {
+    let metrics = RaftMetrics::<u64, ()>::new_initial(1);
+
+    let stats = |logs, bytes, ms| LogStats {
+        logs_since_last: logs,
+        log_bytes_since_last: bytes,
+        since_last: Duration::from_millis(ms),
+    };
+
+    let p = SnapshotPolicy::LogsSinceLast(10);
+    assert!(!p.should_snapshot(&stats(9, 1000, 1000), &metrics));
+    assert!(p.should_snapshot(&stats(10, 0, 0), &metrics));
+
+    let p = SnapshotPolicy::LogBytesSinceLast(1024);
+    assert!(!p.should_snapshot(&stats(1000, 1023, 1000), &metrics));
+    assert!(p.should_snapshot(&stats(1, 1024, 0), &metrics));
+
+    let p = SnapshotPolicy::Interval(Duration::from_millis(100));
+    assert!(!p.should_snapshot(&stats(1, 0, 99), &metrics));
+    assert!(p.should_snapshot(&stats(1, 0, 100), &metrics));
+    assert!(!p.should_snapshot(&stats(0, 0, 100), &metrics), "no log applied");
+
+    let p = SnapshotPolicy::Never;
+    assert!(!p.should_snapshot(&stats(u64::MAX, u64::MAX, 100_000), &metrics));
+
+    let p = SnapshotPolicy::custom(|stats: &LogStats, m: &RaftMetrics<u64, ()>| {
+        m.id == 1 && stats.logs_since_last + stats.log_bytes_since_last >= 10
+    });
+    assert!(!p.should_snapshot(&stats(4, 5, 0), &metrics));
+    assert!(p.should_snapshot(&stats(5, 5, 0), &metrics));
+    assert_eq!(p, p.clone());
+    assert_ne!(p, SnapshotPolicy::custom(|_: &LogStats, _: &RaftMetrics<u64, ()>| true));
+
+    // The hook is not called with metrics of other types.
+    let metrics = RaftMetrics::<u64, BasicNode>::new_initial(1);
+    assert!(!p.should_snapshot(&stats(5, 5, 0), &metrics));
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_policy_check_types() -> anyhow::Result<()> {
+    let p = SnapshotPolicy::custom(|_: &LogStats, _: &RaftMetrics<u64, ()>| true);
+    p.check_types::<u64, ()>()?;
+
+    let res = p.check_types::<u64, BasicNode>();
+    assert!(
+        matches!(res, Err(ConfigError::SnapshotPolicyTypeMismatch { .. })),
+        "{:?}",
+        res
+    );
+
+    SnapshotPolicy::LogsSinceLast(10).check_types::<u64, BasicNode>()?;
+
+    Ok(())
+}
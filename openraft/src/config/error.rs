@@ -1,6 +1,7 @@
 /// Error variants related to configuration.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 #[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum ConfigError {
     #[error("election timeout: min({min}) must be < max({max})")]
     ElectionTimeout { min: u64, max: u64 },
@@ -8,15 +9,44 @@ pub enum ConfigError {
     #[error("max_payload_entries must be > 0")]
     MaxPayloadIs0,
 
+    #[error("max_inflight_append_requests must be > 0")]
+    MaxInflightAppendRequestsIs0,
+
+    #[error("max_write_batch_entries must be > 0")]
+    MaxWriteBatchEntriesIs0,
+
+    #[error("event_buffer_size must be > 0")]
+    EventBufferSizeIs0,
+
+    #[error("snapshot_policy interval must be > 0")]
+    SnapshotIntervalIs0,
+
     #[error("election_timeout_min({election_timeout_min}) must be > heartbeat_interval({heartbeat_interval})")]
     ElectionTimeoutLTHeartBeat {
         election_timeout_min: u64,
         heartbeat_interval: u64,
     },
 
+    #[error("lease_clock_drift({lease_clock_drift}) must be < election_timeout_min({election_timeout_min})")]
+    LeaseClockDriftGEElectionTimeout {
+        lease_clock_drift: u64,
+        election_timeout_min: u64,
+    },
+
     #[error("snapshot policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
     InvalidSnapshotPolicy { invalid: String, syntax: String },
 
+    #[error("custom snapshot policy is built for {got}, but the Raft node reports {expect}")]
+    SnapshotPolicyTypeMismatch { expect: String, got: String },
+
+    #[error("snapshot compression string is invalid: '{invalid:?}' expect: '{syntax}'")]
+    InvalidSnapshotCompression { invalid: String, syntax: String },
+
+    #[error(
+        "snapshot_max_chunk_size({snapshot_max_chunk_size}) must be <= {max} when snapshot compression is enabled"
+    )]
+    SnapshotChunkSizeTooLarge { snapshot_max_chunk_size: u64, max: u64 },
+
     #[error("{reason} when parsing {invalid:?}")]
     InvalidNumber { invalid: String, reason: String },
 }
@@ -4,6 +4,9 @@ mod error;
 #[cfg(test)] mod config_test;
 
 pub use config::Config;
+pub use config::LogStats;
 pub(crate) use config::RuntimeConfig;
+pub use config::SnapshotCompression;
 pub use config::SnapshotPolicy;
+pub use config::SnapshotTrigger;
 pub use error::ConfigError;
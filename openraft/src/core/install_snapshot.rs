@@ -5,31 +5,50 @@ use crate::core::streaming_state::StreamingState;
 use crate::core::RaftCore;
 use crate::core::SnapshotState;
 use crate::error::InstallSnapshotError;
+use crate::error::SnapshotChecksumMismatch;
+use crate::error::SnapshotDecodeError;
 use crate::error::SnapshotMismatch;
 use crate::raft::InstallSnapshotRequest;
 use crate::raft::InstallSnapshotResponse;
+use crate::storage::chunk_checksum;
+use crate::storage::decode_chunk;
 use crate::Entry;
 use crate::ErrorSubject;
 use crate::ErrorVerb;
 use crate::MessageSummary;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
+use crate::SnapshotCompression;
 use crate::SnapshotMeta;
 use crate::SnapshotSegmentId;
 use crate::StorageError;
 use crate::StorageIOError;
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
+impl<C, N, LS, SM> RaftCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// Invoked by leader to send chunks of a snapshot to a follower (§7).
     ///
     /// Leaders always send chunks in order. It is important to note that, according to the Raft spec,
     /// a log may only have one snapshot at any time. As snapshot contents are application specific,
     /// the Raft log will only store a pointer to the snapshot file along with the index & term.
+    ///
+    /// If a chunk or the entire snapshot does not match the checksum in the request, the partially received snapshot is
+    /// discarded and the leader has to restart the transfer from the start.
+    ///
+    /// The partially received snapshot is kept until a chunk of another snapshot arrives.
+    /// Thus when the leader restarts streaming the same snapshot, it resumes from the number of bytes received, which
+    /// is returned in the response.
     #[tracing::instrument(level = "debug", skip_all)]
     pub(super) async fn handle_install_snapshot_request(
         &mut self,
-        req: InstallSnapshotRequest<C>,
+        mut req: InstallSnapshotRequest<C>,
     ) -> Result<InstallSnapshotResponse<C::NodeId>, InstallSnapshotError<C::NodeId>> {
         tracing::debug!(req = display(req.summary()));
 
@@ -39,6 +58,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             tracing::info!(?self.engine.state.vote, %req.vote, "InstallSnapshot RPC term is less than current term, ignoring it.");
             return Ok(InstallSnapshotResponse {
                 vote: self.engine.state.vote,
+                received: None,
+                compressions: SnapshotCompression::decodable(),
             });
         }
 
@@ -81,23 +102,89 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             self.begin_installing_snapshot(&req).await?;
         }
 
+        // Verify the chunk before writing it.
+        if let Some(expect) = req.checksum {
+            let got = chunk_checksum(&req.data);
+            if got != expect {
+                return Err(self.discard_corrupted_snapshot(SnapshotChecksumMismatch {
+                    segment: SnapshotSegmentId {
+                        id: req_meta.snapshot_id.clone(),
+                        offset: req.offset,
+                    },
+                    len: req.data.len() as u64,
+                    expect,
+                    got,
+                }));
+            }
+        }
+
+        // Decode the chunk into raw snapshot data.
+        // Nothing is written yet if it fails.
+        let sent_len = req.data.len() as u64;
+        let data = std::mem::take(&mut req.data);
+        req.data = decode_chunk(req.compression, data).map_err(|source| {
+            SnapshotDecodeError {
+                segment: SnapshotSegmentId {
+                    id: req_meta.snapshot_id.clone(),
+                    offset: req.offset,
+                },
+                compression: req.compression,
+                source,
+            }
+        })?;
+        req.compression = SnapshotCompression::None;
+        let raw_len = req.data.len() as u64;
+
+        let snapshot_checksum = req.snapshot_checksum;
+
         // Receive the data.
-        if let SnapshotState::Streaming(streaming) = &mut self.snapshot_state {
+        let (received, digest) = if let SnapshotState::Streaming(streaming) = &mut self.snapshot_state {
             debug_assert_eq!(req_meta.snapshot_id, streaming.snapshot_id);
             streaming.receive(req).await?;
+            (streaming.received(), streaming.digest.finalize())
         } else {
             unreachable!("It has to be Streaming")
-        }
+        };
+
+        self.snapshot_transfer.add_received(sent_len, raw_len);
+        self.engine.output.metrics_flags.set_data_changed();
 
         if done {
+            // Verify the entire snapshot before installing it.
+            if let Some(expect) = snapshot_checksum {
+                if digest != expect {
+                    return Err(self.discard_corrupted_snapshot(SnapshotChecksumMismatch {
+                        segment: SnapshotSegmentId {
+                            id: req_meta.snapshot_id.clone(),
+                            offset: 0,
+                        },
+                        len: received,
+                        expect,
+                        got: digest,
+                    }));
+                }
+            }
+
             self.finalize_snapshot_installation(req_meta).await?;
         }
 
         Ok(InstallSnapshotResponse {
             vote: self.engine.state.vote,
+            received: Some(received),
+            compressions: SnapshotCompression::decodable(),
         })
     }
 
+    /// Discard the partially received snapshot, because the data can not be trusted.
+    ///
+    /// The returned error lets the leader restart the transfer from the start.
+    fn discard_corrupted_snapshot(&mut self, mismatch: SnapshotChecksumMismatch) -> InstallSnapshotError<C::NodeId> {
+        tracing::warn!("discard the receiving snapshot: {}", mismatch);
+
+        self.snapshot_state = SnapshotState::None;
+        mismatch.into()
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     async fn begin_installing_snapshot(
         &mut self,
@@ -118,7 +205,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             .into());
         }
 
-        let snapshot_data = self.storage.begin_receiving_snapshot().await?;
+        // Let the state machine reject a snapshot it can not install, before receiving any data.
+        self.sm_handle.check_snapshot_meta(req.meta.clone()).await??;
+
+        let snapshot_data = self.sm_handle.begin_receiving_snapshot().await?;
         self.snapshot_state = SnapshotState::Streaming(StreamingState::new(id, snapshot_data));
 
         Ok(())
@@ -130,7 +220,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     #[tracing::instrument(level = "debug", skip_all)]
     async fn finalize_snapshot_installation(
         &mut self,
-        meta: SnapshotMeta<C::NodeId, C::Node>,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
     ) -> Result<(), StorageError<C::NodeId>> {
         tracing::debug!(meta = display(meta.summary()));
 
@@ -151,8 +241,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             ),
         })?;
 
-        // Buffer the snapshot data, let Engine decide to install it or to cancel it.
-        self.received_snapshot.insert(meta.snapshot_id.clone(), snapshot_data);
+        // Buffer the snapshot data and its application-specific metadata, let Engine decide to install or cancel it.
+        let (meta, metadata) = meta.split();
+        self.received_snapshot.insert(meta.snapshot_id.clone(), (metadata, snapshot_data));
 
         self.engine.install_snapshot(meta);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
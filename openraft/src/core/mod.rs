@@ -5,18 +5,24 @@
 //! messages to other raft nodes.
 
 mod install_snapshot;
+mod proposal_times;
 mod raft_core;
 mod replication_expectation;
 mod replication_state;
 mod server_state;
+mod sm_worker;
 mod snapshot_state;
 mod streaming_state;
 mod tick;
 
+pub(crate) use proposal_times::ProposalTimes;
 pub use raft_core::RaftCore;
 pub(crate) use replication_expectation::Expectation;
 pub(crate) use replication_state::replication_lag;
 pub use server_state::ServerState;
+pub(crate) use sm_worker::ApplyResult;
+pub(crate) use sm_worker::StateMachineHandle;
+pub(crate) use sm_worker::StateMachineWorker;
 pub(crate) use snapshot_state::SnapshotResult;
 pub(crate) use snapshot_state::SnapshotState;
 pub(crate) use tick::Tick;
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use tokio::time::Instant;
+
+use crate::metrics::Histogram;
+use crate::metrics::LatencyRecorder;
+use crate::NodeId;
+
+/// A batch of logs proposed together.
+#[derive(Debug)]
+struct Batch {
+    last_index: u64,
+    n: u64,
+    proposed_at: Instant,
+}
+
+/// The time when logs are proposed on the leader, to record the latency of every write stage.
+///
+/// A batch is removed when it is applied, the other stages only move a cursor forward.
+#[derive(Debug, Default)]
+pub(crate) struct ProposalTimes {
+    /// Proposed batches that are not yet applied, in ascending order of log index.
+    batches: VecDeque<Batch>,
+
+    /// The last log index whose append latency is recorded.
+    appended: Option<u64>,
+
+    /// The last log index whose commit latency is recorded.
+    committed: Option<u64>,
+}
+
+impl ProposalTimes {
+    /// Remember that logs in `[first_index, last_index]` are proposed at `at`.
+    pub(crate) fn propose(&mut self, first_index: u64, last_index: u64, at: Instant) {
+        self.batches.push_back(Batch {
+            last_index,
+            n: last_index - first_index + 1,
+            proposed_at: at,
+        });
+    }
+
+    /// Logs upto `index` are flushed to the local log store.
+    pub(crate) fn appended<NID: NodeId>(&mut self, index: u64, latency: &LatencyRecorder<NID>) {
+        Self::record_upto(&self.batches, &mut self.appended, index, &latency.append);
+    }
+
+    /// Logs upto `index` are committed.
+    pub(crate) fn committed<NID: NodeId>(&mut self, index: u64, latency: &LatencyRecorder<NID>) {
+        Self::record_upto(&self.batches, &mut self.committed, index, &latency.commit);
+    }
+
+    /// Logs upto `index` are applied.
+    pub(crate) fn applied<NID: NodeId>(&mut self, index: u64, latency: &LatencyRecorder<NID>) {
+        let now = Instant::now();
+
+        while let Some(b) = self.batches.front() {
+            if b.last_index > index {
+                break;
+            }
+            latency.apply.record_n(now - b.proposed_at, b.n);
+            self.batches.pop_front();
+        }
+    }
+
+    /// Record the latency of batches that end in `(done, index]`, and move `done` to `index`.
+    fn record_upto(batches: &VecDeque<Batch>, done: &mut Option<u64>, index: u64, histogram: &Histogram) {
+        if Some(index) <= *done {
+            return;
+        }
+
+        let now = Instant::now();
+
+        let start = batches.partition_point(|b| Some(b.last_index) <= *done);
+        let end = batches.partition_point(|b| b.last_index <= index);
+
+        for b in batches.range(start..end) {
+            histogram.record_n(now - b.proposed_at, b.n);
+        }
+
+        *done = Some(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::core::proposal_times::ProposalTimes;
+    use crate::metrics::LatencyRecorder;
+
+    #[test]
+    fn test_proposal_times() -> anyhow::Result<()> {
+        let latency = LatencyRecorder::<u64>::default();
+        let mut p = ProposalTimes::default();
+
+        let at = Instant::now() - Duration::from_millis(10);
+        p.propose(1, 3, at);
+        p.propose(4, 4, at);
+        p.propose(5, 6, at);
+
+        // A batch is recorded when its last log is done.
+        p.appended(3, &latency);
+        p.appended(5, &latency);
+        assert_eq!(4, latency.append.snapshot().count());
+
+        // The same batch is not recorded twice.
+        p.appended(4, &latency);
+        p.appended(6, &latency);
+        assert_eq!(6, latency.append.snapshot().count());
+
+        p.committed(6, &latency);
+        assert_eq!(6, latency.commit.snapshot().count());
+
+        p.applied(4, &latency);
+        assert_eq!(4, latency.apply.snapshot().count());
+        assert_eq!(1, p.batches.len());
+
+        assert!(latency.apply.snapshot().percentile(50.0).unwrap() >= Duration::from_millis(10));
+
+        Ok(())
+    }
+}
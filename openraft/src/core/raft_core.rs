@@ -1,11 +1,13 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::Display;
+use std::io;
 use std::mem::swap;
 use std::pin::Pin;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use anyerror::AnyError;
 use futures::future::abortable;
 use futures::future::select;
 use futures::future::Either;
@@ -16,6 +18,7 @@ use maplit::btreeset;
 use pin_utils::pin_mut;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::oneshot::error::TryRecvError;
 use tokio::sync::watch;
 use tokio::time::timeout;
 use tokio::time::Duration;
@@ -25,49 +28,72 @@ use tracing::Level;
 use tracing::Span;
 
 use crate::config::Config;
+use crate::config::LogStats;
 use crate::config::RuntimeConfig;
-use crate::config::SnapshotPolicy;
 use crate::core::replication_lag;
+use crate::core::ApplyResult;
 use crate::core::Expectation;
+use crate::core::ProposalTimes;
 use crate::core::ServerState;
 use crate::core::SnapshotResult;
 use crate::core::SnapshotState;
+use crate::core::StateMachineHandle;
 use crate::core::VoteWiseTime;
 use crate::engine::Command;
 use crate::engine::Engine;
+use crate::engine::EngineConfig;
 use crate::entry::EntryRef;
 use crate::error::AddLearnerError;
+use crate::error::AlreadyVoter;
 use crate::error::ChangeMembershipError;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
 use crate::error::EmptyMembership;
 use crate::error::ExtractFatal;
 use crate::error::Fatal;
+use crate::error::ForceResetMembershipError;
 use crate::error::ForwardToLeader;
+use crate::error::ImportSnapshotError;
 use crate::error::InProgress;
 use crate::error::InitializeError;
+use crate::error::IsWitness;
+use crate::error::LeaderAlive;
 use crate::error::LearnerIsLagging;
 use crate::error::LearnerNotFound;
 use crate::error::NetworkError;
+use crate::error::NotAVoter;
+use crate::error::NotInMembers;
+use crate::error::NotMostUpToDate;
 use crate::error::QuorumNotEnough;
 use crate::error::RPCError;
 use crate::error::Timeout;
+use crate::error::TransferLeaderError;
 use crate::error::VoteError;
+use crate::error::VoterRejected;
+use crate::error::VoterUnreachable;
+use crate::event::EventSender;
+use crate::event::RaftEvent;
+use crate::metrics::AddTarget;
+use crate::metrics::LatencyRecorder;
 use crate::metrics::RaftMetrics;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::SnapshotTransferCounter;
 use crate::metrics::UpdateMatchedLogId;
+use crate::metrics::UpdateReplicationStatus;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Progress;
 use crate::quorum::QuorumSet;
 use crate::raft::AddLearnerResponse;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::ClientWriteManyTx;
 use crate::raft::ClientWriteResponse;
 use crate::raft::ClientWriteTx;
 use crate::raft::ExternalCommand;
 use crate::raft::RaftAddLearnerTx;
 use crate::raft::RaftMsg;
 use crate::raft::RaftRespTx;
+use crate::raft::TimeoutNowRequest;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::LogStateReader;
@@ -78,22 +104,29 @@ use crate::replication::ReplicationCore;
 use crate::replication::ReplicationHandle;
 use crate::replication::ReplicationSessionId;
 use crate::runtime::RaftRuntime;
+use crate::storage::LogFlushed;
 use crate::storage::RaftSnapshotBuilder;
 use crate::versioned::Updatable;
 use crate::versioned::Versioned;
 use crate::ChangeMembers;
 use crate::Entry;
 use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
+use crate::NodeId;
 use crate::RPCTypes;
+use crate::RaftLogStorage;
 use crate::RaftNetwork;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
 use crate::SnapshotId;
+use crate::SnapshotMeta;
 use crate::StorageError;
+use crate::StorageIOError;
 use crate::Update;
 use crate::Vote;
 
@@ -101,9 +134,6 @@ use crate::Vote;
 ///
 /// It is created when RaftCore enters leader state, and will be dropped when it quits leader state.
 pub(crate) struct LeaderData<C: RaftTypeConfig> {
-    /// Channels to send result back to client when logs are committed.
-    pub(crate) client_resp_channels: BTreeMap<u64, ClientWriteTx<C, C::NodeId, C::Node>>,
-
     /// A mapping of node IDs the replication state of the target node.
     // TODO(xp): make it a field of RaftCore. it does not have to belong to leader.
     //           It requires the Engine to emit correct add/remove replication commands
@@ -114,21 +144,65 @@ pub(crate) struct LeaderData<C: RaftTypeConfig> {
 
     /// The time to send next heartbeat.
     pub(crate) next_heartbeat: Instant,
+
+    /// When this node became leader.
+    ///
+    /// A new leader is not required to have been acknowledged by a quorum before an election timeout since then.
+    pub(crate) established_at: Instant,
+
+    /// The ongoing leadership transfer, if any.
+    pub(crate) transfer_leader: Option<TransferLeader<C::NodeId>>,
+
+    /// When the logs that are not yet applied are proposed, to record the latency of the write stages.
+    pub(crate) proposal_times: ProposalTimes,
+}
+
+/// Results of applying a batch of logs written by `client_write_many()`, collected until the last log is applied.
+pub(crate) struct ClientBatchResp<C: RaftTypeConfig> {
+    /// The index of the first log of the batch.
+    pub(crate) first_index: u64,
+
+    /// Results of the applied logs in this batch.
+    pub(crate) responses: Vec<ClientWriteResponse<C>>,
+
+    pub(crate) tx: ClientWriteManyTx<C, C::NodeId, C::Node>,
+}
+
+/// State of an ongoing leadership transfer.
+pub(crate) struct TransferLeader<NID: NodeId> {
+    /// The node to transfer leadership to.
+    pub(crate) to: NID,
+
+    /// Until when client writes are refused. After that, this leader resumes accepting writes.
+    pub(crate) deadline: Instant,
+
+    /// When the last TimeoutNow request was sent, if any.
+    ///
+    /// The request is resent every `heartbeat_interval` until the target becomes the leader or the deadline passes.
+    pub(crate) timeout_now_sent_at: Option<Instant>,
 }
 
 impl<C: RaftTypeConfig> LeaderData<C> {
     pub(crate) fn new() -> Self {
         Self {
-            client_resp_channels: Default::default(),
             nodes: BTreeMap::new(),
             replication_metrics: Versioned::new(ReplicationMetrics::default()),
             next_heartbeat: Instant::now(),
+            established_at: Instant::now(),
+            transfer_leader: None,
+            proposal_times: ProposalTimes::default(),
         }
     }
 }
 
 /// The core type implementing the Raft protocol.
-pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+pub struct RaftCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// This node's ID.
     pub(crate) id: C::NodeId,
 
@@ -140,31 +214,83 @@ pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<
     /// The `RaftNetworkFactory` implementation.
     pub(crate) network: N,
 
-    /// The `RaftStorage` implementation.
-    pub(crate) storage: S,
+    /// The `RaftLogStorage` implementation.
+    pub(crate) log_store: LS,
+
+    /// The handle to the worker that owns the `RaftStateMachine`.
+    ///
+    /// Applying logs and every other operation on the state machine are sent to the worker.
+    pub(crate) sm_handle: StateMachineHandle<C, SM>,
+
+    /// The last log id that is applied to the state machine.
+    ///
+    /// It falls behind `committed` while the state machine worker is applying logs.
+    pub(crate) last_applied: Option<LogId<C::NodeId>>,
 
-    pub(crate) engine: Engine<C::NodeId, C::Node>,
+    pub(crate) engine: Engine<C::NodeId, C::Node, C::QuorumSet>,
 
     pub(crate) leader_data: Option<LeaderData<C>>,
 
+    /// Channels to send result back to client when logs are applied.
+    ///
+    /// They are installed by a leader. The channels of the committed logs are kept when the leader quits,
+    /// because these logs are still applied, e.g., a leader steps down when a membership without it is committed.
+    pub(crate) client_resp_channels: BTreeMap<u64, ClientWriteTx<C, C::NodeId, C::Node>>,
+
+    /// Channels to send results back to client when a batch of logs written by `client_write_many()` are applied.
+    ///
+    /// It is keyed by the index of the last log of a batch.
+    pub(crate) client_batch_resp_channels: BTreeMap<u64, ClientBatchResp<C>>,
+
     /// The node's current snapshot state.
-    pub(crate) snapshot_state: SnapshotState<C, S::SnapshotData>,
+    pub(crate) snapshot_state: SnapshotState<C, SM::SnapshotData>,
 
-    /// Received snapshot that are ready to install.
-    pub(crate) received_snapshot: BTreeMap<SnapshotId, Box<S::SnapshotData>>,
+    /// Received snapshot that are ready to install, along with their application-specific metadata.
+    pub(crate) received_snapshot: BTreeMap<SnapshotId, (C::SnapshotMetadata, Box<SM::SnapshotData>)>,
+
+    /// The total size of the logs applied since the last snapshot is started.
+    ///
+    /// It is only counted if the snapshot policy needs it.
+    pub(crate) log_bytes_since_snapshot: u64,
+
+    /// When the last snapshot is started, or when this node is started.
+    pub(crate) last_snapshot_at: Instant,
 
     /// The time to elect if a follower does not receive any append-entry message.
     pub(crate) next_election_time: VoteWiseTime<C::NodeId>,
 
-    pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
-    pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, S>>,
+    /// Until when this node has granted a leader lease to the current leader.
+    ///
+    /// It is only used when leader lease is enabled. Before it expires, this node refuses to vote for other nodes.
+    pub(crate) leader_lease_granted_until: Option<Instant>,
+
+    pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+    pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, LS, SM>>,
 
     pub(crate) tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node>>,
 
+    /// The number of snapshots this node has built.
+    pub(crate) snapshots_built: u64,
+
+    /// Counts the snapshot data sent by replication tasks and received by this node.
+    pub(crate) snapshot_transfer: Arc<SnapshotTransferCounter>,
+
+    /// Records the latencies of the write stages and of AppendEntries RPCs.
+    pub(crate) latency: Arc<LatencyRecorder<C::NodeId>>,
+
+    /// Publishes state transition events to subscribers.
+    pub(crate) events: EventSender<C::NodeId, C::Node>,
+
     pub(crate) span: Span,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
+impl<C, N, LS, SM> RaftCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// The main loop of the Raft protocol.
     pub(crate) async fn main(mut self, rx_shutdown: oneshot::Receiver<()>) -> Result<(), Fatal<C::NodeId>> {
         let span = tracing::span!(parent: &self.span, Level::DEBUG, "main");
@@ -181,11 +307,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             if let Err(err) = &res {
                 tracing::error!(?err, "quit RaftCore::main on error");
                 curr.running_state = Err(err.clone());
+                self.events.send(RaftEvent::FatalError { error: err.clone() });
             }
 
             let _ = self.tx_metrics.send(curr);
         }
 
+        tracing::info!("wait for the state machine worker to quit");
+        self.sm_handle.shutdown().await;
+
         res
     }
 
@@ -214,28 +344,33 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     /// agreement from each config group. Most of the time, we will have a single uniform
     /// config group.
     ///
+    /// `value` is sent back via `tx` if the leadership is confirmed.
+    ///
     /// From the spec (§8):
     /// Second, a leader must check whether it has been deposed before processing a read-only
     /// request (its information may be stale if a more recent leader has been elected). Raft
     /// handles this by having the leader exchange heartbeat messages with a majority of the
     /// cluster before responding to read-only requests.
-    #[tracing::instrument(level = "trace", skip(self, tx))]
-    pub(super) async fn handle_check_is_leader_request(
+    #[tracing::instrument(level = "trace", skip(self, value, tx))]
+    pub(super) async fn handle_check_is_leader_request<T>(
         &mut self,
-        tx: RaftRespTx<(), CheckIsLeaderError<C::NodeId, C::Node>>,
+        value: T,
+        tx: RaftRespTx<T, CheckIsLeaderError<C::NodeId, C::Node>>,
     ) {
         // Setup sentinel values to track when we've received majority confirmation of leadership.
 
         let em = &self.engine.state.membership_state.effective;
+        let quorum_set = em.membership.to_quorum_set::<C::QuorumSet>();
         let mut granted = btreeset! {self.id};
 
-        if em.is_quorum(granted.iter()) {
-            let _ = tx.send(Ok(()));
+        if quorum_set.is_quorum(granted.iter()) {
+            let _ = tx.send(Ok(value));
             return;
         }
 
         // Spawn parallel requests, all with the standard timeout for heartbeats.
         let mut pending = FuturesUnordered::new();
+        let sent_at = Instant::now();
 
         let voter_progresses = if let Some(l) = &self.engine.internal_server_state.leading() {
             l.progress
@@ -331,13 +466,17 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.reject_with_forward_to_leader(tx);
                     return;
                 }
+            } else {
+                // The target acknowledged this leader, extend the leader lease.
+                self.engine.update_leader_ack_time(target, sent_at);
             }
 
             granted.insert(target);
 
             let mem = &self.engine.state.membership_state.effective;
-            if mem.is_quorum(granted.iter()) {
-                let _ = tx.send(Ok(()));
+            let quorum_set = mem.membership.to_quorum_set::<C::QuorumSet>();
+            if quorum_set.is_quorum(granted.iter()) {
+                let _ = tx.send(Ok(value));
                 return;
             }
         }
@@ -352,6 +491,182 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         .into()));
     }
 
+    /// Confirm this node is the leader, either by a valid leader lease or by contacting a quorum, and send `value`
+    /// back via `tx`. Otherwise it responds with a `ForwardToLeader` error.
+    async fn check_is_leader<T>(&mut self, value: T, tx: RaftRespTx<T, CheckIsLeaderError<C::NodeId, C::Node>>) {
+        if self.config.enable_leader_lease
+            && self.engine.is_leader_lease_valid(Instant::now(), self.config.leader_lease())
+        {
+            let _ = tx.send(Ok(value));
+        } else if self.engine.is_leader() {
+            self.handle_check_is_leader_request(value, tx).await;
+        } else {
+            self.reject_with_forward_to_leader(tx);
+        }
+    }
+
+    /// Replace the config in use with a validated one, and pass it to every running replication stream.
+    #[tracing::instrument(level = "info", skip_all)]
+    pub(crate) fn update_config(&mut self, config: Arc<Config>) {
+        tracing::info!(config = debug(&config), "update config");
+
+        self.engine.config = EngineConfig::new(self.id, &config);
+
+        if let Some(l) = &self.leader_data {
+            for node in l.nodes.values() {
+                let _ = node.tx_repl.send(Replicate::UpdateConfig(config.clone()));
+            }
+        }
+
+        self.config = config;
+    }
+
+    /// Start to transfer leadership to `to`.
+    ///
+    /// Client writes are refused until `to` becomes the leader or `election_timeout_max` passes.
+    /// Once `to` has replicated all logs, a TimeoutNow request is sent to it to let it elect at once.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn handle_transfer_leader(
+        &mut self,
+        to: C::NodeId,
+        tx: RaftRespTx<(), TransferLeaderError<C::NodeId, C::Node>>,
+    ) {
+        if to == self.id {
+            let _ = tx.send(Ok(()));
+            return;
+        }
+
+        if !self.engine.state.membership_state.is_voter(&to) {
+            let _ = tx.send(Err(NotAVoter { node_id: to }.into()));
+            return;
+        }
+
+        if self.engine.state.membership_state.effective.is_witness(&to) {
+            let _ = tx.send(Err(IsWitness { node_id: to }.into()));
+            return;
+        }
+
+        if let Some(l) = &mut self.leader_data {
+            l.transfer_leader = Some(TransferLeader {
+                to,
+                deadline: Instant::now() + Duration::from_millis(self.config.election_timeout_max),
+                timeout_now_sent_at: None,
+            });
+        } else {
+            unreachable!("it has to be a leader!!!");
+        }
+
+        let _ = tx.send(Ok(()));
+
+        self.try_send_timeout_now().await;
+    }
+
+    /// Return the target node if a leadership transfer is in progress and has not yet timed out.
+    fn transferring_leader_to(&mut self) -> Option<C::NodeId> {
+        let l = self.leader_data.as_mut()?;
+        let t = l.transfer_leader.as_ref()?;
+
+        if Instant::now() >= t.deadline {
+            tracing::info!(to = display(t.to), "transfer leader timeout, resume accepting writes");
+            l.transfer_leader = None;
+            return None;
+        }
+
+        Some(t.to)
+    }
+
+    /// Send a TimeoutNow request to the leadership transfer target, if it has replicated all logs.
+    ///
+    /// The request is resent if no new leader is elected within a `heartbeat_interval`,
+    /// e.g., when the RPC failed, until the transfer deadline.
+    async fn try_send_timeout_now(&mut self) {
+        let to = match self.transferring_leader_to() {
+            None => return,
+            Some(x) => x,
+        };
+
+        // Safe unwrap(): `transferring_leader_to()` returns Some only when there is leader data.
+        let l = self.leader_data.as_mut().unwrap();
+        let t = l.transfer_leader.as_mut().unwrap();
+
+        let ttl = Duration::from_millis(self.config.heartbeat_interval);
+
+        if let Some(sent_at) = t.timeout_now_sent_at {
+            if Instant::now() < sent_at + ttl {
+                return;
+            }
+        }
+
+        let matching = self
+            .engine
+            .internal_server_state
+            .leading()
+            .and_then(|x| x.progress.try_get(&to))
+            .map(|x| x.matching);
+        let last_log_id = self.engine.state.last_log_id().copied();
+
+        if matching != Some(last_log_id) {
+            tracing::debug!(
+                to = display(to),
+                matching = debug(&matching),
+                last_log_id = debug(&last_log_id),
+                "transfer leader: target is not yet up-to-date"
+            );
+            return;
+        }
+
+        t.timeout_now_sent_at = Some(Instant::now());
+
+        let req = TimeoutNowRequest {
+            vote: self.engine.state.vote,
+            last_log_id,
+        };
+
+        // Safe unwrap(): target is a voter
+        let target_node = self.engine.state.membership_state.effective.get_node(&to).unwrap().clone();
+        let mut client = match self.network.new_client(to, &target_node).await {
+            Ok(n) => n,
+            Err(err) => {
+                tracing::error!({error=%err, target=display(to)}, "while sending TimeoutNow");
+                return;
+            }
+        };
+
+        let id = self.id;
+
+        tokio::spawn(
+            async move {
+                let res = timeout(ttl, client.send_timeout_now(req)).await;
+                match res {
+                    Ok(Ok(resp)) => {
+                        tracing::info!(
+                            resp = display(resp.summary()),
+                            target = display(to),
+                            "TimeoutNow response"
+                        );
+                    }
+                    Ok(Err(err)) => {
+                        tracing::error!({error=%err, target=display(to)}, "while sending TimeoutNow");
+                    }
+                    Err(_timeout) => {
+                        let timeout_err = Timeout {
+                            action: RPCTypes::TimeoutNow,
+                            id,
+                            target: to,
+                            timeout: ttl,
+                        };
+                        tracing::error!({error = %timeout_err, target = display(to)}, "timeout");
+                    }
+                }
+            }
+            .instrument(tracing::debug_span!(
+                parent: &Span::current(),
+                "send_timeout_now",
+                target = display(to)
+            )),
+        );
+    }
+
     /// Add a new node to the cluster as a learner, bringing it up-to-speed, and then responding
     /// on the given channel.
     ///
@@ -444,6 +759,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>,
     ) -> Result<(), Fatal<C::NodeId>> {
         let last = self.engine.state.membership_state.effective.membership.get_joint_config().last().unwrap();
+        let add_witnesses = changes.witnesses();
         let members = changes.apply_to(last);
 
         // Ensure cluster will have at least one node.
@@ -463,11 +779,26 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let mem = &self.engine.state.membership_state.effective;
         let curr = mem.membership.clone();
 
+        // A voter can not be turned into a witness, which would silently drop the application data it stores.
+        for node_id in add_witnesses.iter() {
+            if mem.is_voter(node_id) && !mem.is_witness(node_id) {
+                let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(
+                    ChangeMembershipError::AlreadyVoter(AlreadyVoter { node_id: *node_id }),
+                )));
+                return Ok(());
+            }
+        }
+
         let old_members = mem.voter_ids().collect::<BTreeSet<_>>();
         let only_in_new = members.difference(&old_members);
 
         let new_config = curr.next_safe(members.clone(), turn_to_learner);
 
+        // Nodes added as witness are witnesses since the joint config.
+        let mut witnesses = new_config.witnesses().clone();
+        witnesses.extend(add_witnesses);
+        let new_config = new_config.with_witnesses(witnesses);
+
         tracing::debug!(?new_config, "new_config");
 
         for node_id in only_in_new.clone() {
@@ -565,20 +896,174 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     ) -> Result<LogId<C::NodeId>, Fatal<C::NodeId>> {
         tracing::debug!(payload = display(payload.summary()), "write_entry");
 
-        let mut entry_refs = [EntryRef::new(&payload)];
+        let log_ids = self.write_entries(vec![(payload, resp_tx)]).await?;
+        Ok(log_ids[0])
+    }
+
+    /// Write a batch of log entries to the cluster through raft protocol.
+    ///
+    /// The entries are appended to local store with one storage call, and are assigned consecutive log ids.
+    /// The result of applying every entry is sent to its own `resp_tx`, if it is not `None`.
+    #[tracing::instrument(level = "debug", skip_all, fields(id = display(self.id)))]
+    pub async fn write_entries(
+        &mut self,
+        batch: Vec<(EntryPayload<C>, Option<ClientWriteTx<C, C::NodeId, C::Node>>)>,
+    ) -> Result<Vec<LogId<C::NodeId>>, Fatal<C::NodeId>> {
+        tracing::debug!(n = batch.len(), "write_entries");
+
+        let (payloads, resp_txs): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+
+        self.leader_append_payloads(payloads, |core, log_ids| {
+            for (log_id, resp_tx) in log_ids.iter().zip(resp_txs) {
+                if let Some(tx) = resp_tx {
+                    core.client_resp_channels.insert(log_id.index, tx);
+                }
+            }
+        })
+        .await
+    }
+
+    /// Append payloads as consecutive log entries and replicate them.
+    ///
+    /// `install_resp` is called with the assigned log ids before the entries are committed, to install the channels
+    /// for sending back the results.
+    async fn leader_append_payloads<F>(
+        &mut self,
+        payloads: Vec<EntryPayload<C>>,
+        install_resp: F,
+    ) -> Result<Vec<LogId<C::NodeId>>, Fatal<C::NodeId>>
+    where
+        F: FnOnce(&mut Self, &[LogId<C::NodeId>]),
+    {
+        let proposed_at = Instant::now();
+
+        let mut entry_refs = payloads.iter().map(EntryRef::new).collect::<Vec<_>>();
         // TODO: it should returns membership config error etc. currently this is done by the caller.
         self.engine.leader_append_entries(&mut entry_refs);
 
+        let log_ids = entry_refs.iter().map(|ent| *ent.get_log_id()).collect::<Vec<_>>();
+
         // Install callback channels.
-        if let Some(tx) = resp_tx {
-            if let Some(l) = &mut self.leader_data {
-                l.client_resp_channels.insert(entry_refs[0].log_id.index, tx);
+        if self.leader_data.is_some() {
+            install_resp(self, &log_ids);
+        }
+
+        if let Some(l) = &mut self.leader_data {
+            if let (Some(first), Some(last)) = (log_ids.first(), log_ids.last()) {
+                l.proposal_times.propose(first.index, last.index, proposed_at);
             }
         }
 
         self.run_engine_commands(&entry_refs).await?;
 
-        Ok(*entry_refs[0].get_log_id())
+        Ok(log_ids)
+    }
+
+    /// Write a batch of client requests submitted by `client_write_many()` if this node is the leader, otherwise
+    /// reject the batch as a whole.
+    ///
+    /// The results are sent back with one `tx` when all of the entries are applied.
+    #[tracing::instrument(level = "debug", skip_all, fields(n = payloads.len()))]
+    async fn write_client_many_requests(
+        &mut self,
+        payloads: Vec<EntryPayload<C>>,
+        tx: ClientWriteManyTx<C, C::NodeId, C::Node>,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        if let Some(to) = self.transferring_leader_to() {
+            let _ = tx.send(Err(ForwardToLeader {
+                leader_id: Some(to),
+                leader_node: self.get_leader_node(Some(to)),
+            }
+            .into()));
+        } else if !self.engine.is_leader() {
+            self.reject_with_forward_to_leader(tx);
+        } else if payloads.is_empty() {
+            let _ = tx.send(Ok(vec![]));
+        } else {
+            let n = payloads.len();
+            self.leader_append_payloads(payloads, |core, log_ids| {
+                let first_index = log_ids[0].index;
+                let last_index = log_ids[n - 1].index;
+                core.client_batch_resp_channels.insert(last_index, ClientBatchResp {
+                    first_index,
+                    responses: Vec::with_capacity(n),
+                    tx,
+                });
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle a client write request, together with the client write requests queued after it in `rx_api`.
+    ///
+    /// Queued client write requests are drained, bounded by `max_write_batch_entries` and `max_write_batch_bytes`,
+    /// and are appended to the log as one batch, so that the storage is called once for all of them.
+    ///
+    /// Draining stops at the first message that is not a client write request, or at the first request that would
+    /// exceed `max_write_batch_bytes`. It is returned to be handled next.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn handle_client_write_requests(
+        &mut self,
+        payload: EntryPayload<C>,
+        tx: ClientWriteTx<C, C::NodeId, C::Node>,
+    ) -> Result<Option<RaftMsg<C, N, LS, SM>>, Fatal<C::NodeId>> {
+        let mut batch_size = payload.estimated_size();
+        let mut batch = vec![(payload, tx)];
+        let mut next_msg = None;
+
+        while (batch.len() as u64) < self.config.max_write_batch_entries {
+            match self.rx_api.try_recv() {
+                Ok(RaftMsg::ClientWriteRequest { payload, tx }) => {
+                    let size = payload.estimated_size();
+
+                    // It does not fit in this batch, it starts the next one.
+                    if batch_size + size > self.config.max_write_batch_bytes {
+                        next_msg = Some(RaftMsg::ClientWriteRequest { payload, tx });
+                        break;
+                    }
+
+                    batch.push((payload, tx));
+                    batch_size += size;
+                }
+                Ok(msg) => {
+                    next_msg = Some(msg);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        self.write_client_requests(batch).await?;
+
+        Ok(next_msg)
+    }
+
+    /// Write a batch of client write requests if this node is the leader, otherwise reject them all.
+    #[tracing::instrument(level = "debug", skip_all, fields(n = batch.len()))]
+    async fn write_client_requests(
+        &mut self,
+        batch: Vec<(EntryPayload<C>, ClientWriteTx<C, C::NodeId, C::Node>)>,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        if let Some(to) = self.transferring_leader_to() {
+            for (_payload, tx) in batch {
+                let _ = tx.send(Err(ForwardToLeader {
+                    leader_id: Some(to),
+                    leader_node: self.get_leader_node(Some(to)),
+                }
+                .into()));
+            }
+        } else if self.engine.is_leader() {
+            let batch = batch.into_iter().map(|(payload, tx)| (payload, Some(tx))).collect();
+            self.write_entries(batch).await?;
+        } else {
+            for (_payload, tx) in batch {
+                self.reject_with_forward_to_leader(tx);
+            }
+        }
+
+        Ok(())
     }
 
     /// Flush cached changes of metrics to notify metrics watchers with updated metrics.
@@ -620,8 +1105,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             // --- data ---
             current_term: self.engine.state.vote.term,
             last_log_index: self.engine.state.last_log_id().map(|id| id.index),
-            last_applied: self.engine.state.committed,
+            last_applied: self.last_applied,
             snapshot: self.engine.state.snapshot_meta.last_log_id,
+            snapshot_transfer: self.snapshot_transfer.metrics(),
+
+            // --- counters ---
+            elections: self.engine.elections,
+            snapshots_built: self.snapshots_built,
 
             // --- cluster ---
             state: self.engine.state.server_state,
@@ -643,28 +1133,296 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         tracing::debug!("report_metrics: {}", m.summary());
         let res = self.tx_metrics.send(m);
 
-        if let Err(err) = res {
-            tracing::error!(error=%err, id=display(self.id), "error reporting metrics");
-        }
+        if let Err(err) = res {
+            tracing::error!(error=%err, id=display(self.id), "error reporting metrics");
+        }
+    }
+
+    /// Handle the admin command `initialize`.
+    ///
+    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,0)`.
+    /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) async fn handle_initialize(
+        &mut self,
+        member_nodes: BTreeMap<C::NodeId, C::Node>,
+    ) -> Result<(), InitializeError<C::NodeId, C::Node>> {
+        let membership = Membership::from(member_nodes);
+        let payload = EntryPayload::<C>::Membership(membership);
+
+        let mut entry_refs = [EntryRef::new(&payload)];
+        self.engine.initialize(&mut entry_refs)?;
+        self.run_engine_commands(&entry_refs).await?;
+
+        Ok(())
+    }
+
+    /// Check if a snapshot can be imported, and returns a handle for the application to write the snapshot data into.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn begin_importing_snapshot(
+        &mut self,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<Box<SM::SnapshotData>, ImportSnapshotError<C::NodeId>> {
+        tracing::info!(meta = display(meta.summary()), "begin importing snapshot");
+
+        self.engine.check_initialize()?;
+        self.sm_handle.check_snapshot_meta(meta).await??;
+
+        let data = self.sm_handle.begin_receiving_snapshot().await?;
+        Ok(data)
+    }
+
+    /// Install an imported snapshot on a pristine node, in the same way as installing one received from the leader.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_import_snapshot(
+        &mut self,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        data: Box<SM::SnapshotData>,
+    ) -> Result<(), ImportSnapshotError<C::NodeId>> {
+        tracing::info!(meta = display(meta.summary()), "import snapshot");
+
+        let (meta, metadata) = meta.split();
+        let snapshot_id = meta.snapshot_id.clone();
+
+        self.engine.import_snapshot(meta)?;
+
+        // Buffer the snapshot data for `Command::InstallSnapshot` or `Command::CancelSnapshot`.
+        self.received_snapshot.insert(snapshot_id, (metadata, data));
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        Ok(())
+    }
+
+    /// Check if this node is allowed to force a new membership of `voters`, and build the new membership.
+    ///
+    /// Learners of the current membership are kept as learners in the new membership.
+    fn check_force_reset_membership(
+        &self,
+        voters: &BTreeSet<C::NodeId>,
+    ) -> Result<Membership<C::NodeId, C::Node>, ForceResetMembershipError<C::NodeId, C::Node>> {
+        if voters.is_empty() {
+            return Err(EmptyMembership {}.into());
+        }
+
+        let em = &self.engine.state.membership_state.effective;
+
+        for node_id in voters.iter() {
+            if !em.contains(node_id) {
+                return Err(LearnerNotFound { node_id: *node_id }.into());
+            }
+        }
+
+        if !em.membership.is_voter(&self.id) {
+            return Err(NotInMembers {
+                node_id: self.id,
+                membership: em.membership.clone(),
+            }
+            .into());
+        }
+
+        if em.is_witness(&self.id) {
+            return Err(IsWitness { node_id: self.id }.into());
+        }
+
+        if self.engine.is_leader_lease_valid(Instant::now(), self.config.leader_lease()) {
+            return Err(LeaderAlive { leader_id: self.id }.into());
+        }
+
+        let vote = &self.engine.state.vote;
+        if let Some(until) = self.leader_lease_granted_until {
+            if self.config.enable_leader_lease && vote.committed && vote.node_id != self.id && Instant::now() < until {
+                return Err(LeaderAlive {
+                    leader_id: vote.node_id,
+                }
+                .into());
+            }
+        }
+
+        // Without a lease, a follower still knows the leader is alive if its election timer has not expired.
+        if vote.committed && vote.node_id != self.id {
+            if let Some(until) = self.next_election_time.get_time(vote) {
+                if Instant::now() < until {
+                    return Err(LeaderAlive {
+                        leader_id: vote.node_id,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let mut nodes = BTreeMap::new();
+        for node_id in voters.iter().copied().chain(em.learner_ids()) {
+            // Safe unwrap(): every node id is checked above or is a learner in the current membership.
+            nodes.insert(node_id, em.get_node(&node_id).unwrap().clone());
+        }
+
+        let witnesses = em.membership.witnesses().clone();
+        let membership = Membership::new(vec![voters.clone()], nodes).with_witnesses(witnesses);
+
+        if !membership.is_voter(&self.id) {
+            return Err(NotInMembers {
+                node_id: self.id,
+                membership,
+            }
+            .into());
+        }
+
+        Ok(membership)
+    }
+
+    /// Send PreVote requests to the other voters in `voters`, before forcing a new membership of them.
+    ///
+    /// A PreVote request does not change the state of the receiver, while its response tells the vote and the last log
+    /// id of it. The responses are sent back with `RaftMsg::ForceResetMembershipChecked`, or an error is returned to
+    /// `tx` at once if any of the voters does not respond.
+    #[tracing::instrument(level = "info", skip(self, tx))]
+    pub(crate) async fn spawn_force_reset_membership_check(
+        &mut self,
+        voters: BTreeSet<C::NodeId>,
+        tx: RaftRespTx<LogId<C::NodeId>, ForceResetMembershipError<C::NodeId, C::Node>>,
+    ) {
+        if let Err(e) = self.check_force_reset_membership(&voters) {
+            tracing::warn!(error = display(&e), "can not force reset membership");
+            let _ = tx.send(Err(e));
+            return;
+        }
+
+        let vote_req = VoteRequest::new(
+            Vote::new(self.engine.next_term(), self.id),
+            self.engine.state.last_log_id().copied(),
+        );
+
+        let ttl = Duration::from_millis(self.config.election_timeout_min);
+        let id = self.id;
+
+        let mut pending = FuturesUnordered::new();
+
+        for target in voters.iter().copied() {
+            if target == self.id {
+                continue;
+            }
+
+            // Safe unwrap(): target is checked to be in the membership.
+            let target_node = self.engine.state.membership_state.effective.get_node(&target).unwrap().clone();
+            let mut client = match self.network.new_client(target, &target_node).await {
+                Ok(n) => n,
+                Err(err) => {
+                    let _ = tx.send(Err(VoterUnreachable {
+                        target,
+                        source: AnyError::new(&err),
+                    }
+                    .into()));
+                    return;
+                }
+            };
+
+            let req = vote_req.clone();
+
+            pending.push(async move {
+                let res = match timeout(ttl, client.send_pre_vote(req)).await {
+                    Ok(Ok(resp)) => Ok(resp),
+                    Ok(Err(err)) => Err(AnyError::new(&err)),
+                    Err(_timeout) => Err(AnyError::new(&Timeout {
+                        action: RPCTypes::PreVote,
+                        id,
+                        target,
+                        timeout: ttl,
+                    })),
+                };
+                (target, res)
+            });
+        }
+
+        let tx_api = self.tx_api.clone();
+
+        tokio::spawn(
+            async move {
+                let mut responses = BTreeMap::new();
+
+                while let Some((target, res)) = pending.next().await {
+                    match res {
+                        Ok(resp) => {
+                            responses.insert(target, resp);
+                        }
+                        Err(source) => {
+                            tracing::error!(
+                                error = display(&source),
+                                target = display(target),
+                                "voter is unreachable"
+                            );
+                            let _ = tx.send(Err(VoterUnreachable { target, source }.into()));
+                            return;
+                        }
+                    }
+                }
+
+                let _ = tx_api.send(RaftMsg::ForceResetMembershipChecked { voters, responses, tx });
+            }
+            .instrument(tracing::debug_span!(
+                parent: &Span::current(),
+                "force_reset_membership_check"
+            )),
+        );
     }
 
-    /// Handle the admin command `initialize`.
+    /// Force this node to become the leader of a new membership of `voters`, if it has the most up-to-date logs and
+    /// every other voter granted the PreVote request.
     ///
-    /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(0,0)`.
-    /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub(crate) async fn handle_initialize(
+    /// `responses` are the PreVote responses from the other voters.
+    #[tracing::instrument(level = "info", skip(self))]
+    pub(crate) async fn handle_force_reset_membership(
         &mut self,
-        member_nodes: BTreeMap<C::NodeId, C::Node>,
-    ) -> Result<(), InitializeError<C::NodeId, C::Node>> {
-        let membership = Membership::from(member_nodes);
-        let payload = EntryPayload::<C>::Membership(membership);
+        voters: BTreeSet<C::NodeId>,
+        responses: BTreeMap<C::NodeId, VoteResponse<C::NodeId>>,
+    ) -> Result<LogId<C::NodeId>, ForceResetMembershipError<C::NodeId, C::Node>> {
+        // The state may have changed since the check before sending PreVote requests.
+        let membership = self.check_force_reset_membership(&voters)?;
+
+        let last_log_id = self.engine.state.last_log_id().copied();
+        let mut term = self.engine.next_term();
+
+        for (target, resp) in responses.iter() {
+            if resp.last_log_id > last_log_id {
+                return Err(NotMostUpToDate {
+                    target: *target,
+                    target_last_log_id: resp.last_log_id,
+                    last_log_id,
+                }
+                .into());
+            }
+
+            // A voter rejects if it is a leader, or it has heard from a live leader, or it has seen a greater vote.
+            if !resp.vote_granted {
+                if resp.vote.committed && resp.vote.node_id != self.id {
+                    return Err(LeaderAlive {
+                        leader_id: resp.vote.node_id,
+                    }
+                    .into());
+                }
+                return Err(VoterRejected {
+                    target: *target,
+                    vote: resp.vote,
+                }
+                .into());
+            }
 
+            term = std::cmp::max(term, resp.vote.term + 1);
+        }
+
+        tracing::warn!(
+            membership = display(membership.summary()),
+            term = display(term),
+            "force reset membership"
+        );
+
+        let payload = EntryPayload::<C>::Membership(membership);
         let mut entry_refs = [EntryRef::new(&payload)];
-        self.engine.initialize(&mut entry_refs)?;
+        self.engine.force_reset_membership(term, &mut entry_refs);
+
+        let log_id = *entry_refs[0].get_log_id();
         self.run_engine_commands(&entry_refs).await?;
 
-        Ok(())
+        Ok(log_id)
     }
 
     /// Set a value for the next election timeout.
@@ -703,6 +1461,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         // TODO: add building-session id to identify different building
         match result {
             SnapshotResult::Ok(meta) => {
+                self.snapshots_built += 1;
+                self.events.send(RaftEvent::SnapshotBuilt { meta: meta.clone() });
                 self.engine.finish_building_snapshot(meta);
                 self.run_engine_commands::<Entry<C>>(&[]).await?;
             }
@@ -730,19 +1490,40 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return;
         }
 
-        let SnapshotPolicy::LogsSinceLast(threshold) = &self.config.snapshot_policy;
-
         if !force {
-            // If we are below the threshold, then there is nothing to do.
-            if self.engine.state.committed.next_index() - self.engine.state.snapshot_meta.last_log_id.next_index()
-                < *threshold
-            {
+            // A custom policy decides with the metrics, which have to reflect the latest state.
+            if self.config.snapshot_policy.needs_metrics() && self.engine.output.metrics_flags.changed() {
+                self.flush_metrics();
+            }
+
+            let stats = LogStats {
+                logs_since_last: self
+                    .last_applied
+                    .next_index()
+                    .saturating_sub(self.engine.state.snapshot_meta.last_log_id.next_index()),
+                log_bytes_since_last: self.log_bytes_since_snapshot,
+                since_last: self.last_snapshot_at.elapsed(),
+            };
+
+            let should_snapshot = self.config.snapshot_policy.should_snapshot(&stats, &*self.tx_metrics.borrow());
+
+            // If the policy is not satisfied, then there is nothing to do.
+            if !should_snapshot {
                 return;
             }
         }
 
+        self.log_bytes_since_snapshot = 0;
+        self.last_snapshot_at = Instant::now();
+
         // At this point, we are clear to begin a new compaction process.
-        let mut builder = self.storage.get_snapshot_builder().await;
+        let mut builder = match self.sm_handle.get_snapshot_builder().await {
+            Ok(b) => b,
+            Err(err) => {
+                tracing::error!(error = display(&err), "fail to get snapshot builder");
+                return;
+            }
+        };
 
         let (fu, abort_handle) = abortable(async move { builder.build_snapshot().await });
 
@@ -754,7 +1535,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     Ok(res) => match res {
                         Ok(snapshot) => {
                             let _ = tx_api.send(RaftMsg::BuildingSnapshotResult {
-                                result: SnapshotResult::Ok(snapshot.meta),
+                                result: SnapshotResult::Ok(snapshot.meta.split().0),
                             });
                         }
                         Err(err) => {
@@ -811,6 +1592,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
         let id = self.engine.state.vote.node_id;
 
+        if id == self.id && !self.engine.is_leader() {
+            // This node has stepped down but still holds the vote.
+            return None;
+        }
+
         // TODO: `is_voter()` is slow, maybe cache `current_leader`,
         //       e.g., only update it when membership or vote changes
         if self.engine.state.membership_state.effective.is_voter(&id) {
@@ -830,52 +1616,58 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         self.engine.state.membership_state.effective.get_node(&leader_id).cloned()
     }
 
+    /// Send committed logs in range `[since, upto]` to the state machine worker to apply them to the state machine.
+    ///
+    /// The result is sent back with `RaftMsg::ApplyResult` when they are applied.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) async fn apply_to_state_machine(
-        &mut self,
-        since: u64,
-        upto_index: u64,
-    ) -> Result<(), StorageError<C::NodeId>> {
-        tracing::debug!(upto_index = display(upto_index), "apply_to_state_machine");
-
-        let end = upto_index + 1;
+    pub(crate) async fn apply_to_state_machine(&mut self, since: u64, upto: LogId<C::NodeId>) {
+        tracing::debug!(upto = display(upto), "apply_to_state_machine");
 
         debug_assert!(
-            since <= end,
+            since <= upto.index + 1,
             "last_applied index {} should <= committed index {}",
             since,
-            end
+            upto.index + 1
         );
 
-        if since == end {
-            return Ok(());
+        if since == upto.index + 1 {
+            return;
         }
 
-        let entries = self.storage.get_log_entries(since..end).await?;
-        tracing::debug!(entries=%entries.as_slice().summary(), "about to apply");
+        self.sm_handle.apply(since, upto).await;
+    }
 
-        let entry_refs = entries.iter().collect::<Vec<_>>();
-        let apply_results = self.storage.apply_to_state_machine(&entry_refs).await?;
+    /// Handle the result of applying logs by the state machine worker.
+    ///
+    /// It updates `last_applied` and sends the results back to the clients.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_apply_result(&mut self, res: ApplyResult<C>) {
+        tracing::debug!(res = display(res.summary()), "handle_apply_result");
 
-        let last_applied = entries[entries.len() - 1].log_id;
-        tracing::debug!(last_applied = display(last_applied), "update last_applied");
+        if Some(res.last_applied) > self.last_applied {
+            self.last_applied = Some(res.last_applied);
+            self.engine.output.metrics_flags.set_data_changed();
+        }
 
-        if let Some(l) = &mut self.leader_data {
-            let mut results = apply_results.into_iter();
+        if self.config.snapshot_policy.needs_log_bytes() {
+            self.log_bytes_since_snapshot += res.entries.iter().map(|e| e.estimated_size()).sum::<u64>();
+        }
 
-            for log_index in since..end {
-                let tx = l.client_resp_channels.remove(&log_index);
+        if let Some(l) = &mut self.leader_data {
+            l.proposal_times.applied(res.last_applied.index, &self.latency);
+        }
 
-                let i = log_index - since;
-                let entry = &entries[i as usize];
-                let apply_res = results.next().unwrap();
+        for (entry, apply_res) in res.entries.iter().zip(res.results) {
+            let tx = self.client_resp_channels.remove(&entry.log_id.index);
 
+            if tx.is_some() {
                 Self::send_response(entry, apply_res, tx);
+            } else {
+                Self::send_batch_response(&mut self.client_batch_resp_channels, entry, apply_res);
             }
         }
 
         self.trigger_snapshot_if_needed(false).await;
-        Ok(())
     }
 
     /// Send result of applying a log entry to its client.
@@ -888,23 +1680,50 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             Some(x) => x,
         };
 
+        let res = Ok(Self::client_write_response(entry, resp));
+
+        let send_res = tx.send(res);
+        tracing::debug!(
+            "send client response through tx, send_res is error: {}",
+            send_res.is_err()
+        );
+    }
+
+    /// Collect the result of applying a log entry that belongs to a batch written by `client_write_many()`.
+    ///
+    /// All of the results of the batch are sent to its client when the last entry of the batch is applied.
+    pub(super) fn send_batch_response(batches: &mut BTreeMap<u64, ClientBatchResp<C>>, entry: &Entry<C>, resp: C::R) {
+        let index = entry.log_id.index;
+
+        let last_index = match batches.range(index..).next() {
+            Some((last_index, b)) if b.first_index <= index => *last_index,
+            _ => return,
+        };
+
+        // Safe unwrap(): the batch is just found
+        let b = batches.get_mut(&last_index).unwrap();
+        b.responses.push(Self::client_write_response(entry, resp));
+
+        if index == last_index {
+            let b = batches.remove(&last_index).unwrap();
+            tracing::debug!(n = b.responses.len(), "send batch client responses through tx");
+
+            let _ = b.tx.send(Ok(b.responses));
+        }
+    }
+
+    fn client_write_response(entry: &Entry<C>, resp: C::R) -> ClientWriteResponse<C> {
         let membership = if let EntryPayload::Membership(ref c) = entry.payload {
             Some(c.clone())
         } else {
             None
         };
 
-        let res = Ok(ClientWriteResponse {
+        ClientWriteResponse {
             log_id: entry.log_id,
             data: resp,
             membership,
-        });
-
-        let send_res = tx.send(res);
-        tracing::debug!(
-            "send client response through tx, send_res is error: {}",
-            send_res.is_err()
-        );
+        }
     }
 
     /// Spawn a new replication stream returning its replication state handle.
@@ -919,19 +1738,29 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         let target_node = self.engine.state.membership_state.effective.get_node(&target).unwrap();
 
         let membership_log_id = self.engine.state.membership_state.effective.log_id;
-        let network = self.network.new_client(target, target_node).await?;
+
+        // Every inflight AppendEntries request uses its own network client.
+        let mut networks = Vec::with_capacity(self.config.max_inflight_append_requests as usize);
+        for _ in 0..self.config.max_inflight_append_requests {
+            networks.push(self.network.new_client(target, target_node).await?);
+        }
 
         let session_id = ReplicationSessionId::new(self.engine.state.vote, membership_log_id);
 
-        Ok(ReplicationCore::<C, N, S>::spawn(
+        let is_witness = self.engine.state.membership_state.effective.is_witness(&target);
+
+        Ok(ReplicationCore::<C, N, LS, SM>::spawn(
             target,
+            is_witness,
             session_id,
             self.config.clone(),
             self.engine.state.committed,
             progress_entry,
-            network,
-            self.storage.get_log_reader().await,
+            networks,
+            self.log_store.get_log_reader().await,
             self.tx_api.clone(),
+            self.snapshot_transfer.clone(),
+            self.latency.append_entries(target),
             tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(self.id), target=display(target)),
         ))
     }
@@ -967,7 +1796,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
+impl<C, N, LS, SM> RaftCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) async fn run_engine_commands<'e, Ent>(
         &mut self,
@@ -986,11 +1821,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         }
 
         let mut curr = 0;
-        let mut commands = vec![];
-        swap(&mut self.engine.output.commands, &mut commands);
-        for cmd in commands {
-            tracing::debug!("run command: {:?}", cmd);
-            self.run_command(input_entries, &mut curr, &cmd).await?;
+
+        // Running a command may output more commands, e.g., flushing logs commits them.
+        while !self.engine.output.commands.is_empty() {
+            let mut commands = vec![];
+            swap(&mut self.engine.output.commands, &mut commands);
+            for cmd in commands {
+                tracing::debug!("run command: {:?}", cmd);
+                self.run_command(input_entries, &mut curr, &cmd).await?;
+            }
         }
 
         Ok(())
@@ -1002,7 +1841,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         loop {
             self.flush_metrics();
 
-            let msg_res: Result<RaftMsg<C, N, S>, &str> = {
+            let msg_res: Result<RaftMsg<C, N, LS, SM>, &str> = {
                 let recv = self.rx_api.recv();
                 pin_mut!(recv);
 
@@ -1018,6 +1857,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             };
 
             match msg_res {
+                Ok(RaftMsg::ClientWriteRequest { payload, tx }) => {
+                    // Client write requests are handled in batch.
+                    let mut next_msg = self.handle_client_write_requests(payload, tx).await?;
+                    while let Some(msg) = next_msg.take() {
+                        match msg {
+                            // A request that did not fit in the last batch starts the next one.
+                            RaftMsg::ClientWriteRequest { payload, tx } => {
+                                next_msg = self.handle_client_write_requests(payload, tx).await?;
+                            }
+                            msg => self.handle_api_msg(msg).await?,
+                        }
+                    }
+                }
                 Ok(msg) => self.handle_api_msg(msg).await?,
                 Err(reason) => {
                     tracing::info!(reason);
@@ -1028,11 +1880,21 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     }
 
     /// Spawn parallel vote requests to all cluster members.
-    #[tracing::instrument(level = "trace", skip_all, fields(vote=vote_req.summary()))]
-    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C::NodeId>) {
+    ///
+    /// If `pre_vote` is true, PreVote requests are sent instead.
+    #[tracing::instrument(level = "trace", skip_all, fields(vote=vote_req.summary(), pre_vote=pre_vote))]
+    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C::NodeId>, pre_vote: bool) {
         let members = self.engine.state.membership_state.effective.voter_ids();
 
-        let vote = vote_req.vote;
+        // The vote of this node when sending the requests.
+        // A PreVote request carries the vote for the next term, which is not yet the vote of this node.
+        let vote = if pre_vote {
+            self.engine.state.vote
+        } else {
+            vote_req.vote
+        };
+
+        let action = if pre_vote { RPCTypes::PreVote } else { RPCTypes::Vote };
 
         for target in members {
             if target == self.id {
@@ -1056,15 +1918,21 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             let ttl = Duration::from_millis(self.config.election_timeout_min);
             let id = self.id;
 
+            let action = action.clone();
+
             let _ = tokio::spawn(
                 async move {
-                    let tm_res = timeout(ttl, client.send_vote(req)).await;
+                    let tm_res = if pre_vote {
+                        timeout(ttl, client.send_pre_vote(req)).await
+                    } else {
+                        timeout(ttl, client.send_vote(req)).await
+                    };
                     let res = match tm_res {
                         Ok(res) => res,
 
                         Err(_timeout) => {
                             let timeout_err = Timeout {
-                                action: RPCTypes::Vote,
+                                action,
                                 id,
                                 target,
                                 timeout: ttl,
@@ -1076,7 +1944,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
                     match res {
                         Ok(resp) => {
-                            let _ = tx.send(RaftMsg::VoteResponse { target, resp, vote });
+                            let msg = if pre_vote {
+                                RaftMsg::PreVoteResponse { target, resp, vote }
+                            } else {
+                                RaftMsg::VoteResponse { target, resp, vote }
+                            };
+                            let _ = tx.send(msg);
                         }
                         Err(err) => tracing::error!({error=%err, target=display(target)}, "while requesting vote"),
                     }
@@ -1097,12 +1970,116 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     ) -> Result<VoteResponse<C::NodeId>, VoteError<C::NodeId>> {
         tracing::debug!(req = display(req.summary()), "handle_vote_request");
 
+        if let Some(resp) = self.reject_within_granted_lease(&req) {
+            return Ok(resp);
+        }
+
         let resp = self.engine.handle_vote_req(req);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
         Ok(resp)
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) fn handle_pre_vote_request(&mut self, req: VoteRequest<C::NodeId>) -> VoteResponse<C::NodeId> {
+        tracing::debug!(req = display(req.summary()), "handle_pre_vote_request");
+
+        if let Some(resp) = self.reject_within_granted_lease(&req) {
+            return resp;
+        }
+
+        if let Some(resp) = self.reject_with_live_leader(&req) {
+            return resp;
+        }
+
+        self.engine.handle_pre_vote_req(req)
+    }
+
+    /// Build a rejecting PreVote response if this node has heard from the current leader within the election timeout.
+    ///
+    /// The election timer of a follower is reset every time it receives an AppendEntries request from the leader. A
+    /// node that would not start an election itself does not help another node to depose a live leader.
+    fn reject_with_live_leader(&self, req: &VoteRequest<C::NodeId>) -> Option<VoteResponse<C::NodeId>> {
+        let vote = &self.engine.state.vote;
+        if !vote.committed || vote.node_id == self.id || req.vote.node_id == vote.node_id {
+            return None;
+        }
+
+        let until = self.next_election_time.get_time(vote)?;
+        if Instant::now() >= until {
+            return None;
+        }
+
+        tracing::info!(
+            req = display(req.summary()),
+            vote = display(vote),
+            "reject pre-vote request: the leader is alive, election timer expires at {:?}",
+            until
+        );
+
+        Some(VoteResponse {
+            vote: *vote,
+            vote_granted: false,
+            last_log_id: self.engine.state.last_log_id().copied(),
+        })
+    }
+
+    /// Build a rejecting response if this node is still inside the leader lease it granted to the current leader.
+    ///
+    /// The current leader itself, and a candidate that the leader asked to elect with TimeoutNow,
+    /// are still allowed to be voted for.
+    fn reject_within_granted_lease(&self, req: &VoteRequest<C::NodeId>) -> Option<VoteResponse<C::NodeId>> {
+        if !self.config.enable_leader_lease {
+            return None;
+        }
+
+        let until = self.leader_lease_granted_until?;
+
+        let vote = &self.engine.state.vote;
+        if !vote.committed || req.vote.node_id == vote.node_id || Instant::now() >= until {
+            return None;
+        }
+
+        // The leader holding the lease asked the candidate to elect in the next term.
+        if req.leader_transfer.as_ref() == Some(vote) && req.vote.term == vote.term + 1 {
+            return None;
+        }
+
+        tracing::info!(
+            req = display(req.summary()),
+            vote = display(vote),
+            "reject vote request: still in the leader lease until {:?}",
+            until
+        );
+
+        Some(VoteResponse {
+            vote: *vote,
+            vote_granted: false,
+            last_log_id: self.engine.state.last_log_id().copied(),
+        })
+    }
+
+    /// Handle response from a pre-vote request sent to a peer.
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    async fn handle_pre_vote_resp(
+        &mut self,
+        resp: VoteResponse<C::NodeId>,
+        target: C::NodeId,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!(
+            resp = debug(&resp),
+            target = display(target),
+            my_vote = display(&self.engine.state.vote),
+            my_last_log_id = debug(self.engine.state.last_log_id()),
+            "recv pre-vote response"
+        );
+
+        self.engine.handle_pre_vote_resp(target, resp);
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        Ok(())
+    }
+
     /// Handle response from a vote request sent to a peer.
     #[tracing::instrument(level = "debug", skip(self, resp))]
     async fn handle_vote_resp(
@@ -1125,14 +2102,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     }
 
     #[tracing::instrument(level = "debug", skip(self, msg), fields(state = debug(self.engine.state.server_state), id=display(self.id)))]
-    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C, N, S>) -> Result<(), Fatal<C::NodeId>> {
+    pub(crate) async fn handle_api_msg(&mut self, msg: RaftMsg<C, N, LS, SM>) -> Result<(), Fatal<C::NodeId>> {
         tracing::debug!("recv from rx_api: {}", msg.summary());
 
         match msg {
             RaftMsg::AppendEntries { rpc, tx } => {
+                let received_at = Instant::now();
                 let resp =
                     self.engine.handle_append_entries_req(&rpc.vote, rpc.prev_log_id, &rpc.entries, rpc.leader_commit);
                 self.run_engine_commands(rpc.entries.as_slice()).await?;
+
+                if self.config.enable_leader_lease && !matches!(resp, AppendEntriesResponse::HigherVote(_)) {
+                    self.leader_lease_granted_until =
+                        Some(received_at + Duration::from_millis(self.config.election_timeout_min));
+                }
                 let _ = tx.send(Ok(resp));
             }
             RaftMsg::RequestVote { rpc, tx } => {
@@ -1143,29 +2126,82 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.handle_vote_resp(resp, target).await?;
                 }
             }
+            RaftMsg::RequestPreVote { rpc, tx } => {
+                let _ = tx.send(Ok(self.handle_pre_vote_request(rpc)));
+            }
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                if self.does_vote_match(&vote, "PreVoteResponse") {
+                    self.handle_pre_vote_resp(resp, target).await?;
+                }
+            }
+            RaftMsg::TimeoutNow { rpc, tx } => {
+                let resp = self.engine.handle_timeout_now_req(rpc);
+                self.run_engine_commands::<Entry<C>>(&[]).await?;
+                let _ = tx.send(Ok(resp));
+            }
             RaftMsg::InstallSnapshot { rpc, tx } => {
                 let _ = tx.send(self.handle_install_snapshot_request(rpc).await.extract_fatal()?);
             }
             RaftMsg::BuildingSnapshotResult { result } => {
                 self.handle_building_snapshot_result(result).await?;
             }
-            RaftMsg::CheckIsLeaderRequest { tx } => {
-                if self.engine.is_leader() {
-                    self.handle_check_is_leader_request(tx).await;
-                } else {
-                    self.reject_with_forward_to_leader(tx);
+            RaftMsg::ApplyResult { result } => {
+                let res = result?;
+                self.handle_apply_result(res).await;
+            }
+            RaftMsg::LogFlushed { vote, result } => {
+                let log_id = result?;
+
+                // A flush notification sent by a previous leader is ignored.
+                if vote == self.engine.state.vote {
+                    self.update_local_progress(log_id);
+                    self.run_engine_commands::<Entry<C>>(&[]).await?;
                 }
             }
-            RaftMsg::ClientWriteRequest { payload: rpc, tx } => {
+            RaftMsg::CheckIsLeaderRequest { tx } => {
+                self.check_is_leader((), tx).await;
+            }
+            RaftMsg::TransferLeader { to, tx } => {
                 if self.engine.is_leader() {
-                    self.write_entry(rpc, Some(tx)).await?;
+                    self.handle_transfer_leader(to, tx).await;
                 } else {
                     self.reject_with_forward_to_leader(tx);
                 }
             }
+            RaftMsg::UpdateConfig { config, tx } => {
+                self.update_config(config);
+                let _ = tx.send(Ok(()));
+            }
+            RaftMsg::ReadIndex { tx } => {
+                // The read log id has to be captured before confirming the leadership.
+                let read_log_id = self.engine.read_log_id();
+                self.check_is_leader(read_log_id, tx).await;
+            }
+            RaftMsg::ClientWriteRequest { payload, tx } => {
+                self.write_client_requests(vec![(payload, tx)]).await?;
+            }
+            RaftMsg::ClientWriteManyRequest { payloads, tx } => {
+                self.write_client_many_requests(payloads, tx).await?;
+            }
             RaftMsg::Initialize { members, tx } => {
                 let _ = tx.send(self.handle_initialize(members).await.extract_fatal()?);
             }
+            RaftMsg::ExportSnapshot { tx } => {
+                let snapshot = self.sm_handle.get_current_snapshot().await?;
+                let _ = tx.send(Ok(snapshot));
+            }
+            RaftMsg::BeginImportSnapshot { meta, tx } => {
+                let _ = tx.send(self.begin_importing_snapshot(meta).await.extract_fatal()?);
+            }
+            RaftMsg::ImportSnapshot { meta, data, tx } => {
+                let _ = tx.send(self.handle_import_snapshot(meta, data).await.extract_fatal()?);
+            }
+            RaftMsg::ForceResetMembership { voters, tx } => {
+                self.spawn_force_reset_membership_check(voters, tx).await;
+            }
+            RaftMsg::ForceResetMembershipChecked { voters, responses, tx } => {
+                let _ = tx.send(self.handle_force_reset_membership(voters, responses).await.extract_fatal()?);
+            }
             RaftMsg::AddLearner { id, node, tx } => {
                 if self.engine.is_leader() {
                     self.add_learner(id, node, tx).await?;
@@ -1186,7 +2222,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 }
             }
             RaftMsg::ExternalRequest { req } => {
-                req(&self.engine.state, &mut self.storage, &mut self.network);
+                req(&self.engine.state, &mut self.log_store, &mut self.network);
             }
             RaftMsg::ExternalCommand { cmd } => {
                 match cmd {
@@ -1255,6 +2291,24 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     }
                 }
 
+                // Resend TimeoutNow if the leadership transfer target has not yet been elected.
+                self.try_send_timeout_now().await;
+
+                // A time based snapshot policy has to be checked even when no log is applied.
+                if self.config.snapshot_policy.is_time_based() {
+                    self.trigger_snapshot_if_needed(false).await;
+                }
+
+                // Leader steps down if it has not been acknowledged by a quorum for an election timeout.
+                if self.config.enable_check_quorum {
+                    if let Some(l) = &self.leader_data {
+                        let timeout = Duration::from_millis(self.config.election_timeout_min);
+                        if self.engine.leader_check_quorum(now, timeout, l.established_at) {
+                            self.run_engine_commands::<Entry<C>>(&[]).await?;
+                        }
+                    }
+                }
+
                 // When a membership that removes the leader is committed,
                 // the leader continue to work for a short while before reverting to a learner.
                 // This way, let the leader replicate the `membership-log-is-committed` message to followers.
@@ -1287,13 +2341,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.handle_update_matched(target, result).await?;
                 }
             }
+            RaftMsg::UpdateReplicationStatus { target, session_id } => {
+                if self.does_replication_session_match(&session_id, "UpdateReplicationStatus") {
+                    self.handle_update_replication_status(target);
+                }
+            }
             RaftMsg::NeedsSnapshot {
                 target: _,
                 tx,
                 session_id,
             } => {
                 if self.does_replication_session_match(&session_id, "NeedsSnapshot") {
-                    let snapshot = self.storage.get_current_snapshot().await?;
+                    let snapshot = self.sm_handle.get_current_snapshot().await?;
 
                     if let Some(snapshot) = snapshot {
                         let _ = tx.send(snapshot);
@@ -1310,6 +2369,89 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(())
     }
 
+    /// Append logs to the log store.
+    ///
+    /// A leader does not wait for the logs to be flushed: the logs are replicated to followers while they are being
+    /// flushed, and the local progress is updated when `RaftMsg::LogFlushed` is received.
+    /// If the log store has flushed the logs when `append()` returns, the local progress is updated at once, so that
+    /// the logs are committed in the order they are appended, before any message is handled.
+    /// A follower or learner has to wait for the logs to be flushed before responding to the leader.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn append_to_log(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        let last_log_id = entries.last().map(|e| e.log_id);
+
+        let (tx, mut rx) = oneshot::channel();
+        let callback = LogFlushed::new(last_log_id, tx);
+
+        self.log_store.append(entries, callback).await?;
+
+        if !self.engine.is_leader() {
+            Self::wait_for_flush(last_log_id, rx).await?;
+            return Ok(());
+        }
+
+        match rx.try_recv() {
+            Ok(res) => {
+                let log_id = Self::flush_result(last_log_id, Some(res))?;
+                self.update_local_progress(log_id);
+            }
+            Err(TryRecvError::Closed) => {
+                Self::flush_result(last_log_id, None)?;
+            }
+            Err(TryRecvError::Empty) => {
+                let vote = self.engine.state.vote;
+                let tx_api = self.tx_api.clone();
+
+                tokio::spawn(
+                    async move {
+                        let result = Self::wait_for_flush(last_log_id, rx).await;
+                        let _ = tx_api.send(RaftMsg::LogFlushed { vote, result });
+                    }
+                    .instrument(tracing::debug_span!("wait-for-log-flush")),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the callback of appending logs upto `last_log_id` to be called.
+    async fn wait_for_flush(
+        last_log_id: Option<LogId<C::NodeId>>,
+        rx: oneshot::Receiver<Result<(), io::Error>>,
+    ) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        Self::flush_result(last_log_id, rx.await.ok())
+    }
+
+    /// Convert the result reported by the callback of appending logs upto `last_log_id`.
+    ///
+    /// `res` is `None` if the callback is dropped without being called.
+    fn flush_result(
+        last_log_id: Option<LogId<C::NodeId>>,
+        res: Option<Result<(), io::Error>>,
+    ) -> Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>> {
+        let res = res.ok_or_else(|| {
+            StorageIOError::new(
+                ErrorSubject::Logs,
+                ErrorVerb::Write,
+                AnyError::error("LogFlushed callback is dropped"),
+            )
+        })?;
+
+        res.map_err(|e| StorageError::from_io_error(ErrorSubject::Logs, ErrorVerb::Write, e))?;
+
+        Ok(last_log_id)
+    }
+
+    /// Update the progress of the leader itself when its local logs upto `log_id` are flushed.
+    fn update_local_progress(&mut self, log_id: Option<LogId<C::NodeId>>) {
+        if let (Some(l), Some(log_id)) = (&mut self.leader_data, log_id) {
+            l.proposal_times.appended(log_id.index, &self.latency);
+        }
+
+        self.engine.update_local_progress(log_id);
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     async fn handle_update_matched(
         &mut self,
@@ -1327,11 +2469,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 if !l.nodes.contains_key(&target) {
                     tracing::warn!("leader has removed target: {}", target);
                 };
-            } else {
-                unreachable!("no longer a leader, received message from previous leader");
             }
         }
 
+        if self.leader_data.is_none() {
+            // A leader that has stepped down keeps its vote, a message sent before it stepped down still matches.
+            tracing::debug!(
+                "no longer a leader, ignore progress from previous replication: {}",
+                target
+            );
+            return Ok(());
+        }
+
         let progress = match result {
             Ok(p) => p,
             Err(_err_str) => {
@@ -1339,9 +2488,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             }
         };
 
-        self.engine.update_progress(target, Some(progress.matching.unwrap()));
+        if let Some(t) = progress.last_ack {
+            self.engine.update_leader_ack_time(target, t);
+        }
+
+        // With leader lease enabled, a progress is reported for every acknowledged heartbeat, in which case
+        // `matching` may still be `None`.
+        if let Some(matching) = progress.matching {
+            self.engine.update_progress(target, Some(matching));
+        }
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
+        self.try_send_timeout_now().await;
+
         Ok(())
     }
 
@@ -1364,6 +2523,26 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         self.engine.output.metrics_flags.set_replication_changed()
     }
 
+    /// A replication stream has changed its status: copy the latest one into the replication metrics.
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn handle_update_replication_status(&mut self, target: C::NodeId) {
+        if let Some(l) = &mut self.leader_data {
+            let node = match l.nodes.get(&target) {
+                Some(x) => x,
+                None => return,
+            };
+
+            // Clear it before the status is copied, so that a later change sends another notification.
+            node.status_notified.store(false, Ordering::Release);
+
+            // Safe unwrap(): the lock is never held across a panic.
+            let status = node.status.lock().unwrap().clone();
+
+            l.replication_metrics.update(UpdateReplicationStatus { target, status });
+            self.engine.output.metrics_flags.set_replication_changed();
+        }
+    }
+
     /// If a message is sent by a previous server state but is received by current server state,
     /// it is a stale message and should be just ignored.
     fn does_vote_match(&self, vote: &Vote<C::NodeId>, msg: impl Display) -> bool {
@@ -1404,7 +2583,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 }
 
 #[async_trait::async_trait]
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime<C> for RaftCore<C, N, S> {
+impl<C, N, LS, SM> RaftRuntime<C> for RaftCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     async fn run_command<'e, Ent>(
         &mut self,
         input_ref_entries: &'e [Ent],
@@ -1419,19 +2604,36 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
             Command::BecomeLeader => {
                 debug_assert!(self.leader_data.is_none(), "can not become leader twice");
                 self.leader_data = Some(LeaderData::new());
+                self.events.send(RaftEvent::BecameLeader {
+                    vote: self.engine.state.vote,
+                });
             }
             Command::QuitLeader => {
-                if let Some(l) = &mut self.leader_data {
-                    // Leadership lost, inform waiting clients
-                    let chans = std::mem::take(&mut l.client_resp_channels);
+                if self.leader_data.is_some() {
+                    // Leadership lost, inform the clients waiting for the logs that are not committed.
+                    // The committed logs are still applied and their clients are responded then.
+                    let uncommitted = self.engine.state.committed.next_index();
+
+                    let chans = self.client_resp_channels.split_off(&uncommitted);
                     for (_, tx) in chans.into_iter() {
                         let _ = tx.send(Err(ClientWriteError::ForwardToLeader(ForwardToLeader {
                             leader_id: None,
                             leader_node: None,
                         })));
                     }
+
+                    let batches = self.client_batch_resp_channels.split_off(&uncommitted);
+                    for (_, b) in batches.into_iter() {
+                        let _ = b.tx.send(Err(ClientWriteError::ForwardToLeader(ForwardToLeader {
+                            leader_id: None,
+                            leader_node: None,
+                        })));
+                    }
                 }
                 self.leader_data = None;
+                self.events.send(RaftEvent::LostLeadership {
+                    vote: self.engine.state.vote,
+                });
             }
             Command::AppendInputEntries { range } => {
                 let entry_refs = &input_ref_entries[range.clone()];
@@ -1444,7 +2646,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 // Build a slice of references.
                 let entry_refs = entries.iter().collect::<Vec<_>>();
 
-                self.storage.append_to_log(&entry_refs).await?
+                self.append_to_log(&entry_refs).await?
             }
             Command::AppendBlankLog { log_id } => {
                 let ent = Entry {
@@ -1452,22 +2654,29 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                     payload: EntryPayload::Blank,
                 };
                 let entry_refs = vec![&ent];
-                self.storage.append_to_log(&entry_refs).await?
+                self.append_to_log(&entry_refs).await?
             }
             Command::MoveInputCursorBy { n } => *cur += n,
             Command::SaveVote { vote } => {
-                self.storage.save_vote(vote).await?;
+                self.log_store.save_vote(vote).await?;
+                self.events.update_term(vote.term);
             }
             Command::InstallElectionTimer { can_be_leader } => {
                 self.set_next_election_time(*can_be_leader);
             }
-            Command::PurgeLog { upto } => self.storage.purge_logs_upto(*upto).await?,
+            Command::PurgeLog { upto } => {
+                self.log_store.purge_logs_upto(*upto).await?;
+                self.events.send(RaftEvent::LogsPurged { upto: *upto });
+            }
             Command::DeleteConflictLog { since } => {
-                self.storage.delete_conflict_logs_since(*since).await?;
+                self.log_store.delete_conflict_logs_since(*since).await?;
             }
             Command::BuildSnapshot { .. } => {}
             Command::SendVote { vote_req } => {
-                self.spawn_parallel_vote_requests(vote_req).await;
+                self.spawn_parallel_vote_requests(vote_req, false).await;
+            }
+            Command::SendPreVote { vote_req } => {
+                self.spawn_parallel_vote_requests(vote_req, true).await;
             }
             Command::ReplicateCommitted { committed } => {
                 if let Some(l) = &self.leader_data {
@@ -1482,13 +2691,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 already_committed: ref committed,
                 ref upto,
             } => {
-                self.apply_to_state_machine(committed.next_index(), upto.index).await?;
+                if let Some(l) = &mut self.leader_data {
+                    l.proposal_times.committed(upto.index, &self.latency);
+                }
+                self.apply_to_state_machine(committed.next_index(), *upto).await;
             }
             Command::FollowerCommit {
                 already_committed: ref committed,
                 ref upto,
             } => {
-                self.apply_to_state_machine(committed.next_index(), upto.index).await?;
+                self.apply_to_state_machine(committed.next_index(), *upto).await;
             }
             Command::ReplicateEntries { upto } => {
                 if let Some(l) = &self.leader_data {
@@ -1506,6 +2718,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                     match self.spawn_replication_stream(*node_id, *matched).await {
                         Ok(state) => {
                             if let Some(l) = &mut self.leader_data {
+                                l.replication_metrics.update(AddTarget {
+                                    target: *node_id,
+                                    matched: matched.matching,
+                                });
+                                self.engine.output.metrics_flags.set_replication_changed();
+
                                 l.nodes.insert(*node_id, state);
                             } else {
                                 unreachable!("it has to be a leader!!!");
@@ -1521,8 +2739,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
             Command::UpdateReplicationMetrics { target, matching } => {
                 self.update_replication_metrics(*target, *matching);
             }
-            Command::UpdateMembership { .. } => {
-                // TODO: not used
+            Command::UpdateMembership { membership } => {
+                self.events.send(RaftEvent::MembershipChanged {
+                    membership: membership.clone(),
+                });
             }
             Command::CancelSnapshot { snapshot_meta } => {
                 let got = self.received_snapshot.remove(&snapshot_meta.snapshot_id);
@@ -1531,9 +2751,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
             Command::InstallSnapshot { snapshot_meta } => {
                 let snapshot_data = self.received_snapshot.remove(&snapshot_meta.snapshot_id);
 
-                if let Some(data) = snapshot_data {
-                    self.storage.install_snapshot(snapshot_meta, data).await?;
+                if let Some((metadata, data)) = snapshot_data {
+                    // The state machine worker installs it after applying all of the logs sent before.
+                    // Thus the installed state machine won't be updated by these logs.
+                    let meta = snapshot_meta.clone().with_metadata(metadata);
+                    self.sm_handle.install_snapshot(meta, data).await?;
                     tracing::debug!("Done install_snapshot, meta: {:?}", snapshot_meta);
+
+                    if snapshot_meta.last_log_id > self.last_applied {
+                        self.last_applied = snapshot_meta.last_log_id;
+                    }
+
+                    self.events.send(RaftEvent::SnapshotInstalled {
+                        meta: snapshot_meta.clone(),
+                    });
                 } else {
                     unreachable!("buffered snapshot not found: snapshot meta: {:?}", snapshot_meta)
                 }
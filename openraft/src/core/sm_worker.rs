@@ -0,0 +1,285 @@
+//! The state machine worker owns the state machine, in a task separated from `RaftCore`.
+//!
+//! It applies committed logs and serves snapshot operations on the state machine, in the order they are requested.
+//! A slow state machine then does not block `RaftCore` from sending heartbeats, handling votes or replicating logs.
+
+use anyerror::AnyError;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use tracing::Level;
+use tracing::Span;
+
+use crate::error::IncompatibleSnapshot;
+use crate::raft::RaftMsg;
+use crate::storage::Snapshot;
+use crate::summary::MessageSummary;
+use crate::Entry;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::LogId;
+use crate::RaftLogReader;
+use crate::RaftLogStorage;
+use crate::RaftNetworkFactory;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
+use crate::StorageError;
+use crate::StorageIOError;
+
+/// The max number of commands that are queued for the state machine worker.
+///
+/// When it is full, `RaftCore` waits for the state machine worker to catch up.
+const COMMAND_CHANNEL_SIZE: usize = 1024;
+
+/// The response of a command sent to the state machine worker.
+type ResponseTx<T, NID> = oneshot::Sender<Result<T, StorageError<NID>>>;
+
+/// A command sent to the state machine worker.
+pub(crate) enum StateMachineCommand<C: RaftTypeConfig, SM: RaftStateMachine<C>> {
+    /// Apply logs in range `[since, upto]` to the state machine.
+    ///
+    /// The result is sent back to `RaftCore` with `RaftMsg::ApplyResult`.
+    Apply {
+        since: u64,
+        upto: LogId<C::NodeId>,
+    },
+
+    GetSnapshotBuilder {
+        tx: oneshot::Sender<SM::SnapshotBuilder>,
+    },
+
+    BeginReceivingSnapshot {
+        tx: ResponseTx<Box<SM::SnapshotData>, C::NodeId>,
+    },
+
+    CheckSnapshotMeta {
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        tx: ResponseTx<Result<(), IncompatibleSnapshot>, C::NodeId>,
+    },
+
+    InstallSnapshot {
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        snapshot: Box<SM::SnapshotData>,
+        tx: ResponseTx<(), C::NodeId>,
+    },
+
+    GetCurrentSnapshot {
+        tx: ResponseTx<Option<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>>, C::NodeId>,
+    },
+}
+
+/// The result of applying a range of logs, sent back to `RaftCore` with `RaftMsg::ApplyResult`.
+pub(crate) struct ApplyResult<C: RaftTypeConfig> {
+    /// The index of the first applied log.
+    pub(crate) since: u64,
+
+    /// The last applied log id.
+    pub(crate) last_applied: LogId<C::NodeId>,
+
+    /// The applied logs.
+    pub(crate) entries: Vec<Entry<C>>,
+
+    /// The result of applying every log in `entries`.
+    pub(crate) results: Vec<C::R>,
+}
+
+impl<C: RaftTypeConfig> MessageSummary<ApplyResult<C>> for ApplyResult<C> {
+    fn summary(&self) -> String {
+        format!("since: {}, last_applied: {}", self.since, self.last_applied)
+    }
+}
+
+/// Executes commands on the state machine one by one, and sends back the results of applying logs to `RaftCore`.
+pub(crate) struct StateMachineWorker<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    state_machine: SM,
+
+    /// For reading the committed logs to apply.
+    log_reader: LS::LogReader,
+
+    rx: mpsc::Receiver<StateMachineCommand<C, SM>>,
+
+    tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+}
+
+/// The handle for `RaftCore` to send commands to the state machine worker.
+pub(crate) struct StateMachineHandle<C: RaftTypeConfig, SM: RaftStateMachine<C>> {
+    tx: mpsc::Sender<StateMachineCommand<C, SM>>,
+    join_handle: JoinHandle<()>,
+}
+
+impl<C, N, LS, SM> StateMachineWorker<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    pub(crate) fn spawn(
+        state_machine: SM,
+        log_reader: LS::LogReader,
+        tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+    ) -> StateMachineHandle<C, SM> {
+        let (tx, rx) = mpsc::channel(COMMAND_CHANNEL_SIZE);
+
+        let this = Self {
+            state_machine,
+            log_reader,
+            rx,
+            tx_api,
+        };
+
+        let join_handle = tokio::spawn(this.worker_loop().instrument(tracing::span!(
+            parent: &Span::current(),
+            Level::DEBUG,
+            "state_machine_worker"
+        )));
+
+        StateMachineHandle { tx, join_handle }
+    }
+
+    async fn worker_loop(mut self) {
+        while let Some(cmd) = self.rx.recv().await {
+            match cmd {
+                StateMachineCommand::Apply { since, upto } => {
+                    let res = self.apply(since, upto).await;
+                    let is_err = res.is_err();
+
+                    let send_res = self.tx_api.send(RaftMsg::ApplyResult { result: res });
+                    if send_res.is_err() {
+                        tracing::info!("state machine worker fails to send result, RaftCore quit");
+                        return;
+                    }
+
+                    // A storage error is fatal: RaftCore will shut down when it receives the error.
+                    if is_err {
+                        return;
+                    }
+                }
+                StateMachineCommand::GetSnapshotBuilder { tx } => {
+                    let _ = tx.send(self.state_machine.get_snapshot_builder().await);
+                }
+                StateMachineCommand::BeginReceivingSnapshot { tx } => {
+                    let _ = tx.send(self.state_machine.begin_receiving_snapshot().await);
+                }
+                StateMachineCommand::CheckSnapshotMeta { meta, tx } => {
+                    let _ = tx.send(Ok(self.state_machine.check_snapshot_meta(&meta).await));
+                }
+                StateMachineCommand::InstallSnapshot { meta, snapshot, tx } => {
+                    let _ = tx.send(self.state_machine.install_snapshot(&meta, snapshot).await);
+                }
+                StateMachineCommand::GetCurrentSnapshot { tx } => {
+                    let _ = tx.send(self.state_machine.get_current_snapshot().await);
+                }
+            }
+        }
+
+        tracing::debug!("state machine worker quit: RaftCore dropped the command channel");
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn apply(&mut self, since: u64, upto: LogId<C::NodeId>) -> Result<ApplyResult<C>, StorageError<C::NodeId>> {
+        let end = upto.index + 1;
+
+        debug_assert!(
+            since < end,
+            "last_applied index {} should < committed index {}",
+            since,
+            end
+        );
+
+        let entries = self.log_reader.get_log_entries(since..end).await?;
+        tracing::debug!(entries = display(entries.as_slice().summary()), "about to apply");
+
+        let entry_refs = entries.iter().collect::<Vec<_>>();
+        let results = self.state_machine.apply(&entry_refs).await?;
+
+        Ok(ApplyResult {
+            since,
+            last_applied: upto,
+            entries,
+            results,
+        })
+    }
+}
+
+impl<C: RaftTypeConfig, SM: RaftStateMachine<C>> StateMachineHandle<C, SM> {
+    /// Send a range of committed logs `[since, upto]` to the state machine worker to apply.
+    ///
+    /// It waits if the state machine worker has too many pending commands.
+    pub(crate) async fn apply(&self, since: u64, upto: LogId<C::NodeId>) {
+        let send_res = self.tx.send(StateMachineCommand::Apply { since, upto }).await;
+        if send_res.is_err() {
+            tracing::info!("state machine worker quit, the error it encountered will be received by RaftCore");
+        }
+    }
+
+    pub(crate) async fn get_snapshot_builder(&self) -> Result<SM::SnapshotBuilder, StorageError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.send(StateMachineCommand::GetSnapshotBuilder { tx }).await?;
+        rx.await.map_err(|_| Self::worker_quit_error())
+    }
+
+    pub(crate) async fn begin_receiving_snapshot(&self) -> Result<Box<SM::SnapshotData>, StorageError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.send(StateMachineCommand::BeginReceivingSnapshot { tx }).await?;
+        rx.await.map_err(|_| Self::worker_quit_error())?
+    }
+
+    /// Check if the state machine is able to install a snapshot with the given metadata.
+    pub(crate) async fn check_snapshot_meta(
+        &self,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<Result<(), IncompatibleSnapshot>, StorageError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.send(StateMachineCommand::CheckSnapshotMeta { meta, tx }).await?;
+        rx.await.map_err(|_| Self::worker_quit_error())?
+    }
+
+    /// Install a snapshot, after all of the logs sent to the state machine worker are applied.
+    pub(crate) async fn install_snapshot(
+        &self,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        snapshot: Box<SM::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.send(StateMachineCommand::InstallSnapshot { meta, snapshot, tx }).await?;
+        rx.await.map_err(|_| Self::worker_quit_error())?
+    }
+
+    pub(crate) async fn get_current_snapshot(
+        &self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>>, StorageError<C::NodeId>>
+    {
+        let (tx, rx) = oneshot::channel();
+        self.send(StateMachineCommand::GetCurrentSnapshot { tx }).await?;
+        rx.await.map_err(|_| Self::worker_quit_error())?
+    }
+
+    /// Stop the state machine worker after it finishes all of the commands that are sent.
+    pub(crate) async fn shutdown(self) {
+        drop(self.tx);
+        let _ = self.join_handle.await;
+    }
+
+    async fn send(&self, cmd: StateMachineCommand<C, SM>) -> Result<(), StorageError<C::NodeId>> {
+        self.tx.send(cmd).await.map_err(|_| Self::worker_quit_error())
+    }
+
+    fn worker_quit_error() -> StorageError<C::NodeId> {
+        StorageError::IO {
+            source: StorageIOError::new(
+                ErrorSubject::StateMachine,
+                ErrorVerb::Read,
+                AnyError::error("state machine worker quit"),
+            ),
+        }
+    }
+}
@@ -7,6 +7,7 @@ use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 
 use crate::raft::InstallSnapshotRequest;
+use crate::storage::SnapshotDigest;
 use crate::ErrorSubject;
 use crate::ErrorVerb;
 use crate::RaftTypeConfig;
@@ -17,6 +18,10 @@ use crate::StorageError;
 pub(crate) struct StreamingState<C: RaftTypeConfig, SD> {
     /// The offset of the last byte written to the snapshot.
     pub(crate) offset: u64,
+    /// The digest of the bytes received continuously from the start of the snapshot.
+    ///
+    /// A chunk written after a gap is not included.
+    pub(crate) digest: SnapshotDigest,
     /// The ID of the snapshot being written.
     pub(crate) snapshot_id: SnapshotId,
     /// A handle to the snapshot writer.
@@ -31,12 +36,18 @@ where SD: AsyncSeek + AsyncWrite + Unpin
     pub(crate) fn new(snapshot_id: SnapshotId, snapshot_data: Box<SD>) -> Self {
         Self {
             offset: 0,
+            digest: SnapshotDigest::default(),
             snapshot_id,
             snapshot_data,
             _p: Default::default(),
         }
     }
 
+    /// The number of bytes received continuously from the start of the snapshot.
+    pub(crate) fn received(&self) -> u64 {
+        self.digest.len()
+    }
+
     /// Receive a chunk of snapshot data.
     pub(crate) async fn receive(&mut self, req: InstallSnapshotRequest<C>) -> Result<bool, StorageError<C::NodeId>> {
         // TODO: check id?
@@ -63,6 +74,8 @@ where SD: AsyncSeek + AsyncWrite + Unpin
             ));
         }
         self.offset += req.data.len() as u64;
+        self.digest.update(req.offset, &req.data);
+
         Ok(req.done)
     }
 }
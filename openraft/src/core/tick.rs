@@ -1,6 +1,7 @@
 //! tick emitter emits a `RaftMsg::Tick` event at a certain interval.
 
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
@@ -15,8 +16,9 @@ use tracing::Span;
 
 use crate::raft::RaftMsg;
 use crate::NodeId;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
 use crate::Vote;
 
@@ -48,15 +50,17 @@ impl<NID: NodeId> VoteWiseTime<NID> {
 }
 
 /// Emit RaftMsg::Tick event at regular `interval`.
-pub(crate) struct Tick<C, N, S>
+pub(crate) struct Tick<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
-    interval: Duration,
+    /// The interval in milliseconds, it can be changed by [`TickHandle::set_interval`].
+    interval_ms: Arc<AtomicU64>,
 
-    tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+    tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
 
     /// Emit event or not
     enabled: Arc<AtomicBool>,
@@ -64,25 +68,36 @@ where
 
 pub(crate) struct TickHandle {
     enabled: Arc<AtomicBool>,
+    interval_ms: Arc<AtomicU64>,
     join_handle: JoinHandle<()>,
 }
 
-impl<C, N, S> Tick<C, N, S>
+impl<C, N, LS, SM> Tick<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
-    pub(crate) fn spawn(interval: Duration, tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>, enabled: bool) -> TickHandle {
+    pub(crate) fn spawn(
+        interval: Duration,
+        tx: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+        enabled: bool,
+    ) -> TickHandle {
         let enabled = Arc::new(AtomicBool::from(enabled));
+        let interval_ms = Arc::new(AtomicU64::from(interval.as_millis() as u64));
         let this = Self {
-            interval,
+            interval_ms: interval_ms.clone(),
             enabled: enabled.clone(),
             tx,
         };
         let join_handle =
             tokio::spawn(this.tick_loop().instrument(tracing::span!(parent: &Span::current(), Level::DEBUG, "tick")));
-        TickHandle { enabled, join_handle }
+        TickHandle {
+            enabled,
+            interval_ms,
+            join_handle,
+        }
     }
 
     pub(crate) async fn tick_loop(self) {
@@ -90,7 +105,8 @@ where
         loop {
             i += 1;
 
-            let at = Instant::now() + self.interval;
+            let interval = Duration::from_millis(self.interval_ms.load(Ordering::Relaxed));
+            let at = Instant::now() + interval;
             sleep_until(at).await;
 
             if !self.enabled.load(Ordering::Relaxed) {
@@ -113,6 +129,13 @@ impl TickHandle {
         self.enabled.store(enabled, Ordering::Relaxed);
     }
 
+    /// Change the interval between two ticks.
+    ///
+    /// It takes effect after the tick that is being waited for.
+    pub(crate) fn set_interval(&self, interval: Duration) {
+        self.interval_ms.store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
     pub(crate) async fn shutdown(&self) {
         self.join_handle.abort();
     }
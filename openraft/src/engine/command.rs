@@ -81,6 +81,9 @@ where
     /// Send vote to all other members
     SendVote { vote_req: VoteRequest<NID> },
 
+    /// Send pre-vote to all other members, to check if a quorum would grant the vote in `vote_req`.
+    SendPreVote { vote_req: VoteRequest<NID> },
+
     /// Install a timer to trigger an election, e.g., calling `Engine::elect()` after some `timeout` which is decided
     /// by the runtime. An already installed timer should be cleared.
     InstallElectionTimer {
@@ -132,6 +135,7 @@ where
             Command::MoveInputCursorBy { .. } => {}
             Command::SaveVote { .. } => flags.set_data_changed(),
             Command::SendVote { .. } => {}
+            Command::SendPreVote { .. } => {}
             Command::InstallElectionTimer { .. } => {}
             Command::PurgeLog { .. } => flags.set_data_changed(),
             Command::DeleteConflictLog { .. } => flags.set_data_changed(),
@@ -47,6 +47,7 @@ fn test_elect() -> anyhow::Result<()> {
         eng.elect();
 
         assert_eq!(Vote::new_committed(1, 1), eng.state.vote);
+        assert_eq!(1, eng.elections);
         assert_eq!(
             Some(btreeset! {1},),
             eng.internal_server_state.leading().map(|x| x.vote_granted_by.clone())
@@ -76,19 +77,6 @@ fn test_elect() -> anyhow::Result<()> {
                         index: 0,
                     },
                 },
-                Command::ReplicateCommitted {
-                    committed: Some(LogId {
-                        leader_id: LeaderId { term: 1, node_id: 1 },
-                        index: 0,
-                    },),
-                },
-                Command::LeaderCommit {
-                    already_committed: None,
-                    upto: LogId {
-                        leader_id: LeaderId { term: 1, node_id: 1 },
-                        index: 0,
-                    },
-                },
                 Command::ReplicateEntries {
                     upto: Some(LogId {
                         leader_id: LeaderId { term: 1, node_id: 1 },
@@ -143,19 +131,6 @@ fn test_elect() -> anyhow::Result<()> {
                         index: 0,
                     },
                 },
-                Command::ReplicateCommitted {
-                    committed: Some(LogId {
-                        leader_id: LeaderId { term: 2, node_id: 1 },
-                        index: 0,
-                    },),
-                },
-                Command::LeaderCommit {
-                    already_committed: None,
-                    upto: LogId {
-                        leader_id: LeaderId { term: 2, node_id: 1 },
-                        index: 0,
-                    },
-                },
                 Command::ReplicateEntries {
                     upto: Some(LogId {
                         leader_id: LeaderId { term: 2, node_id: 1 },
@@ -176,7 +151,7 @@ fn test_elect() -> anyhow::Result<()> {
 
         eng.elect();
 
-        assert_eq!(Vote::new(1, 1), eng.state.vote);
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
         assert_eq!(
             Some(btreeset! {1},),
             eng.internal_server_state.leading().map(|x| x.vote_granted_by.clone())
@@ -194,9 +169,9 @@ fn test_elect() -> anyhow::Result<()> {
 
         assert_eq!(
             vec![
-                Command::SaveVote { vote: Vote::new(1, 1) },
+                Command::SaveVote { vote: Vote::new(2, 1) },
                 Command::SendVote {
-                    vote_req: VoteRequest::new(Vote::new(1, 1), Some(log_id(1, 1)))
+                    vote_req: VoteRequest::new(Vote::new(2, 1), Some(log_id(1, 1)))
                 },
                 Command::InstallElectionTimer { can_be_leader: true },
             ],
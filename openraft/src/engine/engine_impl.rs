@@ -1,8 +1,13 @@
+use std::fmt::Debug;
 use std::sync::Arc;
 
+use tokio::time::Duration;
+use tokio::time::Instant;
+
 use crate::core::ServerState;
 use crate::engine::handler::snapshot_handler::SnapshotHandler;
 use crate::engine::handler::vote_handler::VoteHandler;
+use crate::engine::pre_vote::PreVote;
 use crate::engine::Command;
 use crate::entry::RaftEntry;
 use crate::error::InitializeError;
@@ -17,7 +22,11 @@ use crate::membership::NodeRole;
 use crate::node::Node;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Progress;
+use crate::quorum::Majority;
+use crate::quorum::QuorumSet;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::LogStateReader;
@@ -25,6 +34,7 @@ use crate::raft_state::RaftState;
 use crate::raft_types::RaftLogId;
 use crate::summary::MessageSummary;
 use crate::validate::Valid;
+use crate::Config;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::Membership;
@@ -49,6 +59,22 @@ pub(crate) struct EngineConfig<NID: NodeId> {
 
     /// The maximum number of entries per payload allowed to be transmitted during replication
     pub(crate) max_payload_entries: u64,
+
+    /// Whether to run a PreVote round before starting an election.
+    pub(crate) enable_pre_vote: bool,
+}
+
+impl<NID: NodeId> EngineConfig<NID> {
+    /// Build the Engine config of node `id` from the config of Raft.
+    pub(crate) fn new(id: NID, config: &Config) -> Self {
+        Self {
+            id,
+            max_in_snapshot_log_to_keep: config.max_in_snapshot_log_to_keep,
+            purge_batch_size: config.purge_batch_size,
+            max_payload_entries: config.max_payload_entries,
+            enable_pre_vote: config.enable_pre_vote,
+        }
+    }
 }
 
 impl<NID: NodeId> Default for EngineConfig<NID> {
@@ -58,6 +84,7 @@ impl<NID: NodeId> Default for EngineConfig<NID> {
             max_in_snapshot_log_to_keep: 1000,
             purge_batch_size: 256,
             max_payload_entries: 300,
+            enable_pre_vote: false,
         }
     }
 }
@@ -99,10 +126,11 @@ where
 /// TODO: make the fields private
 #[derive(Debug, Clone, Default)]
 #[derive(PartialEq, Eq)]
-pub(crate) struct Engine<NID, N>
+pub(crate) struct Engine<NID, N, QS = Majority<NID>>
 where
     NID: NodeId,
     N: Node,
+    QS: QuorumSet<NID>,
 {
     pub(crate) config: EngineConfig<NID>,
 
@@ -110,22 +138,31 @@ where
     pub(crate) state: Valid<RaftState<NID, N>>,
 
     /// The internal server state used by Engine.
-    pub(crate) internal_server_state: InternalServerState<NID>,
+    pub(crate) internal_server_state: InternalServerState<NID, QS>,
+
+    /// The PreVote round in progress, if any.
+    pub(crate) pre_vote: Option<PreVote<NID>>,
+
+    /// The number of elections this node has started, reported in metrics.
+    pub(crate) elections: u64,
 
     /// Output entry for the runtime.
     pub(crate) output: EngineOutput<NID, N>,
 }
 
-impl<NID, N> Engine<NID, N>
+impl<NID, N, QS> Engine<NID, N, QS>
 where
     N: Node,
     NID: NodeId,
+    QS: QuorumSet<NID> + From<Vec<NID>> + Debug + Clone + 'static,
 {
     pub(crate) fn new(init_state: RaftState<NID, N>, config: EngineConfig<NID>) -> Self {
         Self {
             config,
             state: Valid::new(init_state),
             internal_server_state: InternalServerState::default(),
+            pre_vote: None,
+            elections: 0,
             output: EngineOutput::default(),
         }
     }
@@ -136,7 +173,7 @@ where
         // Allows starting up as a leader.
 
         // Previously it is a leader. restore it as leader at once
-        if self.is_leader() {
+        if self.state.vote.node_id == self.config.id && self.state.vote.committed {
             self.switch_internal_server_state();
             self.update_server_state_if_changed();
             self.update_replications();
@@ -196,10 +233,89 @@ where
         Ok(())
     }
 
-    /// Start to elect this node as leader
+    /// Force this node to become the leader of a new membership, without being elected by a quorum.
+    ///
+    /// It is used to recover a cluster that has permanently lost a quorum of voters:
+    /// this node commits a vote of `term` for itself and appends the new membership log, which becomes effective at
+    /// once and will be committed by a quorum of the new membership.
+    ///
+    /// The caller has to guarantee that this node is a voter of both the current and the new membership, `term` is
+    /// greater than the term of every voter in the new membership, and this node has the most up-to-date logs among
+    /// them.
+    #[tracing::instrument(level = "debug", skip(self, entries))]
+    pub(crate) fn force_reset_membership<Ent: RaftEntry<NID, N>>(&mut self, term: u64, entries: &mut [Ent]) {
+        debug_assert_eq!(1, entries.len());
+        debug_assert!(entries[0].get_membership().is_some());
+        debug_assert!(self.is_voter());
+
+        // Safe unwrap(): a vote with a greater term is always granted.
+        self.handle_vote_change(&Vote::new_committed(term, self.config.id)).unwrap();
+
+        // Become leader before appending the membership log, which updates the replication streams.
+        self.update_server_state_if_changed();
+
+        self.leader_append_entries(entries);
+    }
+
+    /// Start to elect this node as leader.
+    ///
+    /// If PreVote is enabled, it starts a PreVote round instead.
+    /// The real election starts only after a quorum would grant the vote.
+    ///
+    /// A witness never elects itself.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn elect(&mut self) {
-        self.handle_vote_change(&Vote::new(self.state.vote.term + 1, self.config.id)).unwrap();
+        if self.is_witness() {
+            tracing::debug!("witness {} does not elect", self.config.id);
+            self.output.push_command(Command::InstallElectionTimer { can_be_leader: false });
+            return;
+        }
+
+        if self.config.enable_pre_vote {
+            self.pre_vote();
+            return;
+        }
+
+        self.do_elect(None);
+    }
+
+    /// Start a PreVote round.
+    ///
+    /// It asks the voters whether they would grant the vote of the next term to this node.
+    /// Neither this node nor the voters persist a new vote during a PreVote round.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn pre_vote(&mut self) {
+        let mut pre_vote = PreVote::new(Vote::new(self.next_term(), self.config.id));
+        pre_vote.grant_by(self.config.id);
+
+        // Fast-path: if there is only one node in the cluster.
+
+        let quorum_set = self.state.membership_state.effective.membership.to_quorum_set::<QS>();
+        if pre_vote.is_granted(&quorum_set) {
+            self.pre_vote = None;
+            self.do_elect(None);
+            return;
+        }
+
+        // Slow-path: send pre-vote request, let a quorum grant it.
+
+        self.output.push_command(Command::SendPreVote {
+            vote_req: VoteRequest::new(pre_vote.vote, self.state.last_log_id().copied()),
+        });
+        self.pre_vote = Some(pre_vote);
+
+        // If a quorum can not be reached, retry after next election timeout.
+        self.output.push_command(Command::InstallElectionTimer { can_be_leader: true });
+    }
+
+    /// Increment the term and elect this node as leader, without PreVote.
+    ///
+    /// `leader_transfer` is the vote of the leader that asked this node to elect with a `TimeoutNow` request.
+    /// Voters then grant the vote even within the leader lease they granted to this leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn do_elect(&mut self, leader_transfer: Option<Vote<NID>>) {
+        self.elections += 1;
+        self.handle_vote_change(&Vote::new(self.next_term(), self.config.id)).unwrap();
 
         // Safe unwrap()
         let leader = self.internal_server_state.leading_mut().unwrap();
@@ -215,9 +331,10 @@ where
 
         // Slow-path: send vote request, let a quorum grant it.
 
-        self.output.push_command(Command::SendVote {
-            vote_req: VoteRequest::new(self.state.vote, self.state.last_log_id().copied()),
-        });
+        let mut vote_req = VoteRequest::new(self.state.vote, self.state.last_log_id().copied());
+        vote_req.leader_transfer = leader_transfer;
+
+        self.output.push_command(Command::SendVote { vote_req });
 
         // TODO: For compatibility. remove it. The runtime does not need to know about server state.
         self.update_server_state_if_changed();
@@ -259,6 +376,128 @@ where
         }
     }
 
+    /// Tell the candidate whether this node would grant its vote, without changing the state of this node.
+    ///
+    /// A PreVote request is granted if the request would be granted by [`Self::handle_vote_req`],
+    /// unless this node is a leader: an established leader does not let other nodes disrupt it.
+    ///
+    /// A follower that has heard from a live leader is checked by RaftCore, which owns the election timer.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_pre_vote_req(&self, req: VoteRequest<NID>) -> VoteResponse<NID> {
+        tracing::debug!(req = display(req.summary()), "Engine::handle_pre_vote_req");
+        tracing::debug!(
+            my_vote = display(self.state.vote.summary()),
+            my_last_log_id = display(self.state.last_log_id().summary()),
+            "Engine::handle_pre_vote_req"
+        );
+
+        let res = if req.last_log_id.as_ref() < self.state.last_log_id() {
+            Err(RejectVoteRequest::ByLastLogId(self.state.last_log_id().copied()))
+        } else if req.vote < self.state.vote || self.is_leader() {
+            Err(RejectVoteRequest::ByVote(self.state.vote))
+        } else {
+            Ok(())
+        };
+
+        let vote_granted = if let Err(reject) = res {
+            tracing::debug!(
+                req = display(req.summary()),
+                err = display(reject),
+                "reject pre-vote request"
+            );
+            false
+        } else {
+            true
+        };
+
+        VoteResponse {
+            // The vote is not changed by a PreVote request.
+            vote: self.state.vote,
+            vote_granted,
+            last_log_id: self.state.last_log_id().copied(),
+        }
+    }
+
+    /// Handle a TimeoutNow request from the leader that is transferring its leadership to this node.
+    ///
+    /// It starts an election at once, without a PreVote round, if the request is from the leader this node follows,
+    /// this node is a voter and its log is as up-to-date as the leader's.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn handle_timeout_now_req(&mut self, req: TimeoutNowRequest<NID>) -> TimeoutNowResponse<NID> {
+        tracing::debug!(req = display(req.summary()), "Engine::handle_timeout_now_req");
+        tracing::debug!(
+            my_vote = display(self.state.vote.summary()),
+            my_last_log_id = display(self.state.last_log_id().summary()),
+            "Engine::handle_timeout_now_req"
+        );
+
+        let elected = if req.vote != self.state.vote || !req.vote.committed {
+            tracing::debug!("ignore TimeoutNow: not from the current leader");
+            false
+        } else if self.is_leading() {
+            tracing::debug!("ignore TimeoutNow: already leading");
+            false
+        } else if !self.is_voter() {
+            tracing::debug!("ignore TimeoutNow: not a voter");
+            false
+        } else if self.is_witness() {
+            tracing::debug!("ignore TimeoutNow: a witness never becomes a leader");
+            false
+        } else if self.state.last_log_id() < req.last_log_id.as_ref() {
+            tracing::debug!("ignore TimeoutNow: log is not up-to-date");
+            false
+        } else {
+            self.pre_vote = None;
+            self.do_elect(Some(req.vote));
+            true
+        };
+
+        TimeoutNowResponse {
+            vote: self.state.vote,
+            elected,
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    pub(crate) fn handle_pre_vote_resp(&mut self, target: NID, resp: VoteResponse<NID>) {
+        tracing::debug!(
+            resp = display(resp.summary()),
+            target = display(target),
+            "handle_pre_vote_resp"
+        );
+        tracing::debug!(
+            my_vote = display(self.state.vote),
+            my_last_log_id = display(self.state.last_log_id().summary()),
+            "handle_pre_vote_resp"
+        );
+
+        // If there is no PreVote round in progress, just ignore the delayed pre_vote_resp.
+        let pre_vote = match &mut self.pre_vote {
+            Some(x) => x,
+            None => return,
+        };
+
+        if resp.vote_granted {
+            pre_vote.grant_by(target);
+
+            let quorum_set = self.state.membership_state.effective.membership.to_quorum_set::<QS>();
+            if pre_vote.is_granted(&quorum_set) {
+                tracing::debug!("quorum granted pre-vote, start election");
+                self.pre_vote = None;
+                self.do_elect(None);
+            }
+            return;
+        }
+
+        // pre-vote is rejected:
+        // Nothing is persisted. Just wait for the next election timeout to retry.
+
+        // Seen a higher log.
+        if resp.last_log_id.as_ref() > self.state.last_log_id() {
+            self.output.push_command(Command::InstallElectionTimer { can_be_leader: false });
+        }
+    }
+
     #[tracing::instrument(level = "debug", skip(self, resp))]
     pub(crate) fn handle_vote_resp(&mut self, target: NID, resp: VoteResponse<NID>) {
         tracing::debug!(
@@ -344,50 +583,20 @@ where
 
         self.output.push_command(Command::AppendInputEntries { range: 0..l });
 
-        // Fast commit:
-        // If the cluster has only one voter, then an entry will be committed as soon as it is appended.
-        // But if there is a membership log in the middle of the input entries, the condition to commit will change.
-        // Thus we have to deal with entries before and after a membership entry differently:
-        //
-        // When a membership entry is seen, update progress for all former entries.
-        // Then upgrade the quorum set for the Progress.
+        // The local progress is not updated here:
+        // The logs are not yet persisted when the AppendInputEntries command returns.
+        // The progress of the leader itself is updated by `update_local_progress()` when the logs are flushed.
+        // Thus the logs are replicated to followers while they are being flushed on the leader.
         //
-        // E.g., if the input entries are `2..6`, entry 4 changes membership from `a` to `abc`.
-        // Then it will output a LeaderCommit command to commit entries `2,3`.
-        // ```text
-        // 1 2 3 4 5 6
-        // a x x a y y
-        //       b
-        //       c
-        // ```
-        //
-        // If the input entries are `2..6`, entry 4 changes membership from `abc` to `a`.
-        // Then it will output a LeaderCommit command to commit entries `2,3,4,5,6`.
-        // ```text
-        // 1 2 3 4 5 6
-        // a x x a y y
-        // b
-        // c
-        // ```
+        // Since the effective membership is updated at once, the logs are committed with the last membership when
+        // the local progress and the progress of followers are updated.
         for entry in entries.iter() {
-            if let Some(_m) = entry.get_membership() {
-                let log_index = entry.get_log_id().index;
-
-                if log_index > 0 {
-                    if let Some(prev_log_id) = self.state.get_log_id(log_index - 1) {
-                        self.update_progress(self.config.id, Some(prev_log_id));
-                    }
-                }
-
-                // since this entry, the condition to commit has been changed.
-                self.update_effective_membership(entry.get_log_id(), _m);
+            if let Some(m) = entry.get_membership() {
+                self.update_effective_membership(entry.get_log_id(), m);
             }
         }
-        if let Some(last) = entries.last() {
-            self.update_progress(self.config.id, Some(*last.get_log_id()));
-        }
 
-        // Still need to replicate to learners, even when it is fast-committed.
+        // Replicate the logs while they are being flushed locally.
         self.output.push_command(Command::ReplicateEntries {
             upto: Some(*entries.last().unwrap().get_log_id()),
         });
@@ -737,6 +946,35 @@ where
         }
     }
 
+    /// Update the progress of the leader itself when its local logs upto `log_id` are flushed.
+    ///
+    /// Flush notifications may be delivered out of order, a stale one is ignored.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn update_local_progress(&mut self, log_id: Option<LogId<NID>>) {
+        tracing::debug!("update_local_progress: log_id:{:?}", log_id);
+
+        if log_id.is_none() {
+            return;
+        }
+
+        let leader = match self.internal_server_state.leading() {
+            None => return,
+            Some(x) => x,
+        };
+
+        let matching = match leader.progress.try_get(&self.config.id) {
+            // The leader is not a voter or learner, e.g., it has been removed by a membership change.
+            None => return,
+            Some(x) => x.matching,
+        };
+
+        if matching >= log_id {
+            return;
+        }
+
+        self.update_progress(self.config.id, log_id);
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn update_progress(&mut self, node_id: NID, log_id: Option<LogId<NID>>) {
         tracing::debug!("update_progress: node_id:{} log_id:{:?}", node_id, log_id);
@@ -812,8 +1050,96 @@ where
         }
     }
 
+    /// Update the time when the latest request acknowledged by `node_id` was sent.
+    ///
+    /// It is used to calculate the leader lease. It does nothing if this node is not leading.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn update_leader_ack_time(&mut self, node_id: NID, sent_at: Instant) {
+        if let Some(leader) = self.internal_server_state.leading_mut() {
+            if let Some(p) = leader.progress.get_mut(&node_id) {
+                p.update_last_ack(sent_at);
+            }
+        }
+    }
+
+    /// Return the log id a linearizable read has to wait to be applied before reading the state machine.
+    ///
+    /// It is the greater one of the `committed` and the first log id proposed by the current leader.
+    /// Before the first log of a leader is committed, the committed log id it knows may be stale.
+    pub(crate) fn read_log_id(&self) -> Option<LogId<NID>> {
+        let leader_id = self.state.vote.leader_id();
+
+        let leader_first = self.state.log_ids.key_log_ids().iter().find(|x| x.leader_id == leader_id).copied();
+
+        std::cmp::max(self.state.committed, leader_first)
+    }
+
+    /// Return if this node is a leader and its lease, i.e., `lease` since the last time it is acknowledged by a
+    /// quorum, has not yet expired at `now`.
+    pub(crate) fn is_leader_lease_valid(&self, now: Instant, lease: Duration) -> bool {
+        if !self.is_leader() {
+            return false;
+        }
+
+        let leader = match self.internal_server_state.leading() {
+            None => return false,
+            Some(x) => x,
+        };
+
+        match leader.last_quorum_acked_time(&self.config.id, now) {
+            None => false,
+            Some(t) => now < t + lease,
+        }
+    }
+
+    /// Leader steps down(revert to follower) if it has not been acknowledged by a quorum within `timeout`.
+    ///
+    /// `leading_since` is when this node became leader: a newly elected leader is given a full `timeout` to hear
+    /// from a quorum. It returns `true` if the leader stepped down.
+    ///
+    /// The vote is kept: this node does not grant any other leader in the same term, but it stops acting as a leader,
+    /// and will elect itself again after an election timeout if no other leader shows up.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn leader_check_quorum(&mut self, now: Instant, timeout: Duration, leading_since: Instant) -> bool {
+        if !self.is_leader() {
+            return false;
+        }
+
+        let leader = match self.internal_server_state.leading() {
+            None => return false,
+            Some(x) => x,
+        };
+
+        let since = match leader.last_quorum_acked_time(&self.config.id, now) {
+            Some(t) if t > leading_since => t,
+            _ => leading_since,
+        };
+
+        tracing::debug!(
+            "leader_check_quorum: node_id:{} quorum acked since: {:?}, now: {:?}",
+            self.config.id,
+            since,
+            now
+        );
+
+        if now < since + timeout {
+            return false;
+        }
+
+        tracing::info!(
+            "leader {} has not been acknowledged by a quorum for {:?}, step down",
+            self.config.id,
+            now - since
+        );
+
+        self.enter_following();
+        true
+    }
+
     /// Leader steps down(convert to learner) once the membership not containing it is committed.
     ///
+    /// A leader that is turned into a witness steps down too, once the membership is committed.
+    ///
     /// This is only called by leader.
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn leader_step_down(&mut self) {
@@ -832,7 +1158,7 @@ where
 
         #[allow(clippy::collapsible_if)]
         if em.log_id <= self.state.committed {
-            if !em.is_voter(&self.config.id) && self.is_leading() {
+            if (!em.is_voter(&self.config.id) || em.is_witness(&self.config.id)) && self.is_leading() {
                 tracing::debug!("leader {} is stepping down", self.config.id);
                 self.enter_following();
             }
@@ -940,6 +1266,26 @@ where
         self.state.enable_validate = old_validate;
     }
 
+    /// Install a snapshot provided by the application on a pristine node, e.g., to restore a cluster from a backup.
+    ///
+    /// The vote is kept as is: it must not be a vote of this node, which would let it act as a leader that is not
+    /// elected, nor a vote of another node, which is not granted by any election.
+    /// Thus the term of the vote may be less than the term of the last log in the snapshot.
+    /// An election starts from a term greater than both, see [`Self::next_term()`], thus a leader elected later
+    /// proposes logs greater than the ones in the snapshot.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn import_snapshot(&mut self, meta: SnapshotMeta<NID, N>) -> Result<(), NotAllowed<NID>> {
+        self.check_initialize()?;
+
+        self.install_snapshot(meta);
+        self.update_server_state_if_changed();
+
+        // A pristine node does not have an election timer installed.
+        self.output.push_command(Command::InstallElectionTimer { can_be_leader: true });
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn finish_building_snapshot(&mut self, meta: SnapshotMeta<NID, N>) {
         tracing::info!("finish_building_snapshot: {:?}", meta);
@@ -955,10 +1301,11 @@ where
 }
 
 /// Supporting util
-impl<NID, N> Engine<NID, N>
+impl<NID, N, QS> Engine<NID, N, QS>
 where
     N: Node,
     NID: NodeId,
+    QS: QuorumSet<NID> + From<Vec<NID>> + Debug + Clone + 'static,
 {
     /// Enter leading or following state by checking `vote`.
     ///
@@ -1053,7 +1400,6 @@ where
         };
         self.state.log_ids.append(log_id);
         self.output.push_command(Command::AppendBlankLog { log_id });
-        self.update_progress(self.config.id, Some(log_id));
         self.output.push_command(Command::ReplicateEntries { upto: Some(log_id) });
     }
 
@@ -1182,11 +1528,20 @@ where
         self.state.server_state = server_state;
     }
 
+    /// Returns the term for this node to elect with: greater than the term of the vote and of the last log.
+    ///
+    /// The term of the vote is no less than the term of any log, except after a snapshot is imported with
+    /// [`Self::import_snapshot()`], which keeps the vote as is.
+    pub(crate) fn next_term(&self) -> u64 {
+        let last_log_term = self.state.last_log_id().map(|x| x.leader_id.term).unwrap_or_default();
+        std::cmp::max(self.state.vote.term, last_log_term) + 1
+    }
+
     /// Check if a raft node is in a state that allows to initialize.
     ///
     /// It is allowed to initialize only when `last_log_id.is_none()` and `vote==(term=0, node_id=0)`.
     /// See: [Conditions for initialization](https://datafuselabs.github.io/openraft/cluster-formation.html#conditions-for-initialization)
-    fn check_initialize(&self) -> Result<(), NotAllowed<NID>> {
+    pub(crate) fn check_initialize(&self) -> Result<(), NotAllowed<NID>> {
         if self.state.last_log_id().is_none() && self.state.vote == Vote::default() {
             return Ok(());
         }
@@ -1266,6 +1621,9 @@ where
         if vote > &self.state.vote {
             self.state.vote = *vote;
             self.output.push_command(Command::SaveVote { vote: *vote });
+
+            // A PreVote round is meaningless once the vote changes.
+            self.pre_vote = None;
         }
 
         self.switch_internal_server_state();
@@ -1298,13 +1656,19 @@ where
         self.state.membership_state.is_voter(&self.config.id)
     }
 
+    fn is_witness(&self) -> bool {
+        self.state.membership_state.effective.is_witness(&self.config.id)
+    }
+
     /// The node is candidate or leader
+    ///
+    /// A leader that has stepped down keeps its vote, but it is no longer leading.
     fn is_leading(&self) -> bool {
-        self.state.vote.node_id == self.config.id
+        self.state.vote.node_id == self.config.id && self.internal_server_state.is_leading()
     }
 
     pub(crate) fn is_leader(&self) -> bool {
-        self.state.vote.node_id == self.config.id && self.state.vote.committed
+        self.is_leading() && self.state.vote.committed
     }
 
     // --- handlers ---
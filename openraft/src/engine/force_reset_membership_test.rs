@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::testing::Config;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::entry::EntryRef;
+use crate::progress::entry::ProgressEntry;
+use crate::raft_state::LogStateReader;
+use crate::EffectiveMembership;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+/// voters: {1,2,3}, learners: {4}
+fn m123_4() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4}))
+}
+
+/// voters: {1}, learners: {4}
+fn m1_4() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1}], Some(btreeset! {4}))
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.committed = Some(log_id(1, 1));
+    eng.state.vote = Vote::new_committed(2, 2);
+    eng.state.log_ids.append(log_id(1, 1));
+    eng.state.log_ids.append(log_id(2, 3));
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123_4()));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123_4()));
+    eng.state.server_state = eng.calc_server_state();
+    eng
+}
+
+#[test]
+fn test_force_reset_membership_by_follower() -> anyhow::Result<()> {
+    let mut eng = eng();
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+
+    let payload = EntryPayload::<Config>::Membership(m1_4());
+    let mut entries = [EntryRef::new(&payload)];
+
+    eng.force_reset_membership(3, &mut entries);
+
+    let want_log_id = LogId::new(LeaderId::new(3, 1), 4);
+
+    assert_eq!(Vote::new_committed(3, 1), eng.state.vote);
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+    assert_eq!(Some(&want_log_id), eng.state.last_log_id());
+    assert_eq!(Some(log_id(1, 1)), eng.state.committed, "not committed until flushed");
+
+    assert_eq!(
+        Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123_4())),
+        eng.state.membership_state.committed
+    );
+    assert_eq!(
+        Arc::new(EffectiveMembership::new(Some(want_log_id), m1_4())),
+        eng.state.membership_state.effective
+    );
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(3, 1)
+            },
+            Command::BecomeLeader,
+            Command::AppendInputEntries { range: 0..1 },
+            Command::UpdateMembership {
+                membership: Arc::new(EffectiveMembership::new(Some(want_log_id), m1_4())),
+            },
+            Command::UpdateReplicationStreams {
+                targets: vec![(4, ProgressEntry::empty(4))]
+            },
+            Command::ReplicateEntries {
+                upto: Some(want_log_id)
+            },
+            Command::MoveInputCursorBy { n: 1 },
+        ],
+        eng.output.commands
+    );
+
+    tracing::info!("--- the new membership is committed by this node alone");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(want_log_id));
+
+        assert_eq!(Some(want_log_id), eng.state.committed);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_force_reset_membership_by_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.switch_internal_server_state();
+    eng.state.server_state = eng.calc_server_state();
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+
+    let payload = EntryPayload::<Config>::Membership(m1_4());
+    let mut entries = [EntryRef::new(&payload)];
+
+    eng.force_reset_membership(3, &mut entries);
+
+    let want_log_id = LogId::new(LeaderId::new(3, 1), 4);
+
+    assert_eq!(Vote::new_committed(3, 1), eng.state.vote);
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(3, 1)
+            },
+            Command::AppendInputEntries { range: 0..1 },
+            Command::UpdateMembership {
+                membership: Arc::new(EffectiveMembership::new(Some(want_log_id), m1_4())),
+            },
+            Command::UpdateReplicationStreams {
+                targets: vec![(4, ProgressEntry::empty(4))]
+            },
+            Command::ReplicateEntries {
+                upto: Some(want_log_id)
+            },
+            Command::MoveInputCursorBy { n: 1 },
+        ],
+        eng.output.commands
+    );
+
+    Ok(())
+}
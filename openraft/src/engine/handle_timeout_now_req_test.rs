@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::raft::VoteRequest;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64, ()> {
+    Membership::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn m23() -> Membership<u64, ()> {
+    Membership::new(vec![btreeset! {2,3}], Some(btreeset! {1}))
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.config.enable_pre_vote = true;
+    eng.state.vote = Vote::new_committed(2, 2);
+    eng.state.server_state = ServerState::Follower;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng
+}
+
+#[test]
+fn test_handle_timeout_now_req_reject() -> anyhow::Result<()> {
+    let ignored = |eng: &Engine<u64, ()>, resp: TimeoutNowResponse<u64>, vote: Vote<u64>| {
+        assert_eq!(TimeoutNowResponse { vote, elected: false }, resp);
+        assert_eq!(vote, eng.state.vote);
+        assert_eq!(0, eng.output.commands.len());
+    };
+
+    tracing::info!("--- not from the current leader");
+    {
+        let mut eng = eng();
+
+        let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+            vote: Vote::new_committed(2, 3),
+            last_log_id: Some(log_id(2, 3)),
+        });
+        ignored(&eng, resp, Vote::new_committed(2, 2));
+    }
+
+    tracing::info!("--- vote is not committed");
+    {
+        let mut eng = eng();
+        eng.state.vote = Vote::new(2, 2);
+
+        let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+            vote: Vote::new(2, 2),
+            last_log_id: Some(log_id(2, 3)),
+        });
+        ignored(&eng, resp, Vote::new(2, 2));
+    }
+
+    tracing::info!("--- not a voter");
+    {
+        let mut eng = eng();
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m23()));
+
+        let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+            vote: Vote::new_committed(2, 2),
+            last_log_id: Some(log_id(2, 3)),
+        });
+        ignored(&eng, resp, Vote::new_committed(2, 2));
+    }
+
+    tracing::info!("--- log is not up-to-date");
+    {
+        let mut eng = eng();
+
+        let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+            vote: Vote::new_committed(2, 2),
+            last_log_id: Some(log_id(2, 4)),
+        });
+        ignored(&eng, resp, Vote::new_committed(2, 2));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_req_elect() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+        vote: Vote::new_committed(2, 2),
+        last_log_id: Some(log_id(2, 3)),
+    });
+
+    assert_eq!(
+        TimeoutNowResponse {
+            vote: Vote::new(3, 1),
+            elected: true
+        },
+        resp
+    );
+
+    // PreVote is skipped
+    assert_eq!(None, eng.pre_vote);
+    assert_eq!(Vote::new(3, 1), eng.state.vote);
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote { vote: Vote::new(3, 1) },
+            Command::SendVote {
+                vote_req: VoteRequest {
+                    vote: Vote::new(3, 1),
+                    last_log_id: Some(log_id(2, 3)),
+                    leader_transfer: Some(Vote::new_committed(2, 2)),
+                }
+            },
+            Command::InstallElectionTimer { can_be_leader: true },
+        ],
+        eng.output.commands
+    );
+
+    Ok(())
+}
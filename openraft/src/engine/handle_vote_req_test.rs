@@ -41,10 +41,7 @@ fn eng() -> Engine<u64, ()> {
 fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
     let mut eng = eng();
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(1, 2),
-        last_log_id: None,
-    });
+    let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(1, 2), None));
 
     assert_eq!(
         VoteResponse {
@@ -78,10 +75,7 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(3, 2),
-        last_log_id: Some(log_id(1, 3)),
-    });
+    let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(1, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -116,10 +110,7 @@ fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<(
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(2, 1),
-        last_log_id: Some(log_id(2, 3)),
-    });
+    let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(2, 1), Some(log_id(2, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -160,10 +151,7 @@ fn test_handle_vote_req_granted_greater_vote() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(3, 1),
-        last_log_id: Some(log_id(2, 3)),
-    });
+    let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -212,10 +200,7 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.state.server_state = st;
         eng.output.commands = vec![];
 
-        eng.handle_vote_req(VoteRequest {
-            vote: Vote::new(3, 1),
-            last_log_id: Some(log_id(2, 3)),
-        });
+        eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
         assert_eq!(st, eng.state.server_state);
         assert_eq!(
@@ -237,10 +222,7 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.state.server_state = st;
         eng.output.commands = vec![];
 
-        eng.handle_vote_req(VoteRequest {
-            vote: Vote::new(3, 1),
-            last_log_id: Some(log_id(2, 3)),
-        });
+        eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
         assert_eq!(st, eng.state.server_state);
         assert_eq!(
@@ -80,6 +80,7 @@ mod tests {
             last_log_id: Some(log_id(2, 2)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         };
         eng
     }
@@ -93,6 +94,7 @@ mod tests {
             last_log_id: Some(log_id(2, 2)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         });
 
         assert_eq!(false, got);
@@ -102,6 +104,7 @@ mod tests {
                 last_log_id: Some(log_id(2, 2)),
                 last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
                 snapshot_id: "1-2-3-4".to_string(),
+                metadata: (),
             },
             eng.state.snapshot_meta
         );
@@ -129,6 +132,7 @@ mod tests {
             last_log_id: Some(log_id(2, 3)),
             last_membership: EffectiveMembership::new(Some(log_id(2, 2)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         });
 
         assert_eq!(true, got);
@@ -138,6 +142,7 @@ mod tests {
                 last_log_id: Some(log_id(2, 3)),
                 last_membership: EffectiveMembership::new(Some(log_id(2, 2)), m1234()),
                 snapshot_id: "1-2-3-4".to_string(),
+                metadata: (),
             },
             eng.state.snapshot_meta
         );
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::error::NotAllowed;
+use crate::raft_state::LogStateReader;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::MetricsChangeFlags;
+use crate::SnapshotMeta;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m12() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2}], None)
+}
+
+fn meta() -> SnapshotMeta<u64, ()> {
+    SnapshotMeta {
+        last_log_id: Some(log_id(5, 6)),
+        last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
+        snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
+    }
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::<u64, ()> { ..Default::default() };
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.server_state = eng.calc_server_state();
+    eng
+}
+
+#[test]
+fn test_import_snapshot_pristine() -> anyhow::Result<()> {
+    tracing::info!("--- the last log in the snapshot is proposed by this node: the vote is kept");
+    {
+        let mut eng = eng();
+
+        eng.import_snapshot(meta())?;
+
+        assert_eq!(meta(), eng.state.snapshot_meta);
+        assert_eq!(Some(&log_id(5, 6)), eng.state.last_log_id());
+        assert_eq!(Some(log_id(5, 6)), eng.state.committed);
+        assert_eq!(
+            Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
+            eng.state.membership_state.effective
+        );
+
+        // It does not vote for the leader of the last log in the snapshot, which is this node.
+        assert_eq!(Vote::default(), eng.state.vote);
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(6, eng.next_term(), "elect with a term greater than the last log");
+
+        assert_eq!(
+            MetricsChangeFlags {
+                replication: false,
+                local_data: true,
+                cluster: true,
+            },
+            eng.output.metrics_flags
+        );
+
+        assert_eq!(expected_commands(), eng.output.commands);
+    }
+
+    tracing::info!("--- the last log in the snapshot is proposed by another node: the vote is kept");
+    {
+        let mut eng = eng();
+        eng.config.id = 2;
+        eng.state.server_state = eng.calc_server_state();
+
+        eng.import_snapshot(meta())?;
+
+        assert_eq!(Some(log_id(5, 6)), eng.state.committed);
+        assert_eq!(Vote::default(), eng.state.vote);
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+
+        assert_eq!(expected_commands(), eng.output.commands);
+    }
+
+    tracing::info!("--- elect after importing");
+    {
+        let mut eng = eng();
+        eng.config.id = 2;
+        eng.state.server_state = eng.calc_server_state();
+
+        eng.import_snapshot(meta())?;
+        eng.output.commands = vec![];
+        eng.elect();
+
+        assert_eq!(Vote::new(6, 2), eng.state.vote);
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+    }
+
+    Ok(())
+}
+
+fn expected_commands() -> Vec<Command<u64, ()>> {
+    vec![
+        Command::UpdateMembership {
+            membership: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12())),
+        },
+        Command::InstallSnapshot { snapshot_meta: meta() },
+        Command::PurgeLog { upto: log_id(5, 6) },
+        Command::InstallElectionTimer { can_be_leader: true },
+    ]
+}
+
+#[test]
+fn test_import_snapshot_not_pristine() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.vote = Vote::new(1, 2);
+
+    let res = eng.import_snapshot(meta());
+
+    assert_eq!(
+        Err(NotAllowed {
+            last_log_id: None,
+            vote: Vote::new(1, 2),
+        }),
+        res
+    );
+    assert_eq!(Vote::new(1, 2), eng.state.vote);
+    assert_eq!(None, eng.state.committed);
+    assert_eq!(0, eng.output.commands.len());
+
+    Ok(())
+}
@@ -102,19 +102,6 @@ fn test_initialize_single_node() -> anyhow::Result<()> {
                         index: 1,
                     },
                 },
-                Command::ReplicateCommitted {
-                    committed: Some(LogId {
-                        leader_id: LeaderId { term: 1, node_id: 1 },
-                        index: 1,
-                    },),
-                },
-                Command::LeaderCommit {
-                    already_committed: None,
-                    upto: LogId {
-                        leader_id: LeaderId { term: 1, node_id: 1 },
-                        index: 1,
-                    },
-                },
                 Command::ReplicateEntries {
                     upto: Some(LogId {
                         leader_id: LeaderId { term: 1, node_id: 1 },
@@ -198,6 +185,7 @@ fn test_initialize() -> anyhow::Result<()> {
                             leader_id: LeaderId { term: 0, node_id: 0 },
                             index: 0,
                         },),
+                        leader_transfer: None,
                     },
                 },
                 Command::InstallElectionTimer { can_be_leader: true },
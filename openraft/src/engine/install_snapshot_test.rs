@@ -44,6 +44,7 @@ fn eng() -> Engine<u64, ()> {
         last_log_id: Some(log_id(2, 2)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     };
     eng.state.server_state = eng.calc_server_state();
 
@@ -59,6 +60,7 @@ fn test_install_snapshot_lt_last_snapshot() -> anyhow::Result<()> {
         last_log_id: Some(log_id(2, 2)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     });
 
     assert_eq!(
@@ -66,6 +68,7 @@ fn test_install_snapshot_lt_last_snapshot() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 2)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         },
         eng.state.snapshot_meta
     );
@@ -85,6 +88,7 @@ fn test_install_snapshot_lt_last_snapshot() -> anyhow::Result<()> {
                 last_log_id: Some(log_id(2, 2)),
                 last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
                 snapshot_id: "1-2-3-4".to_string(),
+                metadata: (),
             }
         }],
         eng.output.commands
@@ -104,6 +108,7 @@ fn test_install_snapshot_lt_committed() -> anyhow::Result<()> {
         last_log_id: Some(log_id(4, 5)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     });
 
     assert_eq!(
@@ -111,6 +116,7 @@ fn test_install_snapshot_lt_committed() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 2)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         },
         eng.state.snapshot_meta
     );
@@ -130,6 +136,7 @@ fn test_install_snapshot_lt_committed() -> anyhow::Result<()> {
                 last_log_id: Some(log_id(4, 5)),
                 last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
                 snapshot_id: "1-2-3-4".to_string(),
+                metadata: (),
             }
         }],
         eng.output.commands
@@ -147,6 +154,7 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
         last_log_id: Some(log_id(4, 6)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     });
 
     assert_eq!(
@@ -154,6 +162,7 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(4, 6)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         },
         eng.state.snapshot_meta
     );
@@ -184,6 +193,7 @@ fn test_install_snapshot_not_conflict() -> anyhow::Result<()> {
                     last_log_id: Some(log_id(4, 6)),
                     last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
                     snapshot_id: "1-2-3-4".to_string(),
+                    metadata: (),
                 }
             },
             Command::PurgeLog { upto: log_id(4, 6) },
@@ -215,6 +225,7 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(2, 2)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m12()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         };
 
         eng.state.server_state = eng.calc_server_state();
@@ -226,6 +237,7 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
         last_log_id: Some(log_id(5, 6)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     });
 
     assert_eq!(
@@ -233,6 +245,7 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
             last_log_id: Some(log_id(5, 6)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         },
         eng.state.snapshot_meta
     );
@@ -264,6 +277,7 @@ fn test_install_snapshot_conflict() -> anyhow::Result<()> {
                     last_log_id: Some(log_id(5, 6)),
                     last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
                     snapshot_id: "1-2-3-4".to_string(),
+                    metadata: (),
                 }
             },
             Command::PurgeLog { upto: log_id(5, 6) },
@@ -283,6 +297,7 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
         last_log_id: Some(log_id(100, 100)),
         last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
         snapshot_id: "1-2-3-4".to_string(),
+        metadata: (),
     });
 
     assert_eq!(
@@ -290,6 +305,7 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
             last_log_id: Some(log_id(100, 100)),
             last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
             snapshot_id: "1-2-3-4".to_string(),
+            metadata: (),
         },
         eng.state.snapshot_meta
     );
@@ -324,6 +340,7 @@ fn test_install_snapshot_advance_last_log_id() -> anyhow::Result<()> {
                     last_log_id: Some(log_id(100, 100)),
                     last_membership: EffectiveMembership::new(Some(log_id(1, 1)), m1234()),
                     snapshot_id: "1-2-3-4".to_string(),
+                    metadata: (),
                 }
             },
             Command::PurgeLog { upto: log_id(100, 100) },
@@ -20,7 +20,7 @@ use crate::MetricsChangeFlags;
 use crate::Vote;
 
 crate::declare_raft_types!(
-    pub(crate) Foo: D=(), R=(), NodeId=u64, Node=()
+    pub(crate) Foo: D=(), R=(), NodeId=u64, Node=(), QuorumSet = crate::quorum::Majority<u64>, SnapshotMetadata = ()
 );
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
@@ -195,12 +195,16 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
     );
     assert_eq!(
         MembershipState {
-            committed: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01())),
             effective: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
         },
         eng.state.membership_state
     );
-    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+    assert_eq!(
+        Some(log_id(0, 0)),
+        eng.state.committed,
+        "not committed until the logs are flushed"
+    );
 
     assert_eq!(
         MetricsChangeFlags {
@@ -214,13 +218,6 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
     assert_eq!(
         vec![
             Command::AppendInputEntries { range: 0..3 },
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 6))
-            },
-            Command::LeaderCommit {
-                already_committed: Some(log_id(0, 0)),
-                upto: LogId::new(LeaderId::new(3, 1), 6)
-            },
             Command::ReplicateEntries {
                 upto: Some(log_id(3, 6))
             },
@@ -229,13 +226,41 @@ fn test_leader_append_entries_fast_commit() -> anyhow::Result<()> {
         eng.output.commands
     );
 
+    tracing::info!("--- logs are flushed, commit");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(log_id(3, 6)));
+
+        assert_eq!(
+            MembershipState {
+                committed: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
+                effective: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
+            },
+            eng.state.membership_state
+        );
+        assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(3, 6))
+                },
+                Command::LeaderCommit {
+                    already_committed: Some(log_id(0, 0)),
+                    upto: LogId::new(LeaderId::new(3, 1), 6)
+                },
+            ],
+            eng.output.commands
+        );
+    }
+
     Ok(())
 }
 
-/// With membership log, fast-commit upto membership entry that is not a single-node cluster.
-/// Leader is no longer a voter should work.
+/// With membership log, the leader is no longer a voter.
+/// Flushing the logs on the leader does not commit any log.
 #[test]
-fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Result<()> {
+fn test_leader_append_entries_leader_is_not_a_voter() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1()));
     eng.new_leader();
@@ -265,8 +290,7 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
     );
     assert_eq!(
         MembershipState {
-            // previous effective become committed.
-            committed: Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m1())),
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01())),
             // new effective.
             effective: Arc::new(EffectiveMembership::new(
                 Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -275,7 +299,7 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
         },
         eng.state.membership_state
     );
-    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 4)), eng.state.committed);
+    assert_eq!(Some(log_id(0, 0)), eng.state.committed);
 
     assert_eq!(
         MetricsChangeFlags {
@@ -289,13 +313,6 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
     assert_eq!(
         vec![
             Command::AppendInputEntries { range: 0..3 },
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 4))
-            },
-            Command::LeaderCommit {
-                already_committed: Some(log_id(0, 0)),
-                upto: LogId::new(LeaderId::new(3, 1), 4)
-            },
             Command::UpdateMembership {
                 membership: Arc::new(EffectiveMembership::new(
                     Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -313,6 +330,15 @@ fn test_leader_append_entries_fast_commit_upto_membership_entry() -> anyhow::Res
         eng.output.commands
     );
 
+    tracing::info!("--- logs are flushed, the leader is not in the progress, nothing is committed");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(log_id(3, 6)));
+
+        assert_eq!(Some(log_id(0, 0)), eng.state.committed);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
     Ok(())
 }
 
@@ -349,10 +375,7 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
     );
     assert_eq!(
         MembershipState {
-            committed: Arc::new(EffectiveMembership::new(
-                Some(LogId::new(LeaderId::new(3, 1), 5)),
-                m1_2()
-            )),
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01())),
             effective: Arc::new(EffectiveMembership::new(
                 Some(LogId::new(LeaderId::new(3, 1), 5)),
                 m1_2()
@@ -360,7 +383,7 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
         },
         eng.state.membership_state
     );
-    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+    assert_eq!(Some(log_id(0, 0)), eng.state.committed);
 
     assert_eq!(
         MetricsChangeFlags {
@@ -374,14 +397,6 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
     assert_eq!(
         vec![
             Command::AppendInputEntries { range: 0..3 },
-            // first commit upto the membership entry(exclusive).
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 4))
-            },
-            Command::LeaderCommit {
-                already_committed: Some(log_id(0, 0)),
-                upto: LogId::new(LeaderId::new(3, 1), 4)
-            },
             Command::UpdateMembership {
                 membership: Arc::new(EffectiveMembership::new(
                     Some(LogId::new(LeaderId::new(3, 1), 5)),
@@ -391,14 +406,6 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
             Command::UpdateReplicationStreams {
                 targets: vec![(2, ProgressEntry::empty(7))]
             },
-            // second commit upto the end.
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 6))
-            },
-            Command::LeaderCommit {
-                already_committed: Some(LogId::new(LeaderId::new(3, 1), 4)),
-                upto: LogId::new(LeaderId::new(3, 1), 6)
-            },
             Command::ReplicateEntries {
                 upto: Some(log_id(3, 6))
             },
@@ -407,6 +414,40 @@ fn test_leader_append_entries_fast_commit_membership_no_voter_change() -> anyhow
         eng.output.commands
     );
 
+    tracing::info!("--- logs are flushed, commit all with the new membership");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(log_id(3, 6)));
+
+        assert_eq!(
+            MembershipState {
+                committed: Arc::new(EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(3, 1), 5)),
+                    m1_2()
+                )),
+                effective: Arc::new(EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(3, 1), 5)),
+                    m1_2()
+                ))
+            },
+            eng.state.membership_state
+        );
+        assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(3, 6))
+                },
+                Command::LeaderCommit {
+                    already_committed: Some(log_id(0, 0)),
+                    upto: LogId::new(LeaderId::new(3, 1), 6)
+                },
+            ],
+            eng.output.commands
+        );
+    }
+
     Ok(())
 }
 
@@ -445,10 +486,7 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
     );
     assert_eq!(
         MembershipState {
-            committed: Arc::new(EffectiveMembership::new(
-                Some(LogId::new(LeaderId::new(3, 1), 5)),
-                m1_2()
-            )),
+            committed: Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01())),
             effective: Arc::new(EffectiveMembership::new(
                 Some(LogId::new(LeaderId::new(3, 1), 5)),
                 m1_2()
@@ -456,7 +494,7 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
         },
         eng.state.membership_state
     );
-    assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+    assert_eq!(Some(log_id(0, 0)), eng.state.committed);
 
     assert_eq!(
         MetricsChangeFlags {
@@ -479,14 +517,6 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
             Command::UpdateReplicationStreams {
                 targets: vec![(2, ProgressEntry::empty(7))]
             },
-            // It is correct to commit if the membership change ot a one node cluster.
-            Command::ReplicateCommitted {
-                committed: Some(log_id(3, 6))
-            },
-            Command::LeaderCommit {
-                already_committed: Some(log_id(0, 0)),
-                upto: LogId::new(LeaderId::new(3, 1), 6)
-            },
             Command::ReplicateEntries {
                 upto: Some(log_id(3, 6))
             },
@@ -495,5 +525,39 @@ fn test_leader_append_entries_fast_commit_if_membership_voter_change_to_1() -> a
         eng.output.commands
     );
 
+    tracing::info!("--- logs are flushed, commit all with the new membership");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(log_id(3, 6)));
+
+        assert_eq!(
+            MembershipState {
+                committed: Arc::new(EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(3, 1), 5)),
+                    m1_2()
+                )),
+                effective: Arc::new(EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(3, 1), 5)),
+                    m1_2()
+                ))
+            },
+            eng.state.membership_state
+        );
+        assert_eq!(Some(LogId::new(LeaderId::new(3, 1), 6)), eng.state.committed);
+
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(3, 6))
+                },
+                Command::LeaderCommit {
+                    already_committed: Some(log_id(0, 0)),
+                    upto: LogId::new(LeaderId::new(3, 1), 6)
+                },
+            ],
+            eng.output.commands
+        );
+    }
+
     Ok(())
 }
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.server_state = ServerState::Leader;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+    eng
+}
+
+#[test]
+fn test_leader_check_quorum_not_leader() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let timeout = Duration::from_millis(100);
+
+    tracing::info!("--- not leading: nothing to do");
+    {
+        let mut eng = eng();
+        eng.state.server_state = ServerState::Follower;
+
+        let eng0 = eng.clone();
+        assert!(!eng.leader_check_quorum(t0 + timeout, timeout, t0));
+        assert_eq!(eng0, eng, "nothing changed");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_check_quorum() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let ms = Duration::from_millis;
+    let timeout = ms(100);
+
+    let mut eng = eng();
+    eng.new_leader();
+
+    tracing::info!("--- no ack yet, but a new leader is given a full timeout");
+    {
+        assert!(!eng.leader_check_quorum(t0 + ms(99), timeout, t0));
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- acked by a quorum: the timeout is counted from the ack");
+    {
+        eng.update_leader_ack_time(2, t0 + ms(50));
+
+        assert!(!eng.leader_check_quorum(t0 + ms(149), timeout, t0));
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- no quorum acked within timeout: step down");
+    {
+        assert!(eng.leader_check_quorum(t0 + ms(150), timeout, t0));
+
+        assert_eq!(Vote::new_committed(2, 1), eng.state.vote, "vote is kept");
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert!(!eng.is_leader());
+        assert!(eng.internal_server_state.is_following());
+
+        assert_eq!(
+            vec![
+                //
+                Command::InstallElectionTimer { can_be_leader: false },
+                Command::QuitLeader,
+            ],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- already stepped down: nothing to do");
+    {
+        eng.output.commands = vec![];
+
+        assert!(!eng.leader_check_quorum(t0 + ms(300), timeout, t0));
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::engine::Engine;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m12345() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3,4,5}], None)
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m12345()));
+    eng
+}
+
+#[test]
+fn test_leader_lease_not_leader() -> anyhow::Result<()> {
+    let now = Instant::now();
+    let lease = Duration::from_millis(100);
+
+    tracing::info!("--- not leading: ack time is ignored");
+    {
+        let mut eng = eng();
+
+        let eng0 = eng.clone();
+        eng.update_leader_ack_time(2, now);
+        assert_eq!(eng0, eng, "nothing changed");
+
+        assert!(!eng.is_leader_lease_valid(now, lease));
+    }
+
+    tracing::info!("--- vote is not committed: no lease");
+    {
+        let mut eng = eng();
+        eng.new_leader();
+        eng.state.vote = Vote::new(2, 1);
+
+        eng.update_leader_ack_time(2, now);
+        eng.update_leader_ack_time(3, now);
+
+        assert!(!eng.is_leader_lease_valid(now, lease));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_lease() -> anyhow::Result<()> {
+    let t0 = Instant::now();
+    let ms = Duration::from_millis;
+    let lease = ms(100);
+
+    let mut eng = eng();
+    eng.new_leader();
+
+    tracing::info!("--- no ack yet");
+    {
+        assert!(!eng.is_leader_lease_valid(t0, lease));
+    }
+
+    tracing::info!("--- acked by less than a quorum");
+    {
+        eng.update_leader_ack_time(2, t0 + ms(10));
+        assert!(!eng.is_leader_lease_valid(t0 + ms(10), lease));
+    }
+
+    tracing::info!("--- acked by a quorum: lease starts from the earliest ack in the quorum");
+    {
+        eng.update_leader_ack_time(3, t0 + ms(20));
+
+        assert!(eng.is_leader_lease_valid(t0 + ms(20), lease));
+        assert!(eng.is_leader_lease_valid(t0 + ms(109), lease));
+        assert!(!eng.is_leader_lease_valid(t0 + ms(110), lease));
+    }
+
+    tracing::info!("--- a stale ack does not shorten the lease");
+    {
+        eng.update_leader_ack_time(3, t0 + ms(5));
+        assert!(eng.is_leader_lease_valid(t0 + ms(109), lease));
+    }
+
+    tracing::info!("--- later acks extend the lease");
+    {
+        eng.update_leader_ack_time(4, t0 + ms(50));
+        eng.update_leader_ack_time(5, t0 + ms(60));
+
+        // The latest quorum: 1(now), 5(60), 4(50)
+        assert!(eng.is_leader_lease_valid(t0 + ms(149), lease));
+        assert!(!eng.is_leader_lease_valid(t0 + ms(150), lease));
+    }
+
+    Ok(())
+}
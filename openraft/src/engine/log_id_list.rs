@@ -3,7 +3,8 @@ use crate::storage::StorageHelper;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::NodeId;
-use crate::RaftStorage;
+use crate::RaftLogStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
 use crate::StorageError;
 
@@ -43,14 +44,15 @@ where NID: NodeId
     /// A-------B-------C : find(A,B); find(B,C)   // both find `B`, need to de-dup
     /// A-------C-------C : find(A,C)
     /// ```
-    pub(crate) async fn load_log_ids<C, Sto>(
+    pub(crate) async fn load_log_ids<C, LS, SM>(
         last_purged_log_id: Option<LogId<NID>>,
         last_log_id: Option<LogId<NID>>,
-        sto: &mut StorageHelper<'_, C, Sto>,
+        sto: &mut StorageHelper<'_, C, LS, SM>,
     ) -> Result<LogIdList<NID>, StorageError<NID>>
     where
         C: RaftTypeConfig<NodeId = NID>,
-        Sto: RaftStorage<C>,
+        LS: RaftLogStorage<C>,
+        SM: RaftStateMachine<C>,
     {
         let mut res = vec![];
 
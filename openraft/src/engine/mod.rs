@@ -30,26 +30,35 @@ mod command;
 mod engine_impl;
 mod handler;
 mod log_id_list;
+mod pre_vote;
 
 #[cfg(test)] mod calc_purge_upto_test;
 #[cfg(test)] mod elect_test;
 #[cfg(test)] mod follower_commit_entries_test;
 #[cfg(test)] mod follower_do_append_entries_test;
+#[cfg(test)] mod force_reset_membership_test;
 #[cfg(test)] mod handle_append_entries_req_test;
+#[cfg(test)] mod handle_timeout_now_req_test;
 #[cfg(test)] mod handle_vote_req_test;
 #[cfg(test)] mod handle_vote_resp_test;
+#[cfg(test)] mod import_snapshot_test;
 #[cfg(test)] mod initialize_test;
 #[cfg(test)] mod install_snapshot_test;
 #[cfg(test)] mod internal_handle_vote_req_test;
 #[cfg(test)] mod leader_append_entries_test;
+#[cfg(test)] mod leader_check_quorum_test;
+#[cfg(test)] mod leader_lease_test;
 #[cfg(test)] mod log_id_list_test;
+#[cfg(test)] mod pre_vote_test;
 #[cfg(test)] mod purge_log_test;
+#[cfg(test)] mod read_log_id_test;
 #[cfg(test)] mod startup_test;
 #[cfg(test)] mod testing;
 #[cfg(test)] mod truncate_logs_test;
 #[cfg(test)] mod update_committed_membership_test;
 #[cfg(test)] mod update_effective_membership_test;
 #[cfg(test)] mod update_progress_test;
+#[cfg(test)] mod witness_test;
 
 pub(crate) use command::Command;
 pub(crate) use engine_impl::Engine;
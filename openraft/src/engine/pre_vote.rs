@@ -0,0 +1,39 @@
+use std::collections::BTreeSet;
+
+use crate::quorum::QuorumSet;
+use crate::NodeId;
+use crate::Vote;
+
+/// State of a PreVote round.
+///
+/// Before starting an election, a node asks the voters whether they would grant `vote`, which is the vote it is going
+/// to use in the election. No vote is persisted by either side during a PreVote round.
+/// A real election starts only when a quorum would grant `vote`.
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct PreVote<NID: NodeId> {
+    /// The vote this node would use if it starts a real election.
+    pub(crate) vote: Vote<NID>,
+
+    /// Which nodes would grant `vote`.
+    pub(crate) granted_by: BTreeSet<NID>,
+}
+
+impl<NID: NodeId> PreVote<NID> {
+    pub(crate) fn new(vote: Vote<NID>) -> Self {
+        Self {
+            vote,
+            granted_by: BTreeSet::new(),
+        }
+    }
+
+    /// Update that a node would grant the vote.
+    pub(crate) fn grant_by(&mut self, target: NID) {
+        self.granted_by.insert(target);
+    }
+
+    /// Return if an election quorum of `quorum_set` would grant the vote.
+    pub(crate) fn is_granted<QS: QuorumSet<NID>>(&self, quorum_set: &QS) -> bool {
+        quorum_set.is_election_quorum(self.granted_by.iter())
+    }
+}
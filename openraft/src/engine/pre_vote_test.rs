@@ -0,0 +1,435 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::pre_vote::PreVote;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::quorum::Weighted;
+use crate::quorum::Weights;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::MetricsChangeFlags;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m1() -> Membership<u64, ()> {
+    Membership::new(vec![btreeset! {1}], None)
+}
+
+fn m123() -> Membership<u64, ()> {
+    Membership::new(vec![btreeset! {1,2,3}], None)
+}
+
+fn m1234() -> Membership<u64, ()> {
+    Membership::new(vec![btreeset! {1,2,3,4}], None)
+}
+
+/// A flexible quorum set: a replication quorum is 2 of 4 nodes, an election quorum is 3 of 4 nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Flexible;
+
+impl Weights<u64> for Flexible {
+    fn weight(_id: &u64) -> u64 {
+        1
+    }
+
+    fn replication_quorum(total: u64) -> u64 {
+        (total + 1) / 2
+    }
+
+    fn election_quorum(total: u64) -> u64 {
+        total - Self::replication_quorum(total) + 1
+    }
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+    eng.config.id = 1;
+    eng.config.enable_pre_vote = true;
+    eng
+}
+
+#[test]
+fn test_pre_vote_elect() -> anyhow::Result<()> {
+    tracing::info!("--- single node: pre-vote is granted at once, become leader at once");
+    {
+        let mut eng = eng();
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m1()));
+
+        eng.elect();
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new_committed(1, 1), eng.state.vote);
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(1, 1) },
+                Command::SaveVote {
+                    vote: Vote::new_committed(1, 1)
+                },
+                Command::BecomeLeader,
+                Command::UpdateReplicationStreams { targets: vec![] },
+                Command::AppendBlankLog {
+                    log_id: LogId {
+                        leader_id: LeaderId { term: 1, node_id: 1 },
+                        index: 0,
+                    },
+                },
+                Command::ReplicateEntries {
+                    upto: Some(LogId {
+                        leader_id: LeaderId { term: 1, node_id: 1 },
+                        index: 0,
+                    },),
+                },
+            ],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- multi nodes: send pre-vote, vote is not changed");
+    {
+        let mut eng = eng();
+        eng.state.vote = Vote::new_committed(1, 2);
+        eng.state.server_state = ServerState::Follower;
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m123()));
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+
+        eng.elect();
+
+        assert_eq!(
+            Some(PreVote {
+                vote: Vote::new(2, 1),
+                granted_by: btreeset! {1},
+            }),
+            eng.pre_vote
+        );
+        assert_eq!(Vote::new_committed(1, 2), eng.state.vote);
+        assert!(eng.internal_server_state.is_following());
+
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(
+            MetricsChangeFlags {
+                replication: false,
+                local_data: false,
+                cluster: false,
+            },
+            eng.output.metrics_flags
+        );
+
+        assert_eq!(
+            vec![
+                Command::SendPreVote {
+                    vote_req: VoteRequest::new(Vote::new(2, 1), Some(log_id(1, 1)))
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- pre-vote disabled: increment term at once");
+    {
+        let mut eng = eng();
+        eng.config.enable_pre_vote = false;
+        eng.state.vote = Vote::new_committed(1, 2);
+        eng.state.server_state = ServerState::Follower;
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m123()));
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+
+        eng.elect();
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(2, 1) },
+                Command::SendVote {
+                    vote_req: VoteRequest::new(Vote::new(2, 1), Some(log_id(1, 1)))
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.output.commands
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req() -> anyhow::Result<()> {
+    let follower = || {
+        let mut eng = eng();
+        eng.state.vote = Vote::new_committed(2, 3);
+        eng.state.server_state = ServerState::Follower;
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+        eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+        eng
+    };
+
+    tracing::info!("--- greater vote and up-to-date log: granted, without changing vote");
+    {
+        let eng = follower();
+
+        let resp = eng.handle_pre_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 3))));
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new_committed(2, 3),
+                vote_granted: true,
+                last_log_id: Some(log_id(2, 3)),
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new_committed(2, 3), eng.state.vote);
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(MetricsChangeFlags::default(), eng.output.metrics_flags);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- smaller last_log_id: rejected");
+    {
+        let eng = follower();
+
+        let resp = eng.handle_pre_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(1, 3))));
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new_committed(2, 3),
+                vote_granted: false,
+                last_log_id: Some(log_id(2, 3)),
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new_committed(2, 3), eng.state.vote);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- smaller vote: rejected");
+    {
+        let eng = follower();
+
+        let resp = eng.handle_pre_vote_req(VoteRequest::new(Vote::new(2, 2), Some(log_id(2, 3))));
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new_committed(2, 3),
+                vote_granted: false,
+                last_log_id: Some(log_id(2, 3)),
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new_committed(2, 3), eng.state.vote);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- an established leader rejects pre-vote");
+    {
+        let mut eng = follower();
+        eng.state.vote = Vote::new_committed(2, 1);
+        eng.state.server_state = ServerState::Leader;
+        eng.new_leader();
+
+        let resp = eng.handle_pre_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 3))));
+
+        assert_eq!(
+            VoteResponse {
+                vote: Vote::new_committed(2, 1),
+                vote_granted: false,
+                last_log_id: Some(log_id(2, 3)),
+            },
+            resp
+        );
+
+        assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_resp() -> anyhow::Result<()> {
+    let pre_voting = || {
+        let mut eng = eng();
+        eng.state.vote = Vote::new_committed(1, 2);
+        eng.state.server_state = ServerState::Follower;
+        eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m123()));
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+        eng.pre_vote = Some(PreVote {
+            vote: Vote::new(2, 1),
+            granted_by: btreeset! {1},
+        });
+        eng
+    };
+
+    tracing::info!("--- not in pre-vote. just ignore");
+    {
+        let mut eng = pre_voting();
+        eng.pre_vote = None;
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: true,
+            last_log_id: Some(log_id(1, 1)),
+        });
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new_committed(1, 2), eng.state.vote);
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- rejected by higher last_log_id. nothing is persisted, sleep longer");
+    {
+        let mut eng = pre_voting();
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: false,
+            last_log_id: Some(log_id(1, 2)),
+        });
+
+        assert_eq!(
+            Some(PreVote {
+                vote: Vote::new(2, 1),
+                granted_by: btreeset! {1},
+            }),
+            eng.pre_vote
+        );
+        assert_eq!(Vote::new_committed(1, 2), eng.state.vote);
+        assert!(eng.internal_server_state.is_following());
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+
+        assert_eq!(
+            MetricsChangeFlags {
+                replication: false,
+                local_data: false,
+                cluster: false,
+            },
+            eng.output.metrics_flags
+        );
+        assert_eq!(
+            vec![Command::InstallElectionTimer { can_be_leader: false }],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- granted by a quorum. start a real election");
+    {
+        let mut eng = pre_voting();
+
+        eng.handle_pre_vote_resp(2, VoteResponse {
+            vote: Vote::new_committed(1, 2),
+            vote_granted: true,
+            last_log_id: Some(log_id(1, 1)),
+        });
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
+        assert_eq!(
+            Some(btreeset! {1},),
+            eng.internal_server_state.leading().map(|x| x.vote_granted_by.clone())
+        );
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+
+        assert_eq!(
+            MetricsChangeFlags {
+                replication: false,
+                local_data: true,
+                cluster: false,
+            },
+            eng.output.metrics_flags
+        );
+        assert_eq!(
+            vec![
+                Command::SaveVote { vote: Vote::new(2, 1) },
+                Command::SendVote {
+                    vote_req: VoteRequest::new(Vote::new(2, 1), Some(log_id(1, 1)))
+                },
+                Command::InstallElectionTimer { can_be_leader: true },
+            ],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- vote changed. pre-vote is cleared");
+    {
+        let mut eng = pre_voting();
+
+        eng.handle_vote_change(&Vote::new(3, 3))?;
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new(3, 3), eng.state.vote);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_resp_election_quorum() -> anyhow::Result<()> {
+    let mut eng = Engine::<u64, (), Weighted<u64, Flexible>>::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+    eng.config.id = 1;
+    eng.config.enable_pre_vote = true;
+    eng.state.vote = Vote::new_committed(1, 2);
+    eng.state.server_state = ServerState::Follower;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(0, 1)), m1234()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(1, 1)]);
+    eng.pre_vote = Some(PreVote {
+        vote: Vote::new(2, 1),
+        granted_by: btreeset! {1},
+    });
+
+    let granted = VoteResponse {
+        vote: Vote::new_committed(1, 2),
+        vote_granted: true,
+        last_log_id: Some(log_id(1, 1)),
+    };
+
+    tracing::info!("--- granted by a replication quorum, but not an election quorum: keep waiting");
+    {
+        eng.handle_pre_vote_resp(2, granted.clone());
+
+        assert_eq!(
+            Some(PreVote {
+                vote: Vote::new(2, 1),
+                granted_by: btreeset! {1,2},
+            }),
+            eng.pre_vote
+        );
+        assert_eq!(Vote::new_committed(1, 2), eng.state.vote);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- granted by an election quorum: start a real election");
+    {
+        eng.handle_pre_vote_resp(3, granted);
+
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(Vote::new(2, 1), eng.state.vote);
+        assert_eq!(ServerState::Candidate, eng.state.server_state);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 1;
+    eng.state.vote = Vote::new_committed(3, 1);
+    eng
+}
+
+#[test]
+fn test_read_log_id() -> anyhow::Result<()> {
+    tracing::info!("--- no log at all");
+    {
+        let eng = eng();
+        assert_eq!(None, eng.read_log_id());
+    }
+
+    tracing::info!("--- leader's first log is not committed: wait for it");
+    {
+        let mut eng = eng();
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1), log_id(2, 3), log_id(3, 5), log_id(3, 6)]);
+        eng.state.committed = Some(log_id(2, 4));
+
+        assert_eq!(Some(log_id(3, 5)), eng.read_log_id());
+    }
+
+    tracing::info!("--- leader's first log is committed: use committed");
+    {
+        let mut eng = eng();
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1), log_id(2, 3), log_id(3, 5), log_id(3, 6)]);
+        eng.state.committed = Some(log_id(3, 6));
+
+        assert_eq!(Some(log_id(3, 6)), eng.read_log_id());
+    }
+
+    tracing::info!("--- no log by this leader: use committed");
+    {
+        let mut eng = eng();
+        eng.state.log_ids = LogIdList::new(vec![log_id(1, 1), log_id(2, 3), log_id(2, 4)]);
+        eng.state.committed = Some(log_id(2, 4));
+
+        assert_eq!(Some(log_id(2, 4)), eng.read_log_id());
+    }
+
+    Ok(())
+}
@@ -64,7 +64,8 @@ fn test_startup_as_leader() -> anyhow::Result<()> {
             Command::UpdateReplicationStreams {
                 targets: vec![(3, ProgressEntry {
                     matching: None,
-                    searching: None
+                    searching: None,
+                    last_ack: None,
                 })]
             }
         ],
@@ -10,5 +10,6 @@ pub(crate) struct Resp {}
 
 // Config for test
 crate::declare_raft_types!(
-   pub(crate) Config: D = Req, R = Resp, NodeId = u64, Node=()
+   pub(crate) Config: D = Req, R = Resp, NodeId = u64, Node=(),
+       QuorumSet = crate::quorum::Majority<u64>, SnapshotMetadata = ()
 );
@@ -121,3 +121,57 @@ fn test_update_progress_update_leader_progress() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_update_local_progress() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    tracing::info!("--- not a leader, nothing changed");
+    {
+        let eng0 = eng.clone();
+        eng.update_local_progress(Some(log_id(2, 3)));
+        assert_eq!(eng0, eng, "nothing changed");
+    }
+
+    eng.new_leader();
+
+    // progress: None, None, (2,3)
+    eng.update_progress(3, Some(log_id(2, 3)));
+    eng.output.commands = vec![];
+
+    tracing::info!("--- None is ignored");
+    {
+        eng.update_local_progress(None);
+        assert_eq!(None, eng.state.committed);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- local logs flushed, no replication metrics for the leader itself");
+    {
+        // progress: None, (2,3), (2,3); committed: (2,3)
+        eng.update_local_progress(Some(log_id(2, 3)));
+        assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+        assert_eq!(
+            vec![
+                Command::ReplicateCommitted {
+                    committed: Some(log_id(2, 3))
+                },
+                Command::LeaderCommit {
+                    already_committed: None,
+                    upto: log_id(2, 3)
+                }
+            ],
+            eng.output.commands
+        );
+    }
+
+    tracing::info!("--- a stale flush notification is ignored");
+    {
+        eng.output.commands = vec![];
+        eng.update_local_progress(Some(log_id(2, 1)));
+        assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::raft::VoteRequest;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+/// Voters {1,2,3}, in which 3 is a witness.
+fn m123_w3() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None).with_witnesses(btreeset! {3})
+}
+
+/// Node 3 is a witness following leader 1.
+fn witness() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.enable_validate = false; // Disable validation for incomplete state
+
+    eng.config.id = 3;
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.server_state = ServerState::Follower;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123_w3()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng
+}
+
+#[test]
+fn test_witness_membership() -> anyhow::Result<()> {
+    let m = m123_w3();
+
+    assert_eq!(&btreeset! {3}, m.witnesses());
+    assert!(m.is_voter(&3), "a witness is a voter");
+
+    let m = Membership::<u64, ()>::new(vec![btreeset! {1,2}], btreeset! {3}).with_witnesses(btreeset! {2,3});
+    assert_eq!(&btreeset! {2}, m.witnesses(), "a learner can not be a witness");
+
+    tracing::info!("--- a witness that is still a voter stays a witness");
+    {
+        let next = m123_w3().next_safe(btreeset! {2,3,4}, false);
+        assert_eq!(&btreeset! {3}, next.witnesses());
+
+        let next = m123_w3().next_safe(btreeset! {1,2}, true);
+        let next = next.next_safe(btreeset! {1,2}, true);
+        assert!(next.witnesses().is_empty(), "removed witness is no longer a witness");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_witness_does_not_elect() -> anyhow::Result<()> {
+    for enable_pre_vote in [false, true] {
+        tracing::info!("--- enable_pre_vote: {}", enable_pre_vote);
+
+        let mut eng = witness();
+        eng.config.enable_pre_vote = enable_pre_vote;
+
+        eng.elect();
+
+        assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+        assert_eq!(None, eng.pre_vote);
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(
+            vec![
+                //
+                Command::InstallElectionTimer { can_be_leader: false },
+            ],
+            eng.output.commands
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_witness_ignores_timeout_now() -> anyhow::Result<()> {
+    let mut eng = witness();
+
+    let resp = eng.handle_timeout_now_req(TimeoutNowRequest {
+        vote: Vote::new_committed(2, 1),
+        last_log_id: Some(log_id(2, 3)),
+    });
+
+    assert_eq!(
+        TimeoutNowResponse {
+            vote: Vote::new_committed(2, 1),
+            elected: false
+        },
+        resp
+    );
+    assert_eq!(0, eng.output.commands.len());
+
+    Ok(())
+}
+
+/// A witness stores the log ids. Thus a candidate granted by a quorum that includes a witness has all the logs the
+/// witness has acknowledged.
+#[test]
+fn test_witness_grants_vote_only_to_up_to_date_candidate() -> anyhow::Result<()> {
+    tracing::info!("--- candidate with a smaller last log id is rejected");
+    {
+        let mut eng = witness();
+
+        let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 2))));
+
+        assert!(!resp.vote_granted);
+        assert_eq!(Some(log_id(2, 3)), resp.last_log_id);
+        assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    }
+
+    tracing::info!("--- candidate with an up to date log is granted");
+    {
+        let mut eng = witness();
+
+        let resp = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 3))));
+
+        assert!(resp.vote_granted);
+        assert_eq!(Vote::new(3, 2), eng.state.vote);
+    }
+
+    Ok(())
+}
+
+/// A witness is counted in a quorum when committing logs.
+#[test]
+fn test_leader_commits_with_witness_ack() -> anyhow::Result<()> {
+    let mut eng = witness();
+    eng.config.id = 1;
+    eng.new_leader();
+
+    // progress: None, None, (2,3)
+    eng.update_progress(3, Some(log_id(2, 3)));
+    assert_eq!(
+        None, eng.state.committed,
+        "the leader itself has not yet appended the logs"
+    );
+
+    // progress: (2,3), None, (2,3); committed: (2,3)
+    eng.output.commands = vec![];
+    eng.update_progress(1, Some(log_id(2, 3)));
+    assert_eq!(Some(log_id(2, 3)), eng.state.committed);
+    assert_eq!(
+        vec![
+            Command::ReplicateCommitted {
+                committed: Some(log_id(2, 3))
+            },
+            Command::LeaderCommit {
+                already_committed: None,
+                upto: log_id(2, 3)
+            }
+        ],
+        eng.output.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_turned_into_witness_steps_down() -> anyhow::Result<()> {
+    let mut eng = witness();
+    eng.state.vote = Vote::new_committed(2, 3);
+    eng.state.server_state = ServerState::Leader;
+    eng.new_leader();
+
+    tracing::info!("--- membership is not committed: keep leading");
+    {
+        eng.leader_step_down();
+        assert_eq!(ServerState::Leader, eng.state.server_state);
+        assert_eq!(0, eng.output.commands.len());
+    }
+
+    tracing::info!("--- membership is committed: step down");
+    {
+        eng.state.committed = Some(log_id(1, 1));
+        eng.leader_step_down();
+
+        assert_eq!(ServerState::Follower, eng.state.server_state);
+        assert_eq!(
+            vec![
+                //
+                Command::InstallElectionTimer { can_be_leader: false },
+                Command::QuitLeader,
+            ],
+            eng.output.commands
+        );
+    }
+
+    Ok(())
+}
@@ -21,6 +21,43 @@ where
     fn get_membership(&self) -> Option<&Membership<NID, N>>;
 }
 
+/// Estimates the size in bytes of an application data, see [`RaftTypeConfig::DataSize`].
+///
+/// The size is used to bound a batch of client write requests by [`Config::max_write_batch_bytes`] and by
+/// [`SnapshotPolicy::LogBytesSinceLast`]. It does not have to be exact, but should be in proportion to the size of the
+/// data when it is stored or sent, e.g., including the data on the heap.
+///
+/// For example, if the data has a `Vec<u8>` value:
+/// ```ignore
+/// pub struct ValueSize;
+///
+/// impl DataSize<Request> for ValueSize {
+///     fn data_size(data: &Request) -> u64 {
+///         (std::mem::size_of::<Request>() + data.value.len()) as u64
+///     }
+/// }
+/// ```
+///
+/// [`Config::max_write_batch_bytes`]: crate::Config::max_write_batch_bytes
+/// [`SnapshotPolicy::LogBytesSinceLast`]: crate::SnapshotPolicy::LogBytesSinceLast
+pub trait DataSize<D>: Send + Sync + 'static {
+    /// Returns the estimated size of `data` in bytes.
+    fn data_size(data: &D) -> u64;
+}
+
+/// A [`DataSize`] that estimates an application data by its in-memory size, without the data on the heap owned by it.
+///
+/// It is used if [`declare_raft_types!`](crate::declare_raft_types) does not declare `DataSize`.
+/// Every data of the same type has the same size, thus declare a [`DataSize`] that counts the data on the heap if the
+/// size of the data varies.
+pub struct StackSize;
+
+impl<D> DataSize<D> for StackSize {
+    fn data_size(_data: &D) -> u64 {
+        std::mem::size_of::<D>() as u64
+    }
+}
+
 /// Defines operations on an entry.
 pub trait RaftEntry<NID, N>: RaftPayload<NID, N> + RaftLogId<NID>
 where
@@ -42,6 +79,29 @@ pub enum EntryPayload<C: RaftTypeConfig> {
     Membership(Membership<C::NodeId, C::Node>),
 }
 
+impl<C: RaftTypeConfig> EntryPayload<C> {
+    /// Returns the estimated size of this payload in bytes.
+    ///
+    /// The size of an application data is estimated by [`RaftTypeConfig::DataSize`]. A membership config is counted
+    /// by the number of nodes in it.
+    ///
+    /// A batch of client write requests is bounded by the sum of it, see [`Config::max_write_batch_bytes`].
+    ///
+    /// [`Config::max_write_batch_bytes`]: crate::Config::max_write_batch_bytes
+    pub fn estimated_size(&self) -> u64 {
+        let size = std::mem::size_of::<Self>() as u64;
+
+        match self {
+            EntryPayload::Blank => size,
+            EntryPayload::Normal(data) => size + C::DataSize::data_size(data),
+            EntryPayload::Membership(m) => {
+                let node_size = std::mem::size_of::<(C::NodeId, C::Node)>() as u64;
+                size + m.nodes().count() as u64 * node_size
+            }
+        }
+    }
+}
+
 impl<C: RaftTypeConfig> MessageSummary<EntryPayload<C>> for EntryPayload<C> {
     fn summary(&self) -> String {
         match self {
@@ -81,6 +141,14 @@ impl<C: RaftTypeConfig> Default for Entry<C> {
     }
 }
 
+impl<C: RaftTypeConfig> Entry<C> {
+    /// Returns the estimated size of this entry in bytes: the size of the log id and the estimated size of the
+    /// payload.
+    pub(crate) fn estimated_size(&self) -> u64 {
+        std::mem::size_of::<LogId<C::NodeId>>() as u64 + self.payload.estimated_size()
+    }
+}
+
 impl<C: RaftTypeConfig> AsRef<Entry<C>> for Entry<C> {
     fn as_ref(&self) -> &Entry<C> {
         self
@@ -9,12 +9,16 @@ use anyerror::AnyError;
 
 use crate::node::Node;
 use crate::raft::AppendEntriesResponse;
+use crate::raft_types::SnapshotId;
 use crate::raft_types::SnapshotSegmentId;
+use crate::ConfigError;
 use crate::LogId;
 use crate::Membership;
 use crate::NodeId;
 use crate::RPCTypes;
+use crate::SnapshotCompression;
 use crate::StorageError;
+use crate::StorageIOError;
 use crate::Vote;
 
 /// Fatal is unrecoverable and shuts down raft at once.
@@ -81,6 +85,16 @@ where NID: NodeId
     Fatal(#[from] Fatal<NID>),
 }
 
+/// An error related to a TimeoutNow request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TimeoutNowError<NID>
+where NID: NodeId
+{
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
 // TODO: remove
 #[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
@@ -90,6 +104,19 @@ where NID: NodeId
     #[error(transparent)]
     SnapshotMismatch(#[from] SnapshotMismatch),
 
+    /// The received snapshot data is corrupted. The leader has to restart the transfer.
+    #[error(transparent)]
+    SnapshotChecksumMismatch(#[from] SnapshotChecksumMismatch),
+
+    /// The target can not decode the compressed chunk, e.g., it is corrupted or is larger than the max decoded chunk
+    /// size.
+    #[error(transparent)]
+    SnapshotDecodeError(#[from] SnapshotDecodeError),
+
+    /// The target rejected the snapshot because it can not install it, e.g., the format of it is not supported.
+    #[error(transparent)]
+    IncompatibleSnapshot(#[from] IncompatibleSnapshot),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
@@ -112,6 +139,31 @@ where
     Fatal(#[from] Fatal<NID>),
 }
 
+/// An error related to a transfer_leader request.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum TransferLeaderError<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    #[error(transparent)]
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
+
+    #[error(transparent)]
+    NotAVoter(#[from] NotAVoter<NID>),
+
+    #[error(transparent)]
+    IsWitness(#[from] IsWitness<NID>),
+
+    #[error(transparent)]
+    Timeout(#[from] TransferLeaderTimeout<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
 /// An error related to a client write request.
 #[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
 #[derive(PartialEq, Eq)]
@@ -147,6 +199,9 @@ pub enum ChangeMembershipError<NID: NodeId> {
 
     #[error(transparent)]
     LearnerIsLagging(#[from] LearnerIsLagging<NID>),
+
+    #[error(transparent)]
+    AlreadyVoter(#[from] AlreadyVoter<NID>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -203,6 +258,129 @@ where
     Fatal(#[from] Fatal<NID>),
 }
 
+/// The set of errors which may take place when exporting the current snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum ExportSnapshotError<NID>
+where NID: NodeId
+{
+    /// Failed to read the snapshot data or to write it to the destination.
+    #[error(transparent)]
+    IO(#[from] StorageIOError<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// The set of errors which may take place when importing a snapshot to a pristine Raft node.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum ImportSnapshotError<NID>
+where NID: NodeId
+{
+    /// The node is not pristine: it already has logs or a vote.
+    #[error(transparent)]
+    NotAllowed(#[from] NotAllowed<NID>),
+
+    /// The state machine rejected the snapshot.
+    #[error(transparent)]
+    IncompatibleSnapshot(#[from] IncompatibleSnapshot),
+
+    /// Failed to read the snapshot data or to write it into the state machine.
+    #[error(transparent)]
+    IO(#[from] StorageIOError<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// The set of errors which may take place when creating a Raft node.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum NewRaftError<NID>
+where NID: NodeId
+{
+    /// The config can not be used by this Raft node, e.g., a custom snapshot policy is built for other types.
+    #[error(transparent)]
+    InvalidConfig(#[from] ConfigError),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// The set of errors which may take place when updating the config of a running Raft node.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum UpdateConfigError<NID>
+where NID: NodeId
+{
+    /// The new config does not pass [`Config::validate`](`crate::Config::validate`).
+    #[error(transparent)]
+    InvalidConfig(#[from] ConfigError),
+
+    /// The new config changes a field that can not be changed at runtime.
+    #[error(transparent)]
+    UnchangeableConfig(#[from] UnchangeableConfig),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// The errors of receiving an event from [`EventReceiver`](`crate::event::EventReceiver`).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum RecvEventError {
+    /// The receiver fell behind, and the oldest `n` events it has not yet received are dropped.
+    #[error("event receiver lagged behind, {0} events are dropped")]
+    Lagged(u64),
+
+    /// RaftCore has quit and all events are received.
+    #[error("RaftCore has quit, no more events")]
+    Closed,
+}
+
+/// The set of errors which may take place when forcing a new membership after a quorum of voters is lost.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum ForceResetMembershipError<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    #[error(transparent)]
+    EmptyMembership(#[from] EmptyMembership),
+
+    /// This node is not a voter of the current membership or of the new membership.
+    #[error(transparent)]
+    NotInMembers(#[from] NotInMembers<NID, N>),
+
+    /// A voter of the new membership is not a node in the current membership.
+    #[error(transparent)]
+    LearnerNotFound(#[from] LearnerNotFound<NID>),
+
+    #[error(transparent)]
+    IsWitness(#[from] IsWitness<NID>),
+
+    /// There is still a leader acknowledged by a quorum, the membership can be changed safely.
+    #[error(transparent)]
+    LeaderAlive(#[from] LeaderAlive<NID>),
+
+    /// A voter of the new membership did not respond.
+    #[error(transparent)]
+    VoterUnreachable(#[from] VoterUnreachable<NID>),
+
+    /// A voter of the new membership has more logs than this node.
+    #[error(transparent)]
+    NotMostUpToDate(#[from] NotMostUpToDate<NID>),
+
+    /// A voter of the new membership rejected the PreVote request.
+    #[error(transparent)]
+    VoterRejected(#[from] VoterRejected<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
 impl<NID> From<StorageError<NID>> for AppendEntriesError<NID>
 where NID: NodeId
 {
@@ -219,6 +397,22 @@ where NID: NodeId
         f.into()
     }
 }
+impl<NID> From<StorageError<NID>> for TimeoutNowError<NID>
+where NID: NodeId
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
+impl<NID> From<StorageError<NID>> for NewRaftError<NID>
+where NID: NodeId
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
 impl<NID> From<StorageError<NID>> for InstallSnapshotError<NID>
 where NID: NodeId
 {
@@ -227,6 +421,24 @@ where NID: NodeId
         f.into()
     }
 }
+impl<NID> From<StorageError<NID>> for ImportSnapshotError<NID>
+where NID: NodeId
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
+impl<NID, N> From<StorageError<NID>> for ForceResetMembershipError<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
 impl<NID, N> From<StorageError<NID>> for CheckIsLeaderError<NID, N>
 where
     NID: NodeId,
@@ -403,6 +615,49 @@ pub struct SnapshotMismatch {
     pub got: SnapshotSegmentId,
 }
 
+/// The checksum of the snapshot data in range `[segment.offset, segment.offset + len)` does not match.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("snapshot checksum mismatch, segment: {segment}, len: {len}, expect: {expect:08x}, got: {got:08x}")]
+pub struct SnapshotChecksumMismatch {
+    pub segment: SnapshotSegmentId,
+    pub len: u64,
+    pub expect: u32,
+    pub got: u32,
+}
+
+/// The snapshot chunk at `segment` can not be decoded with the compression specified in the request.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("failed to decode snapshot chunk, segment: {segment}, compression: {compression:?}: {source}")]
+pub struct SnapshotDecodeError {
+    pub segment: SnapshotSegmentId,
+    pub compression: SnapshotCompression,
+    pub source: AnyError,
+}
+
+/// The snapshot can not be installed by this node, returned by
+/// [`RaftStateMachine::check_snapshot_meta`](`crate::storage::RaftStateMachine::check_snapshot_meta`).
+///
+/// `source` is the application defined error that tells why, e.g., the format of the snapshot is not supported.
+/// It keeps the type name and the message of the error, and is sent to the leader.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("incompatible snapshot {snapshot_id}: {source}")]
+pub struct IncompatibleSnapshot {
+    pub snapshot_id: SnapshotId,
+    pub source: AnyError,
+}
+
+impl IncompatibleSnapshot {
+    pub fn new<E: Error + 'static>(snapshot_id: impl ToString, e: &E) -> Self {
+        Self {
+            snapshot_id: snapshot_id.to_string(),
+            source: AnyError::new(e),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("not enough for a quorum, cluster: {cluster}, got: {got:?}")]
@@ -426,6 +681,13 @@ pub struct LearnerNotFound<NID: NodeId> {
     pub node_id: NID,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is already a voter: remove it before adding it as a witness")]
+pub struct AlreadyVoter<NID: NodeId> {
+    pub node_id: NID,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("replication to learner {node_id} is lagging {distance}, matched: {matched:?}, can not add as member")]
@@ -435,6 +697,69 @@ pub struct LearnerIsLagging<NID: NodeId> {
     pub distance: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is not a voter, can not transfer leadership to it")]
+pub struct NotAVoter<NID: NodeId> {
+    pub node_id: NID,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is a witness, it can not become a leader")]
+pub struct IsWitness<NID: NodeId> {
+    pub node_id: NID,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("timeout after {timeout:?} when transferring leadership to {target}")]
+pub struct TransferLeaderTimeout<NID: NodeId> {
+    pub target: NID,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[error("{name} can not be changed at runtime: current: {current}, new: {new}")]
+pub struct UnchangeableConfig {
+    pub name: String,
+    pub current: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("leader {leader_id} is still acknowledged by a quorum, change membership with it instead")]
+pub struct LeaderAlive<NID: NodeId> {
+    pub leader_id: NID,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("voter {target} is unreachable: {source}")]
+pub struct VoterUnreachable<NID: NodeId> {
+    pub target: NID,
+    pub source: AnyError,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("voter {target} rejected the PreVote request, its vote: {vote}")]
+pub struct VoterRejected<NID: NodeId> {
+    pub target: NID,
+    pub vote: Vote<NID>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("voter {target} has more logs: {target_last_log_id:?} > {last_log_id:?}, reset membership on it instead")]
+pub struct NotMostUpToDate<NID: NodeId> {
+    pub target: NID,
+    pub target_last_log_id: Option<LogId<NID>>,
+    pub last_log_id: Option<LogId<NID>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("not allowed to initialize due to current raft state: last_log_id: {last_log_id:?} vote: {vote}")]
@@ -0,0 +1,181 @@
+//! Events about state transitions of a Raft node, delivered in order by
+//! [`Raft::subscribe()`](`crate::Raft::subscribe`).
+//!
+//! Unlike [`RaftMetrics`](`crate::RaftMetrics`), which only keeps the latest state, every transition is delivered,
+//! including a transient one, e.g., a joint membership that is quickly replaced by a uniform membership.
+
+use std::sync::Arc;
+use std::sync::Weak;
+
+use futures::Stream;
+use tokio::sync::broadcast;
+
+use crate::error::Fatal;
+use crate::error::RecvEventError;
+use crate::membership::EffectiveMembership;
+use crate::LogId;
+use crate::Node;
+use crate::NodeId;
+use crate::SnapshotMeta;
+use crate::Vote;
+
+/// A state transition of a Raft node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum RaftEvent<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// This node became the leader, with the committed `vote` of it.
+    BecameLeader { vote: Vote<NID> },
+
+    /// This node is no longer the leader, `vote` is the vote it has seen when it quit.
+    LostLeadership { vote: Vote<NID> },
+
+    /// This node has seen a greater term.
+    TermChanged { term: u64 },
+
+    /// The effective membership of this node changed, e.g., a membership log is appended, or is truncated.
+    MembershipChanged {
+        membership: Arc<EffectiveMembership<NID, N>>,
+    },
+
+    /// A snapshot is built by this node.
+    SnapshotBuilt { meta: SnapshotMeta<NID, N> },
+
+    /// A snapshot received from the leader, or imported, is installed.
+    SnapshotInstalled { meta: SnapshotMeta<NID, N> },
+
+    /// Logs up to `upto`, inclusive, are purged.
+    LogsPurged { upto: LogId<NID> },
+
+    /// RaftCore quit on a fatal error. No more event is delivered after it.
+    FatalError { error: Fatal<NID> },
+}
+
+/// Receives [`RaftEvent`]s in the order they happen.
+///
+/// Events are buffered in a bounded buffer of [`Config::event_buffer_size`](`crate::Config::event_buffer_size`)
+/// events shared by all receivers. If a receiver falls behind so that the oldest events it has not yet received are
+/// overwritten, the next call to [`EventReceiver::recv()`] returns [`RecvEventError::Lagged`] with the number of
+/// events it missed, and then continues with the oldest buffered event.
+pub struct EventReceiver<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    rx: broadcast::Receiver<RaftEvent<NID, N>>,
+}
+
+impl<NID, N> EventReceiver<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// Wait for the next event.
+    ///
+    /// It returns [`RecvEventError::Closed`] once RaftCore quits and all buffered events are received.
+    pub async fn recv(&mut self) -> Result<RaftEvent<NID, N>, RecvEventError> {
+        self.rx.recv().await.map_err(|e| match e {
+            broadcast::error::RecvError::Lagged(n) => RecvEventError::Lagged(n),
+            broadcast::error::RecvError::Closed => RecvEventError::Closed,
+        })
+    }
+
+    /// Return the next event if there is one, without waiting.
+    pub fn try_recv(&mut self) -> Result<Option<RaftEvent<NID, N>>, RecvEventError> {
+        match self.rx.try_recv() {
+            Ok(ev) => Ok(Some(ev)),
+            Err(broadcast::error::TryRecvError::Empty) => Ok(None),
+            Err(broadcast::error::TryRecvError::Lagged(n)) => Err(RecvEventError::Lagged(n)),
+            Err(broadcast::error::TryRecvError::Closed) => Err(RecvEventError::Closed),
+        }
+    }
+
+    /// Convert it into a [`Stream`] of events, which ends when RaftCore quits.
+    ///
+    /// A lag is delivered as an `Err(RecvEventError::Lagged)` item.
+    pub fn into_stream(self) -> impl Stream<Item = Result<RaftEvent<NID, N>, RecvEventError>> {
+        futures::stream::unfold(self, |mut rx| async move {
+            match rx.recv().await {
+                Err(RecvEventError::Closed) => None,
+                res => Some((res, rx)),
+            }
+        })
+    }
+}
+
+/// Publishes [`RaftEvent`]s to all of the subscribers. It is owned by RaftCore.
+pub(crate) struct EventSender<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// The only strong reference, the channel is closed when RaftCore drops it.
+    tx: Arc<broadcast::Sender<RaftEvent<NID, N>>>,
+
+    /// The greatest term that is seen, to tell if a saved vote changes the term.
+    term: u64,
+}
+
+impl<NID, N> EventSender<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// Create a sender with a buffer of `capacity` events, and a handle to subscribe to it.
+    ///
+    /// `term` is the term this node starts with.
+    pub(crate) fn new(capacity: usize, term: u64) -> (Self, EventSubscriber<NID, N>) {
+        let (tx, _rx) = broadcast::channel(capacity);
+        let tx = Arc::new(tx);
+        let subscriber = EventSubscriber {
+            tx: Arc::downgrade(&tx),
+        };
+        (Self { tx, term }, subscriber)
+    }
+
+    /// Send a [`RaftEvent::TermChanged`] if the term of a saved vote is greater than the last seen one.
+    pub(crate) fn update_term(&mut self, term: u64) {
+        if term > self.term {
+            self.term = term;
+            self.send(RaftEvent::TermChanged { term });
+        }
+    }
+
+    pub(crate) fn send(&self, event: RaftEvent<NID, N>) {
+        tracing::debug!(event = debug(&event), "send event");
+
+        // An error means there is no subscriber.
+        let _ = self.tx.send(event);
+    }
+}
+
+/// Creates [`EventReceiver`]s, without keeping the channel open after RaftCore quits.
+pub(crate) struct EventSubscriber<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    tx: Weak<broadcast::Sender<RaftEvent<NID, N>>>,
+}
+
+impl<NID, N> EventSubscriber<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    pub(crate) fn subscribe(&self) -> EventReceiver<NID, N> {
+        let rx = match self.tx.upgrade() {
+            Some(tx) => tx.subscribe(),
+            None => {
+                // RaftCore has quit, return a closed receiver.
+                let (_tx, rx) = broadcast::channel(1);
+                rx
+            }
+        };
+
+        EventReceiver { rx }
+    }
+}
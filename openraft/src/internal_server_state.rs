@@ -1,9 +1,11 @@
 use crate::leader::Leader;
 use crate::quorum::Joint;
+use crate::quorum::Majority;
+use crate::quorum::QuorumSet;
 use crate::NodeId;
 
-/// The quorum set type used by `Leader`.
-pub(crate) type LeaderQuorumSet<NID> = Joint<NID, Vec<NID>, Vec<Vec<NID>>>;
+/// The quorum set type used by `Leader`: a joint of the quorum set `QS` of every config.
+pub(crate) type LeaderQuorumSet<NID, QS> = Joint<NID, QS, Vec<QS>>;
 
 /// In openraft there are only two state for a server:
 /// Leading(raft leader or raft candidate) and following(raft follower or raft learner):
@@ -17,13 +19,15 @@ pub(crate) type LeaderQuorumSet<NID> = Joint<NID, Vec<NID>, Vec<Vec<NID>>>;
 ///   become leader. A following state that is not a member is just a learner.
 #[derive(Clone, Debug)]
 #[derive(PartialEq, Eq)]
-pub(crate) enum InternalServerState<NID>
-where NID: NodeId
+pub(crate) enum InternalServerState<NID, QS = Majority<NID>>
+where
+    NID: NodeId,
+    QS: QuorumSet<NID>,
 {
     /// Leader or candidate.
     ///
     /// `vote.committed==true` means it is a leader.
-    Leading(Leader<NID, LeaderQuorumSet<NID>>),
+    Leading(Leader<NID, LeaderQuorumSet<NID, QS>>),
 
     /// Follower or learner.
     ///
@@ -31,25 +35,29 @@ where NID: NodeId
     Following,
 }
 
-impl<NID> Default for InternalServerState<NID>
-where NID: NodeId
+impl<NID, QS> Default for InternalServerState<NID, QS>
+where
+    NID: NodeId,
+    QS: QuorumSet<NID>,
 {
     fn default() -> Self {
         Self::Following
     }
 }
 
-impl<NID> InternalServerState<NID>
-where NID: NodeId
+impl<NID, QS> InternalServerState<NID, QS>
+where
+    NID: NodeId,
+    QS: QuorumSet<NID>,
 {
-    pub(crate) fn leading(&self) -> Option<&Leader<NID, LeaderQuorumSet<NID>>> {
+    pub(crate) fn leading(&self) -> Option<&Leader<NID, LeaderQuorumSet<NID, QS>>> {
         match self {
             InternalServerState::Leading(l) => Some(l),
             InternalServerState::Following => None,
         }
     }
 
-    pub(crate) fn leading_mut(&mut self) -> Option<&mut Leader<NID, LeaderQuorumSet<NID>>> {
+    pub(crate) fn leading_mut(&mut self) -> Option<&mut Leader<NID, LeaderQuorumSet<NID, QS>>> {
         match self {
             InternalServerState::Leading(l) => Some(l),
             InternalServerState::Following => None,
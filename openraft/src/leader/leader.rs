@@ -1,5 +1,8 @@
+use std::cmp::Reverse;
 use std::collections::BTreeSet;
 
+use tokio::time::Instant;
+
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Progress;
 use crate::progress::VecProgress;
@@ -52,9 +55,41 @@ where
         self.vote_granted_by.insert(target);
     }
 
-    /// Return if a quorum of `membership` has granted it.
+    /// Return if an election quorum of `membership` has granted it.
     pub(crate) fn is_vote_granted(&self) -> bool {
         let qs = self.progress.quorum_set();
-        qs.is_quorum(self.vote_granted_by.iter())
+        qs.is_election_quorum(self.vote_granted_by.iter())
+    }
+
+    /// Return the latest time at which a quorum had acknowledged this leader.
+    ///
+    /// The leader itself, `leader_id`, is regarded as acknowledged at `now`.
+    /// Returns `None` if no quorum has acknowledged this leader yet.
+    pub(crate) fn last_quorum_acked_time(&self, leader_id: &NID, now: Instant) -> Option<Instant> {
+        let mut acked = self
+            .progress
+            .iter()
+            .filter(|(id, _)| self.progress.is_voter(id) == Some(true))
+            .filter_map(|(id, p)| {
+                if id == leader_id {
+                    Some((*id, now))
+                } else {
+                    p.last_ack.map(|t| (*id, t))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Latest first
+        acked.sort_by_key(|x| Reverse(x.1));
+
+        let qs = self.progress.quorum_set();
+
+        for i in 0..acked.len() {
+            if qs.is_quorum(acked[..=i].iter().map(|(id, _)| id)) {
+                return Some(acked[i].1);
+            }
+        }
+
+        None
     }
 }
@@ -15,6 +15,9 @@
 //!
 //! - `serde`: Add serde::Serialize and serde:Deserialize bound to data types. If you'd like to use `serde` to serialize
 //!   messages.
+//!
+//! - `prometheus`: Render `RaftMetrics` in the Prometheus text exposition format with
+//!   `RaftMetrics::render_prometheus()`.
 
 mod change_members;
 mod config;
@@ -24,7 +27,6 @@ mod entry;
 mod membership;
 mod node;
 mod progress;
-mod quorum;
 mod raft_types;
 mod replication;
 mod storage_error;
@@ -37,10 +39,12 @@ pub(crate) mod log_id_range;
 
 mod engine;
 pub mod error;
+pub mod event;
 mod internal_server_state;
 mod leader;
 pub mod metrics;
 pub mod network;
+pub mod quorum;
 pub mod raft;
 mod raft_state;
 mod runtime;
@@ -60,13 +64,19 @@ pub use metrics::ReplicationTargetMetrics;
 pub use crate::change_members::ChangeMembers;
 pub use crate::config::Config;
 pub use crate::config::ConfigError;
+pub use crate::config::LogStats;
+pub use crate::config::SnapshotCompression;
 pub use crate::config::SnapshotPolicy;
+pub use crate::config::SnapshotTrigger;
 pub use crate::core::ServerState;
 pub use crate::defensive::DefensiveCheck;
 pub use crate::defensive::DefensiveCheckBase;
+pub use crate::entry::DataSize;
 pub use crate::entry::Entry;
 pub use crate::entry::EntryPayload;
 pub use crate::entry::RaftPayload;
+pub use crate::entry::StackSize;
+pub use crate::event::RaftEvent;
 pub use crate::membership::EffectiveMembership;
 pub use crate::membership::Membership;
 pub use crate::membership::MembershipState;
@@ -87,7 +97,9 @@ pub use crate::raft_types::SnapshotId;
 pub use crate::raft_types::SnapshotSegmentId;
 pub use crate::raft_types::Update;
 pub use crate::storage::RaftLogReader;
+pub use crate::storage::RaftLogStorage;
 pub use crate::storage::RaftSnapshotBuilder;
+pub use crate::storage::RaftStateMachine;
 pub use crate::storage::RaftStorage;
 pub use crate::storage::RaftStorageDebug;
 pub use crate::storage::SnapshotMeta;
@@ -156,3 +168,17 @@ pub trait AppDataResponse: Clone + Send + Sync + 'static {}
 
 #[cfg(not(feature = "serde"))]
 impl<T> AppDataResponse for T where T: Clone + Send + Sync + 'static {}
+
+/// A trait defining application specific metadata of a snapshot.
+///
+/// It is carried by [`SnapshotMeta::metadata`], e.g., the format version or the schema of the snapshot data, so that
+/// a follower is able to check whether it can install a snapshot before receiving the data, with
+/// [`RaftStateMachine::check_snapshot_meta`].
+///
+/// ## Note
+///
+/// The trait is automatically implemented for all types which satisfy its supertraits.
+/// Use `()` if there is no such metadata.
+pub trait AppSnapshotMetadata: AppData + std::fmt::Debug + Default + PartialEq + Eq {}
+
+impl<T> AppSnapshotMetadata for T where T: AppData + std::fmt::Debug + Default + PartialEq + Eq {}
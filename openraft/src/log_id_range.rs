@@ -15,6 +15,7 @@ use crate::NodeId;
 /// The range of log to send is left open right close: `(prev_log_id, last_log_id]`.
 #[derive(Clone, Copy, Debug)]
 #[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub(crate) struct LogIdRange<NID: NodeId> {
     /// The prev log id before the first to send, exclusive.
     pub(crate) prev_log_id: Option<LogId<NID>>,
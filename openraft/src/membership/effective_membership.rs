@@ -118,9 +118,11 @@ where
     N: Node,
     NID: NodeId,
 {
-    /// Return if a node is a voter or learner, or not in this membership config at all.
+    /// Return if a node is a voter, witness or learner, or not in this membership config at all.
     pub(crate) fn get_node_role(&self, nid: &NID) -> Option<NodeRole> {
-        if self.voter_ids.contains(nid) {
+        if self.membership.is_witness(nid) {
+            Some(NodeRole::Witness)
+        } else if self.voter_ids.contains(nid) {
             Some(NodeRole::Voter)
         } else if self.contains(nid) {
             Some(NodeRole::Learner)
@@ -134,6 +136,11 @@ where
         self.membership.is_voter(nid)
     }
 
+    /// Check if the given `NodeId` is a witness, a voter that never becomes a leader.
+    pub(crate) fn is_witness(&self, nid: &NID) -> bool {
+        self.membership.is_witness(nid)
+    }
+
     /// Returns an Iterator of all voter node ids. Learners are not included.
     pub fn voter_ids(&self) -> impl Iterator<Item = NID> + '_ {
         self.voter_ids.iter().copied()
@@ -191,6 +198,10 @@ where
         self.quorum_set.is_quorum(ids)
     }
 
+    fn is_election_quorum<'a, I: Iterator<Item = &'a NID> + Clone>(&self, ids: I) -> bool {
+        self.quorum_set.is_election_quorum(ids)
+    }
+
     fn ids(&self) -> Self::Iter {
         self.quorum_set.ids()
     }
@@ -84,6 +84,14 @@ where
     ///
     /// A node-id key that is in `nodes` but is not in `configs` is a **learner**.
     nodes: BTreeMap<NID, N>,
+
+    /// Voters that are **witness**es.
+    ///
+    /// A witness votes and is counted in a quorum, but it receives only log ids and metadata, i.e., the payload of a
+    /// normal log is not replicated to it. A witness never becomes a leader. It receives a snapshot only when the logs
+    /// it needs are purged on the leader.
+    #[cfg_attr(feature = "serde", serde(default))]
+    witnesses: BTreeSet<NID>,
 }
 
 impl<NID, N> From<BTreeMap<NID, N>> for Membership<NID, N>
@@ -138,6 +146,11 @@ where
             res.push(format!(":{{{:?}}}", n));
         }
         res.push("]".to_string());
+
+        if !self.witnesses.is_empty() {
+            res.push(format!(",witnesses:{:?}", self.witnesses));
+        }
+
         res.join("")
     }
 }
@@ -157,7 +170,20 @@ where
         let voter_ids = configs.as_joint().ids().collect::<BTreeSet<_>>();
         let nodes = Self::extend_nodes(nodes.into_nodes(), &voter_ids.into_nodes());
 
-        Membership { configs, nodes }
+        Membership {
+            configs,
+            nodes,
+            witnesses: BTreeSet::new(),
+        }
+    }
+
+    /// Mark the voters in `witnesses` as witness and return the updated membership.
+    ///
+    /// A node id in `witnesses` that is not a voter is ignored.
+    pub fn with_witnesses(mut self, witnesses: BTreeSet<NID>) -> Self {
+        let voter_ids = self.voter_ids().collect::<BTreeSet<_>>();
+        self.witnesses = witnesses.intersection(&voter_ids).copied().collect();
+        self
     }
 
     /// Create a new Membership of multiple configs and optional node infos.
@@ -182,7 +208,11 @@ where
             }
         }
 
-        Membership { configs, nodes }
+        Membership {
+            configs,
+            nodes,
+            witnesses: BTreeSet::new(),
+        }
     }
 
     /// Extends nodes btreemap with another.
@@ -212,7 +242,7 @@ where
 
         let nodes = Self::extend_nodes(self.nodes.clone(), &btreemap! {node_id=>node});
 
-        Self::with_nodes(configs, nodes)
+        Self::with_nodes(configs, nodes).with_witnesses(self.witnesses.clone())
     }
 }
 
@@ -222,10 +252,12 @@ where
     N: Node,
     NID: NodeId,
 {
-    /// Return if a node is a voter or learner, or not in this membership config at all.
+    /// Return if a node is a voter, witness or learner, or not in this membership config at all.
     #[allow(dead_code)]
     pub(crate) fn get_node_role(&self, nid: &NID) -> Option<NodeRole> {
-        if self.is_voter(nid) {
+        if self.is_witness(nid) {
+            Some(NodeRole::Witness)
+        } else if self.is_voter(nid) {
             Some(NodeRole::Voter)
         } else if self.contains(nid) {
             Some(NodeRole::Learner)
@@ -244,6 +276,18 @@ where
         false
     }
 
+    /// Check if the given `NodeId` is a witness.
+    ///
+    /// A witness is also a voter.
+    pub(crate) fn is_witness(&self, node_id: &NID) -> bool {
+        self.witnesses.contains(node_id)
+    }
+
+    /// Returns ids of all witnesses.
+    pub fn witnesses(&self) -> &BTreeSet<NID> {
+        &self.witnesses
+    }
+
     /// Returns an Iterator of all voter node ids. Learners are not included.
     pub(crate) fn voter_ids(&self) -> impl Iterator<Item = NID> {
         self.configs.as_joint().ids()
@@ -302,6 +346,8 @@ where
     ///     curr = next;
     /// }
     /// ```
+    ///
+    /// A witness that is still a voter in the next membership stays a witness.
     pub(crate) fn next_safe<T>(&self, goal: T, turn_to_learner: bool) -> Self
     where T: IntoNodes<NID, N> {
         let goal = goal.into_nodes();
@@ -321,14 +367,15 @@ where
             }
         };
 
-        Membership::with_nodes(config, nodes)
+        Membership::with_nodes(config, nodes).with_witnesses(self.witnesses.clone())
     }
 
-    /// Build a QuorumSet from current joint config
-    pub(crate) fn to_quorum_set(&self) -> Joint<NID, Vec<NID>, Vec<Vec<NID>>> {
+    /// Build a QuorumSet from current joint config, with `QS` as the quorum set of every config.
+    pub(crate) fn to_quorum_set<QS>(&self) -> Joint<NID, QS, Vec<QS>>
+    where QS: QuorumSet<NID> + From<Vec<NID>> {
         let mut qs = vec![];
         for c in self.get_joint_config().iter() {
-            qs.push(c.iter().copied().collect::<Vec<_>>());
+            qs.push(QS::from(c.iter().copied().collect::<Vec<_>>()));
         }
         Joint::new(qs)
     }
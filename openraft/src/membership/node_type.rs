@@ -3,4 +3,6 @@
 pub(crate) enum NodeRole {
     Voter,
     Learner,
+    /// A voter that stores only log ids and metadata, and never becomes a leader.
+    Witness,
 }
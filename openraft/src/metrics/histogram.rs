@@ -0,0 +1,190 @@
+use std::fmt;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// The number of bits of a value, after its highest bit, that select a sub-bucket.
+///
+/// Every power of 2 range is split into `2^SUB_BITS` buckets, thus a recorded value is off by at most 1/8.
+const SUB_BITS: u32 = 3;
+
+const SUB_BUCKETS: usize = 1 << SUB_BITS;
+
+/// The number of buckets to hold any `u64` value.
+const BUCKETS: usize = (64 - SUB_BITS as usize + 1) * SUB_BUCKETS;
+
+/// Return the index of the bucket a value in microseconds belongs to.
+pub(crate) fn bucket_index(v: u64) -> usize {
+    if v < SUB_BUCKETS as u64 {
+        return v as usize;
+    }
+
+    let high_bit = 63 - v.leading_zeros();
+    let shift = high_bit - SUB_BITS;
+    let sub = (v >> shift) as usize & (SUB_BUCKETS - 1);
+
+    ((shift as usize + 1) << SUB_BITS) + sub
+}
+
+/// Return the greatest value in microseconds that belongs to the bucket.
+pub(crate) fn bucket_upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+
+    let shift = (index >> SUB_BITS) as u32 - 1;
+    let sub = (index & (SUB_BUCKETS - 1)) as u64;
+    let low = (SUB_BUCKETS as u64 + sub) << shift;
+
+    low + ((1 << shift) - 1)
+}
+
+/// Records latencies into exponential buckets with atomic counters.
+///
+/// Recording is lock free and takes a few atomic additions, it is shared by `RaftCore` and the replication tasks.
+pub(crate) struct Histogram {
+    buckets: Box<[AtomicU64]>,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.snapshot(), f)
+    }
+}
+
+impl Histogram {
+    /// Record a latency.
+    pub(crate) fn record(&self, latency: Duration) {
+        self.record_n(latency, 1);
+    }
+
+    /// Record a latency that is shared by `n` samples, e.g., by every entry in a batch.
+    pub(crate) fn record_n(&self, latency: Duration, n: u64) {
+        if n == 0 {
+            return;
+        }
+
+        let us = latency.as_micros().min(u64::MAX as u128) as u64;
+
+        self.buckets[bucket_index(us)].fetch_add(n, Ordering::Relaxed);
+        self.sum_us.fetch_add(us.saturating_mul(n), Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    /// Take a copy of the recorded samples.
+    pub(crate) fn snapshot(&self) -> LatencyHistogram {
+        let buckets = self
+            .buckets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| {
+                let n = b.load(Ordering::Relaxed);
+                if n > 0 {
+                    Some((bucket_upper_bound(i), n))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        LatencyHistogram {
+            count: buckets.iter().map(|(_, n)| n).sum(),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+            max_us: self.max_us.load(Ordering::Relaxed),
+            buckets,
+        }
+    }
+}
+
+/// A copy of the latencies recorded by a histogram.
+///
+/// A latency is recorded in microseconds, into a bucket whose width is 1/8 of its value. A percentile is reported as
+/// the upper bound of the bucket it falls in, thus it is greater than the exact value by at most 12.5%.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LatencyHistogram {
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+
+    /// The upper bound in microseconds and the number of samples of every non-empty bucket, in ascending order.
+    buckets: Vec<(u64, u64)>,
+}
+
+impl LatencyHistogram {
+    /// The number of recorded samples.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The average latency, or `None` if nothing is recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Duration::from_micros(self.sum_us / self.count))
+    }
+
+    /// The greatest latency, or `None` if nothing is recorded.
+    pub fn max(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Duration::from_micros(self.max_us))
+    }
+
+    /// The latency below which `p` percent of the samples fall, e.g., `percentile(99.0)`, or `None` if nothing is
+    /// recorded.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let p = p.clamp(0.0, 100.0);
+        let rank = ((p / 100.0 * self.count as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (upper, n) in self.buckets.iter() {
+            seen += n;
+            if seen >= rank {
+                return Some(Duration::from_micros((*upper).min(self.max_us)));
+            }
+        }
+
+        Some(Duration::from_micros(self.max_us))
+    }
+}
+
+impl fmt::Display for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return write!(f, "n: 0");
+        }
+
+        let pct = |p| self.percentile(p).unwrap_or_default();
+
+        write!(
+            f,
+            "n: {}, mean: {:?}, p50: {:?}, p90: {:?}, p99: {:?}, p99.9: {:?}, max: {:?}",
+            self.count,
+            self.mean().unwrap_or_default(),
+            pct(50.0),
+            pct(90.0),
+            pct(99.0),
+            pct(99.9),
+            self.max().unwrap_or_default(),
+        )
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use crate::metrics::histogram::bucket_index;
+use crate::metrics::histogram::bucket_upper_bound;
+use crate::metrics::Histogram;
+
+#[test]
+fn test_bucket_index() -> anyhow::Result<()> {
+    // Small values have their own bucket.
+    for v in 0..8 {
+        assert_eq!(v as usize, bucket_index(v));
+        assert_eq!(v, bucket_upper_bound(v as usize));
+    }
+
+    assert_eq!(8, bucket_index(8));
+    assert_eq!(15, bucket_index(15));
+    assert_eq!(16, bucket_index(16));
+    assert_eq!(16, bucket_index(17));
+    assert_eq!(17, bucket_index(18));
+
+    // Every value is not greater than the upper bound of its bucket, and is greater than that of the previous bucket.
+    for v in [8, 9, 100, 1_000, 12_345, 1 << 40, u64::MAX] {
+        let i = bucket_index(v);
+        assert!(v <= bucket_upper_bound(i), "v: {}", v);
+        assert!(v > bucket_upper_bound(i - 1), "v: {}", v);
+        assert!(bucket_upper_bound(i) - v <= v / 8, "v: {}", v);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_histogram_percentile() -> anyhow::Result<()> {
+    let h = Histogram::default();
+
+    let empty = h.snapshot();
+    assert_eq!(0, empty.count());
+    assert_eq!(None, empty.mean());
+    assert_eq!(None, empty.percentile(50.0));
+    assert_eq!("n: 0", empty.to_string());
+
+    for ms in 1..=100 {
+        h.record(Duration::from_millis(ms));
+    }
+    // A batch of 100 logs shares one latency.
+    h.record_n(Duration::from_millis(1_000), 100);
+
+    let s = h.snapshot();
+    assert_eq!(200, s.count());
+    assert_eq!(Some(Duration::from_micros((5050 + 100_000) * 1_000 / 200)), s.mean());
+    assert_eq!(Some(Duration::from_millis(1_000)), s.max());
+
+    let p25 = s.percentile(25.0).unwrap();
+    assert!(
+        p25 >= Duration::from_millis(50) && p25 <= Duration::from_micros(50_000 * 9 / 8),
+        "{:?}",
+        p25
+    );
+
+    // The upper bound of a bucket is capped by the max.
+    assert_eq!(Some(Duration::from_millis(1_000)), s.percentile(99.0));
+    assert_eq!(Some(Duration::from_millis(1_000)), s.percentile(100.0));
+
+    let p0 = s.percentile(0.0).unwrap();
+    assert!(
+        p0 >= Duration::from_millis(1) && p0 <= Duration::from_micros(1_125),
+        "{:?}",
+        p0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::metrics::histogram::Histogram;
+use crate::metrics::LatencyHistogram;
+use crate::NodeId;
+
+/// The latencies of the stages of writing logs, returned by
+/// [`Raft::latency_metrics()`](`crate::Raft::latency_metrics`).
+///
+/// The latencies of the write stages are measured on the leader, from when a log is proposed, i.e., when RaftCore
+/// handles a write request, to when the stage is done. Logs written in one batch share the latency of the batch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LatencyMetrics<NID: NodeId> {
+    /// From a log being proposed to it being flushed to the local log store.
+    pub append: LatencyHistogram,
+
+    /// From a log being proposed to it being committed by a quorum.
+    pub commit: LatencyHistogram,
+
+    /// From a log being proposed to it being applied to the state machine.
+    pub apply: LatencyHistogram,
+
+    /// The round trip time of successful AppendEntries RPCs sent to every target, including heartbeats.
+    pub append_entries: BTreeMap<NID, LatencyHistogram>,
+}
+
+/// Records the latencies, shared by `RaftCore` and the replication tasks.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyRecorder<NID: NodeId> {
+    pub(crate) append: Histogram,
+    pub(crate) commit: Histogram,
+    pub(crate) apply: Histogram,
+
+    /// A histogram is created for a target when a replication stream to it is spawned for the first time.
+    append_entries: Mutex<BTreeMap<NID, Arc<Histogram>>>,
+}
+
+impl<NID: NodeId> LatencyRecorder<NID> {
+    /// Get the histogram of AppendEntries RPCs to `target`, create one if it does not exist.
+    pub(crate) fn append_entries(&self, target: NID) -> Arc<Histogram> {
+        let mut targets = self.append_entries.lock().unwrap();
+        targets.entry(target).or_default().clone()
+    }
+
+    pub(crate) fn metrics(&self) -> LatencyMetrics<NID> {
+        let targets = self.append_entries.lock().unwrap();
+
+        LatencyMetrics {
+            append: self.append.snapshot(),
+            commit: self.commit.snapshot(),
+            apply: self.apply.snapshot(),
+            append_entries: targets.iter().map(|(id, h)| (*id, h.snapshot())).collect(),
+        }
+    }
+}
@@ -6,17 +6,39 @@
 //!
 //! Metrics are observed on a running Raft node via the `Raft::metrics()` method, which will
 //! return a stream of metrics.
+//!
+//! With feature `prometheus`, [`RaftMetrics::render_prometheus`] renders the metrics in the Prometheus text exposition
+//! format.
+//!
+//! The latencies of the write stages and of AppendEntries RPCs are recorded in histograms, returned by
+//! `Raft::latency_metrics()`.
 
+mod histogram;
+mod latency_metrics;
+#[cfg(feature = "prometheus")] mod prometheus;
 mod raft_metrics;
 mod replication_metrics;
+mod snapshot_transfer_metrics;
 mod wait;
 
+#[cfg(test)] mod histogram_test;
+#[cfg(all(test, feature = "prometheus"))] mod prometheus_test;
 #[cfg(test)] mod replication_metrics_test;
 #[cfg(test)] mod wait_test;
 
+pub(crate) use histogram::Histogram;
+pub use histogram::LatencyHistogram;
+pub use latency_metrics::LatencyMetrics;
+pub(crate) use latency_metrics::LatencyRecorder;
 pub use raft_metrics::RaftMetrics;
+pub(crate) use replication_metrics::AddTarget;
 pub use replication_metrics::ReplicationMetrics;
+pub(crate) use replication_metrics::ReplicationStatus;
 pub use replication_metrics::ReplicationTargetMetrics;
+pub use replication_metrics::SnapshotProgress;
 pub(crate) use replication_metrics::UpdateMatchedLogId;
+pub(crate) use replication_metrics::UpdateReplicationStatus;
+pub(crate) use snapshot_transfer_metrics::SnapshotTransferCounter;
+pub use snapshot_transfer_metrics::SnapshotTransferMetrics;
 pub use wait::Wait;
 pub use wait::WaitError;
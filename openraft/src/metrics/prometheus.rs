@@ -0,0 +1,227 @@
+//! Render [`RaftMetrics`] in the Prometheus text exposition format.
+
+use std::fmt::Display;
+use std::fmt::Write;
+
+use crate::core::replication_lag;
+use crate::core::ServerState;
+use crate::metrics::RaftMetrics;
+use crate::node::Node;
+use crate::NodeId;
+
+/// All of the server states, a metric is rendered for each of them.
+const SERVER_STATES: [ServerState; 5] = [
+    ServerState::Learner,
+    ServerState::Follower,
+    ServerState::Candidate,
+    ServerState::Leader,
+    ServerState::Shutdown,
+];
+
+impl<NID, N> RaftMetrics<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// Render the metrics in the [Prometheus text exposition format][format], so that they can be served by any
+    /// HTTP server as a scrape target.
+    ///
+    /// Every sample has a label `id` of this node. Replication metrics have another label `target` of the follower or
+    /// learner, and they are rendered only on the leader. A metric whose value is `None`, such as
+    /// `openraft_snapshot_index` before a snapshot is built, is not rendered.
+    ///
+    /// ```ignore
+    /// let text = raft.metrics().borrow().render_prometheus();
+    /// ```
+    ///
+    /// [format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+    pub fn render_prometheus(&self) -> String {
+        let mut w = Writer {
+            buf: String::new(),
+            id: escape(&self.id),
+        };
+
+        w.header("openraft_current_term", "gauge", "The current term of the Raft node.");
+        w.sample("openraft_current_term", "", self.current_term);
+
+        w.header(
+            "openraft_last_log_index",
+            "gauge",
+            "The index of the last log appended to this node.",
+        );
+        if let Some(index) = self.last_log_index {
+            w.sample("openraft_last_log_index", "", index);
+        }
+
+        w.header(
+            "openraft_last_applied_index",
+            "gauge",
+            "The index of the last log applied to the state machine.",
+        );
+        if let Some(log_id) = &self.last_applied {
+            w.sample("openraft_last_applied_index", "", log_id.index);
+        }
+
+        w.header(
+            "openraft_snapshot_index",
+            "gauge",
+            "The index of the last log included in the current snapshot.",
+        );
+        if let Some(log_id) = &self.snapshot {
+            w.sample("openraft_snapshot_index", "", log_id.index);
+        }
+
+        w.header(
+            "openraft_server_state",
+            "gauge",
+            "The server state of this node, 1 for the current state and 0 for the others.",
+        );
+        for state in SERVER_STATES.iter() {
+            let labels = format!(",state=\"{:?}\"", state);
+            w.sample("openraft_server_state", &labels, (state == &self.state) as u64);
+        }
+
+        w.header(
+            "openraft_current_leader",
+            "gauge",
+            "The leader known by this node, labeled by its id. It is absent if there is no known leader.",
+        );
+        if let Some(leader) = &self.current_leader {
+            let labels = format!(",leader=\"{}\"", escape(leader));
+            w.sample("openraft_current_leader", &labels, 1);
+        }
+
+        let membership = &self.membership_config;
+        let voters = membership.voter_ids().count();
+        let nodes = membership.nodes().count();
+
+        w.header(
+            "openraft_membership_voters",
+            "gauge",
+            "The number of voters in the membership.",
+        );
+        w.sample("openraft_membership_voters", "", voters);
+
+        w.header(
+            "openraft_membership_learners",
+            "gauge",
+            "The number of learners in the membership.",
+        );
+        w.sample("openraft_membership_learners", "", nodes - voters);
+
+        w.header(
+            "openraft_elections_total",
+            "counter",
+            "The number of elections this node has started.",
+        );
+        w.sample("openraft_elections_total", "", self.elections);
+
+        w.header(
+            "openraft_snapshots_built_total",
+            "counter",
+            "The number of snapshots this node has built.",
+        );
+        w.sample("openraft_snapshots_built_total", "", self.snapshots_built);
+
+        let transfer = &self.snapshot_transfer;
+
+        w.header(
+            "openraft_snapshots_sent_total",
+            "counter",
+            "The number of snapshots completely sent to and accepted by other nodes.",
+        );
+        w.sample("openraft_snapshots_sent_total", "", transfer.sent_snapshots);
+
+        w.header(
+            "openraft_snapshot_sent_bytes_total",
+            "counter",
+            "The number of bytes of snapshot chunks sent to and accepted by other nodes.",
+        );
+        w.sample("openraft_snapshot_sent_bytes_total", "", transfer.sent_bytes);
+
+        w.header(
+            "openraft_snapshot_sent_raw_bytes_total",
+            "counter",
+            "The number of raw bytes of snapshot data sent to and accepted by other nodes.",
+        );
+        w.sample("openraft_snapshot_sent_raw_bytes_total", "", transfer.sent_raw_bytes);
+
+        w.header(
+            "openraft_snapshot_received_bytes_total",
+            "counter",
+            "The number of bytes of snapshot chunks received from the leader.",
+        );
+        w.sample("openraft_snapshot_received_bytes_total", "", transfer.received_bytes);
+
+        w.header(
+            "openraft_snapshot_received_raw_bytes_total",
+            "counter",
+            "The number of raw bytes of snapshot data received from the leader.",
+        );
+        w.sample(
+            "openraft_snapshot_received_raw_bytes_total",
+            "",
+            transfer.received_raw_bytes,
+        );
+
+        // Samples of a metric have to be grouped together, following its `HELP` and `TYPE`.
+        let targets = match &self.replication {
+            Some(replication) => replication
+                .data()
+                .replication
+                .iter()
+                .map(|(target, m)| (format!(",target=\"{}\"", escape(target)), m.matched_log_id()))
+                .collect::<Vec<_>>(),
+            None => vec![],
+        };
+
+        w.header(
+            "openraft_replication_matched_index",
+            "gauge",
+            "The index of the last log replicated to a target, rendered only on the leader.",
+        );
+        for (labels, matched) in targets.iter() {
+            w.sample(
+                "openraft_replication_matched_index",
+                labels,
+                matched.unwrap_or_default().index,
+            );
+        }
+
+        w.header(
+            "openraft_replication_lag",
+            "gauge",
+            "The number of logs the leader has but a target does not, rendered only on the leader.",
+        );
+        for (labels, matched) in targets.iter() {
+            let lag = replication_lag(&matched.map(|x| x.index), &self.last_log_index);
+            w.sample("openraft_replication_lag", labels, lag);
+        }
+
+        w.buf
+    }
+}
+
+/// Builds the exposition text, all samples are labeled with the id of this node.
+struct Writer {
+    buf: String,
+    id: String,
+}
+
+impl Writer {
+    fn header(&mut self, name: &str, typ: &str, help: &str) {
+        // Safe unwrap(): writing to a String never fails.
+        writeln!(self.buf, "# HELP {} {}", name, help).unwrap();
+        writeln!(self.buf, "# TYPE {} {}", name, typ).unwrap();
+    }
+
+    /// Write a sample, `labels` are the labels other than `id`, each starts with a comma.
+    fn sample(&mut self, name: &str, labels: &str, value: impl Display) {
+        writeln!(self.buf, "{}{{id=\"{}\"{}}} {}", name, self.id, labels, value).unwrap();
+    }
+}
+
+/// Escape a label value: backslash, double-quote and line feed have to be escaped.
+fn escape(v: &impl Display) -> String {
+    v.to_string().replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use maplit::btreemap;
+use maplit::btreeset;
+use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::membership::EffectiveMembership;
+use crate::metrics::ReplicationMetrics;
+use crate::metrics::ReplicationTargetMetrics;
+use crate::metrics::SnapshotTransferMetrics;
+use crate::versioned::Versioned;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftMetrics;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+#[test]
+fn test_render_prometheus_initial() -> anyhow::Result<()> {
+    let m = RaftMetrics::<u64, ()>::new_initial(3);
+
+    let text = m.render_prometheus();
+
+    assert!(text.contains("openraft_current_term{id=\"3\"} 0\n"));
+    assert!(text.contains("openraft_server_state{id=\"3\",state=\"Follower\"} 1\n"));
+    assert!(text.contains("openraft_server_state{id=\"3\",state=\"Leader\"} 0\n"));
+    assert!(text.contains("openraft_membership_voters{id=\"3\"} 0\n"));
+
+    // Metrics without a value are not rendered.
+    assert!(!text.contains("openraft_last_log_index{"));
+    assert!(!text.contains("openraft_last_applied_index{"));
+    assert!(!text.contains("openraft_snapshot_index{"));
+    assert!(!text.contains("openraft_current_leader{"));
+    assert!(!text.contains("openraft_replication_lag{"));
+
+    Ok(())
+}
+
+#[test]
+fn test_render_prometheus_leader() -> anyhow::Result<()> {
+    let mut m = RaftMetrics::<u64, ()>::new_initial(1);
+    m.current_term = 2;
+    m.last_log_index = Some(10);
+    m.last_applied = Some(log_id(2, 9));
+    m.snapshot = Some(log_id(1, 5));
+    m.elections = 3;
+    m.snapshots_built = 4;
+    m.snapshot_transfer = SnapshotTransferMetrics {
+        sent_snapshots: 1,
+        sent_bytes: 100,
+        sent_raw_bytes: 200,
+        received_bytes: 0,
+        received_raw_bytes: 0,
+    };
+    m.state = ServerState::Leader;
+    m.current_leader = Some(1);
+    m.membership_config = Arc::new(EffectiveMembership::new(
+        Some(log_id(1, 1)),
+        Membership::new(vec![btreeset! {1,2}], btreeset! {3,4}),
+    ));
+    m.replication = Some(Versioned::new(ReplicationMetrics {
+        replication: btreemap! {
+            2 => ReplicationTargetMetrics::new(log_id(2, 10)),
+            3 => ReplicationTargetMetrics::new(log_id(2, 7)),
+            4 => ReplicationTargetMetrics::default(),
+        },
+    }));
+
+    let want = r#"# HELP openraft_current_term The current term of the Raft node.
+# TYPE openraft_current_term gauge
+openraft_current_term{id="1"} 2
+# HELP openraft_last_log_index The index of the last log appended to this node.
+# TYPE openraft_last_log_index gauge
+openraft_last_log_index{id="1"} 10
+# HELP openraft_last_applied_index The index of the last log applied to the state machine.
+# TYPE openraft_last_applied_index gauge
+openraft_last_applied_index{id="1"} 9
+# HELP openraft_snapshot_index The index of the last log included in the current snapshot.
+# TYPE openraft_snapshot_index gauge
+openraft_snapshot_index{id="1"} 5
+# HELP openraft_server_state The server state of this node, 1 for the current state and 0 for the others.
+# TYPE openraft_server_state gauge
+openraft_server_state{id="1",state="Learner"} 0
+openraft_server_state{id="1",state="Follower"} 0
+openraft_server_state{id="1",state="Candidate"} 0
+openraft_server_state{id="1",state="Leader"} 1
+openraft_server_state{id="1",state="Shutdown"} 0
+# HELP openraft_current_leader The leader known by this node, labeled by its id. It is absent if there is no known leader.
+# TYPE openraft_current_leader gauge
+openraft_current_leader{id="1",leader="1"} 1
+# HELP openraft_membership_voters The number of voters in the membership.
+# TYPE openraft_membership_voters gauge
+openraft_membership_voters{id="1"} 2
+# HELP openraft_membership_learners The number of learners in the membership.
+# TYPE openraft_membership_learners gauge
+openraft_membership_learners{id="1"} 2
+# HELP openraft_elections_total The number of elections this node has started.
+# TYPE openraft_elections_total counter
+openraft_elections_total{id="1"} 3
+# HELP openraft_snapshots_built_total The number of snapshots this node has built.
+# TYPE openraft_snapshots_built_total counter
+openraft_snapshots_built_total{id="1"} 4
+# HELP openraft_snapshots_sent_total The number of snapshots completely sent to and accepted by other nodes.
+# TYPE openraft_snapshots_sent_total counter
+openraft_snapshots_sent_total{id="1"} 1
+# HELP openraft_snapshot_sent_bytes_total The number of bytes of snapshot chunks sent to and accepted by other nodes.
+# TYPE openraft_snapshot_sent_bytes_total counter
+openraft_snapshot_sent_bytes_total{id="1"} 100
+# HELP openraft_snapshot_sent_raw_bytes_total The number of raw bytes of snapshot data sent to and accepted by other nodes.
+# TYPE openraft_snapshot_sent_raw_bytes_total counter
+openraft_snapshot_sent_raw_bytes_total{id="1"} 200
+# HELP openraft_snapshot_received_bytes_total The number of bytes of snapshot chunks received from the leader.
+# TYPE openraft_snapshot_received_bytes_total counter
+openraft_snapshot_received_bytes_total{id="1"} 0
+# HELP openraft_snapshot_received_raw_bytes_total The number of raw bytes of snapshot data received from the leader.
+# TYPE openraft_snapshot_received_raw_bytes_total counter
+openraft_snapshot_received_raw_bytes_total{id="1"} 0
+# HELP openraft_replication_matched_index The index of the last log replicated to a target, rendered only on the leader.
+# TYPE openraft_replication_matched_index gauge
+openraft_replication_matched_index{id="1",target="2"} 10
+openraft_replication_matched_index{id="1",target="3"} 7
+openraft_replication_matched_index{id="1",target="4"} 0
+# HELP openraft_replication_lag The number of logs the leader has but a target does not, rendered only on the leader.
+# TYPE openraft_replication_lag gauge
+openraft_replication_lag{id="1",target="2"} 0
+openraft_replication_lag{id="1",target="3"} 3
+openraft_replication_lag{id="1",target="4"} 11
+"#;
+
+    assert_eq!(want, m.render_prometheus());
+
+    Ok(())
+}
@@ -4,6 +4,7 @@ use crate::core::ServerState;
 use crate::error::Fatal;
 use crate::membership::EffectiveMembership;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::SnapshotTransferMetrics;
 use crate::node::Node;
 use crate::summary::MessageSummary;
 use crate::versioned::Versioned;
@@ -39,6 +40,20 @@ where
     /// If there is no snapshot, it is (0,0).
     pub snapshot: Option<LogId<NID>>,
 
+    /// The amount of snapshot data sent and received by this node.
+    pub snapshot_transfer: SnapshotTransferMetrics,
+
+    // ---
+    // --- counters ---
+    // ---
+    /// The number of elections this node has started since it started, including the one that made it a leader.
+    ///
+    /// A PreVote round that does not lead to an election is not counted.
+    pub elections: u64,
+
+    /// The number of snapshots this node has built since it started.
+    pub snapshots_built: u64,
+
     // ---
     // --- cluster ---
     // ---
@@ -94,6 +109,9 @@ where
             current_leader: None,
             membership_config: Arc::new(EffectiveMembership::default()),
             snapshot: None,
+            snapshot_transfer: SnapshotTransferMetrics::default(),
+            elections: 0,
+            snapshots_built: 0,
             replication: None,
         }
     }
@@ -2,7 +2,11 @@ use std::collections::BTreeMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::time::Instant;
+
+use crate::log_id_range::LogIdRange;
 use crate::versioned::Update;
 use crate::versioned::UpdateError;
 use crate::LeaderId;
@@ -33,6 +37,29 @@ impl<NID: NodeId> MessageSummary<ReplicationMetrics<NID>> for ReplicationMetrics
     }
 }
 
+/// Add a replication target to `LeaderMetrics.replication` when the replication stream to it is spawned.
+pub(crate) struct AddTarget<NID: NodeId> {
+    pub target: NID,
+
+    /// The matched log id when the replication stream is spawned, `None` if nothing is known to match.
+    pub matched: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> Update<ReplicationMetrics<NID>> for AddTarget<NID> {
+    /// Inserting can not be done in place
+    fn apply_in_place(&self, _to: &Arc<ReplicationMetrics<NID>>) -> Result<(), UpdateError> {
+        Err(UpdateError::CanNotUpdateInPlace)
+    }
+
+    fn apply_mut(&self, to: &mut ReplicationMetrics<NID>) {
+        to.replication.insert(self.target, ReplicationTargetMetrics {
+            matched_leader_id: self.matched.map(|x| x.leader_id),
+            matched_index: AtomicU64::new(self.matched.map(|x| x.index).unwrap_or_default()),
+            status: ReplicationStatus::default(),
+        });
+    }
+}
+
 /// Update one replication metrics in `LeaderMetrics.replication`.
 pub(crate) struct UpdateMatchedLogId<NID: NodeId> {
     pub target: NID,
@@ -44,7 +71,7 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
     fn apply_in_place(&self, to: &Arc<ReplicationMetrics<NID>>) -> Result<(), UpdateError> {
         let target_metrics = to.replication.get(&self.target).ok_or(UpdateError::CanNotUpdateInPlace)?;
 
-        if target_metrics.matched_leader_id == self.matched.leader_id {
+        if target_metrics.matched_leader_id == Some(self.matched.leader_id) {
             target_metrics.matched_index.store(self.matched.index, Ordering::Relaxed);
             return Ok(());
         }
@@ -52,15 +79,38 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
         Err(UpdateError::CanNotUpdateInPlace)
     }
 
-    /// To insert a new record always work.
+    /// To insert a new record always work. The status of an existing record is kept.
     fn apply_mut(&self, to: &mut ReplicationMetrics<NID>) {
+        let status = to.replication.get(&self.target).map(|x| x.status.clone()).unwrap_or_default();
+
         to.replication.insert(self.target, ReplicationTargetMetrics {
-            matched_leader_id: self.matched.leader_id,
+            matched_leader_id: Some(self.matched.leader_id),
             matched_index: AtomicU64::new(self.matched.index),
+            status,
         });
     }
 }
 
+/// Replace the status of one replication target in `LeaderMetrics.replication` with a copy of the latest one.
+pub(crate) struct UpdateReplicationStatus<NID: NodeId> {
+    pub target: NID,
+    pub status: ReplicationStatus<NID>,
+}
+
+impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateReplicationStatus<NID> {
+    /// The status is not atomic, thus it can not be updated in place.
+    fn apply_in_place(&self, _to: &Arc<ReplicationMetrics<NID>>) -> Result<(), UpdateError> {
+        Err(UpdateError::CanNotUpdateInPlace)
+    }
+
+    /// A target that is not in the metrics is not added: it is added when its replication stream is spawned.
+    fn apply_mut(&self, to: &mut ReplicationMetrics<NID>) {
+        if let Some(target_metrics) = to.replication.get_mut(&self.target) {
+            target_metrics.status = self.status.clone();
+        }
+    }
+}
+
 /// Remove one replication metrics in `LeaderMetrics.replication`.
 pub(crate) struct RemoveTarget<NID: NodeId> {
     pub target: NID,
@@ -77,11 +127,74 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for RemoveTarget<NID> {
     }
 }
 
+/// The progress of streaming a snapshot to a replication target.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SnapshotProgress {
+    /// The number of bytes of snapshot data acknowledged by the target.
+    pub sent_bytes: u64,
+
+    /// The size of the snapshot data in bytes.
+    pub total_bytes: u64,
+}
+
+/// The status of a replication stream that changes too often to be sent to RaftCore as a whole.
+///
+/// It is shared by the replication stream with RaftCore, which copies it into [`ReplicationTargetMetrics`] when
+/// notified of a change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub(crate) struct ReplicationStatus<NID: NodeId> {
+    /// The time when the last successful response is received.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) last_ack: Option<Instant>,
+
+    /// The range of logs that are sent but not yet acknowledged.
+    pub(crate) inflight: Option<LogIdRange<NID>>,
+
+    /// The progress of the snapshot being streamed.
+    pub(crate) snapshot: Option<SnapshotProgress>,
+
+    /// The number of RPC errors since the last successful response.
+    pub(crate) consecutive_errors: u64,
+
+    /// The last RPC error.
+    pub(crate) last_error: Option<String>,
+}
+
+impl<NID: NodeId> ReplicationStatus<NID> {
+    /// Record a successful response and reset the consecutive error count.
+    pub(crate) fn ack(&mut self, at: Instant) {
+        self.last_ack = Some(at);
+        self.consecutive_errors = 0;
+    }
+
+    /// Record a failed RPC.
+    pub(crate) fn error(&mut self, error: String) {
+        self.consecutive_errors += 1;
+        self.last_error = Some(error);
+    }
+}
+
+/// The replication metrics of a follower or learner, reported by the leader.
+///
+/// An entry is added when the replication stream to the target is spawned, and its matched log id is updated when the
+/// target acknowledges more logs. Before the target acknowledges any log, the matched log id is the default one.
+///
+/// The status, such as the last ack time, the inflight logs or the snapshot progress, is a copy taken when the metrics
+/// are reported: it does not change after the metrics are received.
+/// Every status change is reported through `Raft::metrics()`, thus a [`Wait`](`crate::metrics::Wait`) on it is woken
+/// up.
+///
+/// Two metrics are equal if they have the same matched log id; the status is not compared.
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct ReplicationTargetMetrics<NID: NodeId> {
-    pub(crate) matched_leader_id: LeaderId<NID>,
+    /// It is `None` until the target acknowledges a log.
+    pub(crate) matched_leader_id: Option<LeaderId<NID>>,
     pub(crate) matched_index: AtomicU64,
+
+    pub(crate) status: ReplicationStatus<NID>,
 }
 
 impl<NID: NodeId> Clone for ReplicationTargetMetrics<NID> {
@@ -89,6 +202,7 @@ impl<NID: NodeId> Clone for ReplicationTargetMetrics<NID> {
         Self {
             matched_leader_id: self.matched_leader_id,
             matched_index: AtomicU64::new(self.matched_index.load(Ordering::Relaxed)),
+            status: self.status.clone(),
         }
     }
 }
@@ -105,17 +219,52 @@ impl<NID: NodeId> Eq for ReplicationTargetMetrics<NID> {}
 impl<NID: NodeId> ReplicationTargetMetrics<NID> {
     pub fn new(log_id: LogId<NID>) -> Self {
         Self {
-            matched_leader_id: log_id.leader_id,
+            matched_leader_id: Some(log_id.leader_id),
             matched_index: AtomicU64::new(log_id.index),
+            status: Default::default(),
         }
     }
 
     pub fn matched(&self) -> LogId<NID> {
+        self.matched_log_id().unwrap_or_default()
+    }
+
+    /// The matched log id, or `None` if the target has not yet acknowledged any log.
+    pub(crate) fn matched_log_id(&self) -> Option<LogId<NID>> {
         let index = self.matched_index.load(Ordering::Relaxed);
-        LogId {
-            leader_id: self.matched_leader_id,
-            index,
-        }
+        self.matched_leader_id.map(|leader_id| LogId { leader_id, index })
+    }
+
+    /// The time elapsed since the last successful response from the target.
+    ///
+    /// It is `None` if the target has not yet responded successfully to this leader.
+    pub fn last_ack_elapsed(&self) -> Option<Duration> {
+        self.status.last_ack.map(|t| t.elapsed())
+    }
+
+    /// The logs that are sent to the target but not yet acknowledged, a left open right closed range:
+    /// `(prev_log_id, last_log_id]`.
+    ///
+    /// It is `None` if no logs are inflight.
+    pub fn inflight(&self) -> Option<(Option<LogId<NID>>, Option<LogId<NID>>)> {
+        self.status.inflight.map(|r| (r.prev_log_id, r.last_log_id))
+    }
+
+    /// The progress of the snapshot being streamed to the target.
+    ///
+    /// It is `None` if no snapshot is being streamed.
+    pub fn snapshot_progress(&self) -> Option<SnapshotProgress> {
+        self.status.snapshot
+    }
+
+    /// The number of failed RPCs to the target since the last successful response.
+    pub fn consecutive_errors(&self) -> u64 {
+        self.status.consecutive_errors
+    }
+
+    /// The last RPC error replicating to the target, it is kept after the target recovers.
+    pub fn last_error(&self) -> Option<String> {
+        self.status.last_error.clone()
     }
 }
 
@@ -1,5 +1,9 @@
+use crate::metrics::AddTarget;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::ReplicationStatus;
+use crate::metrics::ReplicationTargetMetrics;
 use crate::metrics::UpdateMatchedLogId;
+use crate::metrics::UpdateReplicationStatus;
 use crate::versioned::Updatable;
 use crate::versioned::Versioned;
 use crate::LeaderId;
@@ -101,3 +105,63 @@ fn test_versioned_methods() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_replication_status() -> anyhow::Result<()> {
+    let mut a = Versioned::new(ReplicationMetrics::<u64>::default());
+
+    // A target is added before it acknowledges any log.
+    a.update(AddTarget {
+        target: 1,
+        matched: None,
+    });
+    assert_eq!("{ver:1, LeaderMetrics{1:0-0-0}}", a.summary());
+
+    let b = a.clone();
+
+    // A status change bumps the version, but a copy received earlier does not change.
+    let mut status = ReplicationStatus::<u64>::default();
+    status.error("foo".to_string());
+    a.update(UpdateReplicationStatus { target: 1, status });
+
+    assert_eq!(2, a.version());
+    assert_ne!(a, b);
+    assert_eq!(Some("foo".to_string()), a.data().replication[&1].last_error());
+    assert_eq!(None, b.data().replication[&1].last_error());
+
+    // The status of a target not in the metrics is ignored.
+    a.update(UpdateReplicationStatus {
+        target: 2,
+        status: ReplicationStatus::default(),
+    });
+    assert!(!a.data().replication.contains_key(&2));
+
+    // Updating the matched log id keeps the status.
+    a.update(UpdateMatchedLogId {
+        target: 1,
+        matched: LogId::new(LeaderId::new(1, 2), 3),
+    });
+    assert_eq!("{ver:4, LeaderMetrics{1:1-2-3}}", a.summary());
+    assert_eq!(1, a.data().replication[&1].consecutive_errors());
+
+    Ok(())
+}
+
+#[test]
+fn test_replication_target_metrics_eq() -> anyhow::Result<()> {
+    let log_id = LogId::new(LeaderId::new(1, 2), 3);
+
+    let a = ReplicationTargetMetrics::<u64>::new(log_id);
+    let mut b = ReplicationTargetMetrics::<u64>::new(log_id);
+    assert_eq!(a, b);
+    assert_eq!(a, a.clone());
+
+    // Only the matched log id is compared.
+    b.status.error("foo".to_string());
+    assert_eq!(a, b);
+
+    let c = ReplicationTargetMetrics::<u64>::new(LogId::new(LeaderId::new(1, 2), 4));
+    assert_ne!(a, c);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// The amount of snapshot data this node has transferred.
+///
+/// `*_bytes` are the bytes of snapshot chunks as they are sent over the network, i.e., compressed if
+/// [`Config::snapshot_compression`](`crate::Config::snapshot_compression`) is enabled.
+/// `*_raw_bytes` are the bytes of the snapshot data before compression.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SnapshotTransferMetrics {
+    /// The number of snapshots completely sent to and accepted by other nodes.
+    pub sent_snapshots: u64,
+
+    /// The number of bytes of snapshot chunks sent to and accepted by other nodes.
+    pub sent_bytes: u64,
+
+    /// The number of raw bytes of snapshot data sent to and accepted by other nodes.
+    pub sent_raw_bytes: u64,
+
+    /// The number of bytes of snapshot chunks received from the leader.
+    pub received_bytes: u64,
+
+    /// The number of raw bytes of snapshot data received from the leader.
+    pub received_raw_bytes: u64,
+}
+
+/// Counts the snapshot data transferred, shared by `RaftCore` and the replication tasks.
+#[derive(Debug, Default)]
+pub(crate) struct SnapshotTransferCounter {
+    sent_snapshots: AtomicU64,
+    sent_bytes: AtomicU64,
+    sent_raw_bytes: AtomicU64,
+    received_bytes: AtomicU64,
+    received_raw_bytes: AtomicU64,
+}
+
+impl SnapshotTransferCounter {
+    pub(crate) fn add_sent(&self, bytes: u64, raw_bytes: u64) {
+        self.sent_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.sent_raw_bytes.fetch_add(raw_bytes, Ordering::Relaxed);
+    }
+
+    /// Count a snapshot whose last chunk is accepted by the target.
+    pub(crate) fn add_sent_snapshot(&self) {
+        self.sent_snapshots.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_received(&self, bytes: u64, raw_bytes: u64) {
+        self.received_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.received_raw_bytes.fetch_add(raw_bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn metrics(&self) -> SnapshotTransferMetrics {
+        SnapshotTransferMetrics {
+            sent_snapshots: self.sent_snapshots.load(Ordering::Relaxed),
+            sent_bytes: self.sent_bytes.load(Ordering::Relaxed),
+            sent_raw_bytes: self.sent_raw_bytes.load(Ordering::Relaxed),
+            received_bytes: self.received_bytes.load(Ordering::Relaxed),
+            received_raw_bytes: self.received_raw_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
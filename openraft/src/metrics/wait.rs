@@ -149,6 +149,23 @@ where
         .await
     }
 
+    /// Wait until `last_applied` reaches at least `want_index`(inclusive) or timeout.
+    ///
+    /// Unlike [`Wait::log_at_least`], it does not check `last_log_index`. It is used to wait for the log id returned by
+    /// `Raft::read_index()` to be applied before serving a linearizable read.
+    #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
+    pub async fn applied_index_at_least(
+        &self,
+        want_index: Option<u64>,
+        msg: impl ToString,
+    ) -> Result<RaftMetrics<NID, N>, WaitError> {
+        self.metrics(
+            |x| x.last_applied.index() >= want_index,
+            &format!("{} .last_applied >= {:?}", msg.to_string(), want_index),
+        )
+        .await
+    }
+
     /// Wait for `state` to become `want_state` or timeout.
     #[tracing::instrument(level = "trace", skip(self), fields(msg=msg.to_string().as_str()))]
     pub async fn state(&self, want_state: ServerState, msg: impl ToString) -> Result<RaftMetrics<NID, N>, WaitError> {
@@ -64,6 +64,28 @@ async fn test_wait() -> anyhow::Result<()> {
         assert!(got_least4.is_err());
     }
 
+    {
+        // wait for applied index
+        let (init, w, tx) = init_wait_test::<u64, ()>();
+
+        let h = tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            let mut update = init.clone();
+            update.last_applied = Some(LogId::new(LeaderId::new(1, 0), 3));
+            let rst = tx.send(update);
+            assert!(rst.is_ok());
+        });
+        let got = w.applied_index_at_least(Some(3), "applied").await?;
+        let got_none = w.applied_index_at_least(None, "applied").await?;
+        let got_least4 = w.applied_index_at_least(Some(4), "applied").await;
+        h.await?;
+
+        assert_eq!(Some(3), got.last_applied.index());
+        assert_eq!(Some(3), got_none.last_applied.index());
+
+        assert!(got_least4.is_err());
+    }
+
     {
         // wait for state
         let (init, w, tx) = init_wait_test::<u64, ()>();
@@ -192,6 +214,9 @@ where
         )),
 
         snapshot: None,
+        snapshot_transfer: Default::default(),
+        elections: 0,
+        snapshots_built: 0,
         replication: None,
     };
     let (tx, rx) = watch::channel(init.clone());
@@ -3,16 +3,21 @@
 use std::error::Error;
 use std::fmt::Formatter;
 
+use anyerror::AnyError;
 use async_trait::async_trait;
 
 use crate::error::AppendEntriesError;
 use crate::error::InstallSnapshotError;
+use crate::error::NetworkError;
 use crate::error::RPCError;
+use crate::error::TimeoutNowError;
 use crate::error::VoteError;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::InstallSnapshotRequest;
 use crate::raft::InstallSnapshotResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::RaftTypeConfig;
@@ -21,8 +26,10 @@ use crate::RaftTypeConfig;
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum RPCTypes {
     Vote,
+    PreVote,
     AppendEntries,
     InstallSnapshot,
+    TimeoutNow,
 }
 
 impl std::fmt::Display for RPCTypes {
@@ -62,6 +69,42 @@ where C: RaftTypeConfig
         &mut self,
         rpc: VoteRequest<C::NodeId>,
     ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>>;
+
+    /// Send a PreVote RPC to the target Raft node.
+    ///
+    /// A PreVote request has the same form as a RequestVote request, but the receiver only tells whether it would
+    /// grant the vote, without persisting anything.
+    /// It is only used when [`Config::enable_pre_vote`](`crate::Config::enable_pre_vote`) is `true`.
+    ///
+    /// The default implementation returns a [`NetworkError`] without sending anything. It has to be implemented, by
+    /// calling [`Raft::pre_vote()`](`crate::Raft::pre_vote`) on the target, before PreVote is enabled.
+    async fn send_pre_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        let _ = rpc;
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "PreVote RPC is not implemented",
+        ))))
+    }
+
+    /// Send a TimeoutNow RPC to the target Raft node.
+    ///
+    /// It is sent by a leader that is transferring its leadership to the target, to let the target start an election
+    /// at once. See [`Raft::transfer_leader()`](`crate::Raft::transfer_leader`).
+    ///
+    /// The default implementation returns a [`NetworkError`] without sending anything, in which case a leadership
+    /// transfer always times out. It has to be implemented, by calling
+    /// [`Raft::timeout_now()`](`crate::Raft::timeout_now`) on the target, before leadership can be transferred.
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, C::Node, TimeoutNowError<C::NodeId>>> {
+        let _ = rpc;
+        Err(RPCError::Network(NetworkError::new(&AnyError::error(
+            "TimeoutNow RPC is not implemented",
+        ))))
+    }
 }
 
 /// A trait defining the interface for a Raft network factory to create connections between cluster members.
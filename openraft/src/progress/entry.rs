@@ -2,6 +2,8 @@ use std::borrow::Borrow;
 use std::fmt::Display;
 use std::fmt::Formatter;
 
+use tokio::time::Instant;
+
 use crate::summary::MessageSummary;
 use crate::LogId;
 use crate::LogIdOptionExt;
@@ -26,6 +28,11 @@ pub(crate) struct ProgressEntry<NID: NodeId> {
 
     /// The last matching log index has not yet been determined.
     pub(crate) searching: Option<Searching>,
+
+    /// The time when the latest request that is acknowledged by the target node was sent.
+    ///
+    /// It is used to calculate the leader lease.
+    pub(crate) last_ack: Option<Instant>,
 }
 
 impl<NID: NodeId> ProgressEntry<NID> {
@@ -34,6 +41,7 @@ impl<NID: NodeId> ProgressEntry<NID> {
         Self {
             matching,
             searching: None,
+            last_ack: None,
         }
     }
 
@@ -53,6 +61,16 @@ impl<NID: NodeId> ProgressEntry<NID> {
         Self {
             matching: None,
             searching,
+            last_ack: None,
+        }
+    }
+
+    /// Update the time when the latest acknowledged request was sent.
+    ///
+    /// A response to an earlier request may arrive later, thus it only keeps the greatest time.
+    pub(crate) fn update_last_ack(&mut self, sent_at: Instant) {
+        if self.last_ack < Some(sent_at) {
+            self.last_ack = Some(sent_at);
         }
     }
 
@@ -78,27 +96,34 @@ impl<NID: NodeId> ProgressEntry<NID> {
         }
     }
 
-    #[allow(dead_code)]
+    /// Update the progress when the log at index `conflict` is found not matching on the target.
+    ///
+    /// With multiple inflight AppendEntries requests, a conflict may be responded when no binary search is in
+    /// progress, e.g., a later request arrives at the target before an earlier one. Then it starts a new search
+    /// between the matching log and `conflict`.
+    /// A conflict at a log that is already known to be matching is stale and is ignored.
     pub(crate) fn update_conflicting(&mut self, conflict: u64) {
         tracing::debug!(self = debug(&self), conflict = display(conflict), "update_conflict");
 
         let matching_next = self.matching.next_index();
 
-        debug_assert!(conflict >= matching_next);
-
-        if let Some(s) = &mut self.searching {
-            debug_assert!(conflict < s.end);
-            debug_assert!(conflict + 1 >= s.mid, "conflict can only be the prev_log_index");
+        if conflict < matching_next {
+            tracing::debug!("conflict({}) is stale, matching: {}", conflict, self.matching.summary());
+            return;
+        }
 
-            s.end = conflict;
+        let end = match &self.searching {
+            Some(s) => std::cmp::min(conflict, s.end),
+            None => conflict,
+        };
 
-            if matching_next >= s.end {
-                self.searching = None;
-            } else {
-                s.mid = Self::calc_mid(matching_next, s.end);
-            }
+        if matching_next >= end {
+            self.searching = None;
         } else {
-            unreachable!("found conflict({}) when searching is None", conflict);
+            self.searching = Some(Searching {
+                mid: Self::calc_mid(matching_next, end),
+                end,
+            });
         }
     }
 
@@ -159,6 +184,9 @@ impl<NID: NodeId> Display for ProgressEntry<NID> {
 mod tests {
     use std::borrow::Borrow;
 
+    use tokio::time::Duration;
+    use tokio::time::Instant;
+
     use crate::progress::entry::ProgressEntry;
     use crate::LeaderId;
     use crate::LogId;
@@ -226,4 +254,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_update_conflict_with_multiple_inflight() -> anyhow::Result<()> {
+        let mut pe = ProgressEntry::empty(0);
+        pe.update_matching(Some(log_id(4)));
+        assert!(pe.searching.is_none());
+        assert_eq!((5, 5), pe.sending_start());
+
+        // A stale conflict: log 4 is already matching.
+        pe.update_conflicting(4);
+        assert_eq!(&Some(log_id(4)), pe.borrow());
+        assert!(pe.searching.is_none());
+
+        // The target lacks the log at 5: nothing to search.
+        pe.update_conflicting(5);
+        assert!(pe.searching.is_none());
+        assert_eq!((5, 5), pe.sending_start());
+
+        // A conflict of a later inflight request starts a new search.
+        pe.update_conflicting(40);
+        assert_eq!(&Some(log_id(4)), pe.borrow());
+        assert_eq!((21, 40), pe.sending_start());
+        assert_eq!(Some(39), pe.max_possible_matching());
+
+        // A conflict beyond the current search range does not expand it.
+        pe.update_conflicting(50);
+        assert_eq!((21, 40), pe.sending_start());
+
+        pe.update_matching(Some(log_id(30)));
+        assert_eq!((31, 40), pe.sending_start());
+
+        pe.update_matching(Some(log_id(39)));
+        assert!(pe.searching.is_none());
+        assert_eq!((40, 40), pe.sending_start());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_last_ack() -> anyhow::Result<()> {
+        let mut pe = ProgressEntry::<u64>::empty(20);
+        assert_eq!(None, pe.last_ack);
+
+        let now = Instant::now();
+
+        pe.update_last_ack(now);
+        assert_eq!(Some(now), pe.last_ack);
+
+        pe.update_last_ack(now + Duration::from_millis(10));
+        assert_eq!(Some(now + Duration::from_millis(10)), pe.last_ack);
+
+        // An earlier ack does not override
+        pe.update_last_ack(now + Duration::from_millis(5));
+        assert_eq!(Some(now + Duration::from_millis(10)), pe.last_ack);
+
+        Ok(())
+    }
 }
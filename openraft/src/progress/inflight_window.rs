@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use crate::log_id_range::LogIdRange;
+use crate::progress::Inflight;
+use crate::LogId;
+use crate::LogIdOptionExt;
+use crate::NodeId;
+
+/// A window of the logs being sent to a follower/learner by multiple inflight AppendEntries requests.
+///
+/// The inflight logs are kept in the order they are sent, and every range of logs starts where the previous one ends.
+/// The follower/learner acknowledges them in the same order.
+#[derive(Clone, Debug, Default)]
+#[derive(PartialEq, Eq)]
+pub(crate) struct InflightWindow<NID: NodeId> {
+    inflights: VecDeque<Inflight<NID>>,
+}
+
+impl<NID: NodeId> InflightWindow<NID> {
+    /// The number of inflight ranges of logs.
+    pub(crate) fn len(&self) -> usize {
+        self.inflights.len()
+    }
+
+    /// The index of the first log to send after all inflight logs.
+    ///
+    /// It returns `None` if there is no inflight logs.
+    pub(crate) fn next_index(&self) -> Option<u64> {
+        match self.inflights.back() {
+            Some(Inflight::Logs(logs)) => Some(logs.last_log_id.next_index()),
+            _ => None,
+        }
+    }
+
+    /// The range of all of the inflight logs: `(prev_log_id of the first, last_log_id of the last]`.
+    ///
+    /// It returns `None` if there is no inflight logs.
+    pub(crate) fn range(&self) -> Option<LogIdRange<NID>> {
+        match (self.inflights.front(), self.inflights.back()) {
+            (Some(Inflight::Logs(first)), Some(Inflight::Logs(last))) => {
+                Some(LogIdRange::new(first.prev_log_id, last.last_log_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Add a range of logs that is being sent, after all of the inflight logs.
+    ///
+    /// An empty range, i.e., `Inflight::None` is ignored.
+    pub(crate) fn push(&mut self, inflight: Inflight<NID>) {
+        let logs = match inflight {
+            Inflight::None => return,
+            Inflight::Logs(logs) => logs,
+            Inflight::Snapshot { .. } => {
+                unreachable!("snapshot is not sent in an inflight window");
+            }
+        };
+
+        if let Some(Inflight::Logs(last)) = self.inflights.back() {
+            debug_assert_eq!(last.last_log_id, logs.prev_log_id, "inflight logs must be consecutive");
+        }
+
+        self.inflights.push_back(inflight);
+    }
+
+    /// Update the window when logs upto `upto` are acknowledged by a follower/learner.
+    ///
+    /// Fully acknowledged ranges are removed and a partially acknowledged range is shrunk.
+    pub(crate) fn ack(&mut self, upto: Option<LogId<NID>>) {
+        while let Some(front) = self.inflights.front_mut() {
+            let logs = match front {
+                Inflight::Logs(logs) => *logs,
+                _ => unreachable!("only logs are inflight"),
+            };
+
+            if upto >= logs.last_log_id {
+                self.inflights.pop_front();
+                continue;
+            }
+
+            if upto > logs.prev_log_id {
+                front.ack(upto);
+            }
+            break;
+        }
+    }
+
+    /// Discard all inflight logs, e.g., when a conflict is found or a request fails.
+    ///
+    /// Logs after a conflicting log or after a failed request can not be accepted by the follower/learner.
+    /// They have to be sent again, starting from the replication progress.
+    pub(crate) fn rollback(&mut self) {
+        self.inflights.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::log_id_range::LogIdRange;
+    use crate::progress::Inflight;
+    use crate::progress::InflightWindow;
+    use crate::LeaderId;
+    use crate::LogId;
+
+    fn log_id(index: u64) -> LogId<u64> {
+        LogId {
+            leader_id: LeaderId { term: 1, node_id: 1 },
+            index,
+        }
+    }
+
+    #[test]
+    fn test_inflight_window_push() -> anyhow::Result<()> {
+        let mut w = InflightWindow::<u64>::default();
+        assert_eq!(0, w.len());
+        assert_eq!(None, w.next_index());
+
+        w.push(Inflight::None);
+        assert_eq!(0, w.len());
+
+        w.push(Inflight::logs(None, Some(log_id(5))));
+        w.push(Inflight::logs(Some(log_id(5)), Some(log_id(10))));
+        assert_eq!(2, w.len());
+        assert_eq!(Some(11), w.next_index());
+
+        {
+            let res = std::panic::catch_unwind(|| {
+                let mut w = InflightWindow::<u64>::default();
+                w.push(Inflight::logs(None, Some(log_id(5))));
+                w.push(Inflight::logs(Some(log_id(6)), Some(log_id(10))));
+            });
+            tracing::info!("res: {:?}", res);
+            assert!(res.is_err(), "inflight logs must be consecutive");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inflight_window_ack() -> anyhow::Result<()> {
+        let mut w = InflightWindow::<u64>::default();
+        w.push(Inflight::logs(Some(log_id(2)), Some(log_id(5))));
+        w.push(Inflight::logs(Some(log_id(5)), Some(log_id(10))));
+        w.push(Inflight::logs(Some(log_id(10)), Some(log_id(15))));
+
+        // Ack nothing
+        w.ack(Some(log_id(2)));
+        assert_eq!(3, w.len());
+
+        // Partial ack
+        w.ack(Some(log_id(3)));
+        assert_eq!(3, w.len());
+        assert_eq!(Some(16), w.next_index());
+
+        // Ack the first range
+        w.ack(Some(log_id(5)));
+        assert_eq!(2, w.len());
+
+        // Ack more than one range
+        w.ack(Some(log_id(12)));
+        assert_eq!(1, w.len());
+        assert_eq!(Some(16), w.next_index());
+
+        // A stale ack changes nothing
+        w.ack(Some(log_id(7)));
+        assert_eq!(1, w.len());
+
+        w.ack(Some(log_id(15)));
+        assert_eq!(0, w.len());
+        assert_eq!(None, w.next_index());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inflight_window_range() -> anyhow::Result<()> {
+        let mut w = InflightWindow::<u64>::default();
+        assert_eq!(None, w.range());
+
+        w.push(Inflight::logs(Some(log_id(2)), Some(log_id(5))));
+        w.push(Inflight::logs(Some(log_id(5)), Some(log_id(10))));
+        assert_eq!(Some(LogIdRange::new(Some(log_id(2)), Some(log_id(10)))), w.range());
+
+        w.ack(Some(log_id(7)));
+        assert_eq!(Some(LogIdRange::new(Some(log_id(7)), Some(log_id(10)))), w.range());
+
+        w.rollback();
+        assert_eq!(None, w.range());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inflight_window_rollback() -> anyhow::Result<()> {
+        let mut w = InflightWindow::<u64>::default();
+        w.push(Inflight::logs(Some(log_id(2)), Some(log_id(5))));
+        w.push(Inflight::logs(Some(log_id(5)), Some(log_id(10))));
+
+        w.rollback();
+        assert_eq!(0, w.len());
+        assert_eq!(None, w.next_index());
+
+        Ok(())
+    }
+}
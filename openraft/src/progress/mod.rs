@@ -9,13 +9,14 @@
 mod bench;
 pub(crate) mod entry;
 mod inflight;
+mod inflight_window;
 
 use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::slice::Iter;
 
-// TODO: remove it
-#[allow(unused_imports)] pub(crate) use inflight::Inflight;
+pub(crate) use inflight::Inflight;
+pub(crate) use inflight_window::InflightWindow;
 
 use crate::quorum::QuorumSet;
 
@@ -62,6 +62,15 @@ where
         true
     }
 
+    fn is_election_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        for child in self.data.iter() {
+            if !child.is_election_quorum(ids.clone()) {
+                return false;
+            }
+        }
+        true
+    }
+
     fn ids(&self) -> Self::Iter {
         let mut ids = btreeset! {};
         for child in self.data.iter() {
@@ -88,6 +97,15 @@ where
         true
     }
 
+    fn is_election_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        for child in self.data.iter() {
+            if !child.is_election_quorum(ids.clone()) {
+                return false;
+            }
+        }
+        true
+    }
+
     fn ids(&self) -> Self::Iter {
         let mut ids = btreeset! {};
         for child in self.data.iter() {
@@ -0,0 +1,36 @@
+use crate::quorum::QuorumSet;
+
+/// A majority quorum set: a quorum is more than half of the nodes.
+///
+/// This is the quorum set of the standard raft.
+#[derive(Clone, Debug)]
+#[derive(PartialEq, Eq)]
+pub struct Majority<ID> {
+    ids: Vec<ID>,
+}
+
+impl<ID> Default for Majority<ID> {
+    fn default() -> Self {
+        Self { ids: vec![] }
+    }
+}
+
+impl<ID> From<Vec<ID>> for Majority<ID> {
+    fn from(ids: Vec<ID>) -> Self {
+        Self { ids }
+    }
+}
+
+impl<ID> QuorumSet<ID> for Majority<ID>
+where ID: PartialOrd + Ord + Copy + 'static
+{
+    type Iter = std::collections::btree_set::IntoIter<ID>;
+
+    fn is_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        self.ids.is_quorum(ids)
+    }
+
+    fn ids(&self) -> Self::Iter {
+        self.ids.ids()
+    }
+}
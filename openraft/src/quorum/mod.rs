@@ -6,8 +6,10 @@ mod coherent;
 mod coherent_impl;
 mod joint;
 mod joint_impl;
+mod majority;
 mod quorum_set;
 mod quorum_set_impl;
+mod weighted;
 
 #[cfg(feature = "bench")]
 #[cfg(test)]
@@ -20,4 +22,8 @@ pub(crate) use coherent::Coherent;
 pub(crate) use coherent::FindCoherent;
 pub(crate) use joint::AsJoint;
 pub(crate) use joint::Joint;
-pub(crate) use quorum_set::QuorumSet;
+pub use majority::Majority;
+pub use quorum_set::QuorumSet;
+pub use weighted::EqualWeights;
+pub use weighted::Weighted;
+pub use weighted::Weights;
@@ -4,12 +4,30 @@ use std::sync::Arc;
 ///
 /// A quorum is a collection of nodes that a read or write operation in distributed system has to contact to.
 /// See: http://web.mit.edu/6.033/2005/wwwdocs/quorum_note.html
-pub(crate) trait QuorumSet<ID: 'static> {
+///
+/// The quorum set of every config in a membership is selected by [`RaftTypeConfig::QuorumSet`], e.g., [`Majority`]
+/// or [`Weighted`]. A joint membership config requires a quorum of every config.
+///
+/// [`RaftTypeConfig::QuorumSet`]: crate::RaftTypeConfig::QuorumSet
+/// [`Majority`]: crate::quorum::Majority
+/// [`Weighted`]: crate::quorum::Weighted
+pub trait QuorumSet<ID: 'static> {
     type Iter: Iterator<Item = ID>;
 
     /// Check if a series of ID constitute a quorum that is defined by this quorum set.
+    ///
+    /// This is the quorum to replicate logs to, i.e., to commit a log.
     fn is_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool;
 
+    /// Check if a series of ID constitute a quorum to elect a leader.
+    ///
+    /// By default it is the same as [`QuorumSet::is_quorum`].
+    /// A flexible quorum set may use different quorums for election and replication,
+    /// as long as every election quorum intersects with every replication quorum.
+    fn is_election_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        self.is_quorum(ids)
+    }
+
     /// Returns all ids in this QuorumSet
     fn ids(&self) -> Self::Iter;
 }
@@ -21,6 +39,10 @@ impl<ID: 'static, T: QuorumSet<ID>> QuorumSet<ID> for Arc<T> {
         self.as_ref().is_quorum(ids)
     }
 
+    fn is_election_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        self.as_ref().is_election_quorum(ids)
+    }
+
     fn ids(&self) -> Self::Iter {
         self.as_ref().ids()
     }
@@ -2,7 +2,92 @@ use maplit::btreeset;
 
 use crate::quorum::AsJoint;
 use crate::quorum::Joint;
+use crate::quorum::Majority;
 use crate::quorum::QuorumSet;
+use crate::quorum::Weighted;
+use crate::quorum::Weights;
+
+/// Node 1 has a weight of 2, others have a weight of 1.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DoubleNode1;
+
+impl Weights<u64> for DoubleNode1 {
+    fn weight(id: &u64) -> u64 {
+        if *id == 1 {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// A flexible quorum set: a smaller replication quorum and a larger election quorum.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Flexible;
+
+impl Weights<u64> for Flexible {
+    fn weight(_id: &u64) -> u64 {
+        1
+    }
+
+    fn replication_quorum(total: u64) -> u64 {
+        (total + 1) / 2
+    }
+
+    fn election_quorum(total: u64) -> u64 {
+        total - Self::replication_quorum(total) + 1
+    }
+}
+
+/// All subsets of `ids`.
+fn subsets(ids: &[u64]) -> Vec<Vec<u64>> {
+    (0..1usize << ids.len())
+        .map(|mask| ids.iter().enumerate().filter(|(i, _)| mask & (1 << i) != 0).map(|(_, id)| *id).collect())
+        .collect()
+}
+
+/// Assert that every election quorum of `qs` intersects with every replication quorum of `qs`,
+/// and that a superset of a quorum is also a quorum.
+fn assert_quorum_properties<QS: QuorumSet<u64>>(qs: &QS, ids: &[u64]) {
+    let all = subsets(ids);
+    let election = all.iter().filter(|s| qs.is_election_quorum(s.iter())).collect::<Vec<_>>();
+    let replication = all.iter().filter(|s| qs.is_quorum(s.iter())).collect::<Vec<_>>();
+
+    assert!(!election.is_empty(), "there must be an election quorum");
+    assert!(!replication.is_empty(), "there must be a replication quorum");
+
+    for e in election.iter() {
+        for r in replication.iter() {
+            assert!(
+                e.iter().any(|id| r.contains(id)),
+                "election quorum {:?} and replication quorum {:?} must intersect",
+                e,
+                r
+            );
+        }
+    }
+
+    for s in all.iter() {
+        for sup in all.iter().filter(|sup| s.iter().all(|id| sup.contains(id))) {
+            if qs.is_quorum(s.iter()) {
+                assert!(
+                    qs.is_quorum(sup.iter()),
+                    "{:?} is a quorum but its superset {:?} is not",
+                    s,
+                    sup
+                );
+            }
+            if qs.is_election_quorum(s.iter()) {
+                assert!(
+                    qs.is_election_quorum(sup.iter()),
+                    "{:?} is an election quorum but {:?} is not",
+                    s,
+                    sup
+                );
+            }
+        }
+    }
+}
 
 #[test]
 fn test_simple_quorum_set_impl() -> anyhow::Result<()> {
@@ -33,6 +118,134 @@ fn test_simple_quorum_set_impl() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_majority_quorum_set() -> anyhow::Result<()> {
+    let m12345 = Majority::from(vec![1, 2, 3, 4, 5]);
+
+    assert!(!m12345.is_quorum([0].iter()));
+    assert!(!m12345.is_quorum([0, 1, 2].iter()));
+    assert!(!m12345.is_quorum([6, 7, 8].iter()));
+    assert!(m12345.is_quorum([1, 2, 3].iter()));
+    assert!(m12345.is_quorum([3, 4, 5].iter()));
+    assert!(m12345.is_quorum([1, 3, 4, 5].iter()));
+
+    assert!(!m12345.is_election_quorum([1, 2].iter()));
+    assert!(m12345.is_election_quorum([1, 2, 3].iter()));
+
+    assert_eq!(btreeset! {1,2,3,4,5}, m12345.ids().collect());
+
+    // An empty config has no quorum
+    assert!(!Majority::<u64>::default().is_quorum([1].iter()));
+
+    Ok(())
+}
+
+#[test]
+fn test_weighted_quorum_set() -> anyhow::Result<()> {
+    // Equal weights is majority
+    {
+        let qs = Weighted::<u64>::from(vec![1, 2, 3, 4, 5]);
+
+        assert!(!qs.is_quorum([0, 1, 2].iter()));
+        assert!(qs.is_quorum([1, 2, 3].iter()));
+        assert!(!qs.is_election_quorum([4, 5].iter()));
+        assert!(qs.is_election_quorum([3, 4, 5].iter()));
+    }
+
+    // Node 1 has weight 2: total weight is 5, quorum weight is 3
+    {
+        let qs = Weighted::<u64, DoubleNode1>::from(vec![1, 2, 3, 4]);
+
+        assert!(!qs.is_quorum([1].iter()));
+        assert!(qs.is_quorum([1, 2].iter()));
+        assert!(!qs.is_quorum([2, 3].iter()));
+        assert!(qs.is_quorum([2, 3, 4].iter()));
+
+        // Duplicated ids and ids out of the config do not count
+        assert!(!qs.is_quorum([2, 2, 3, 3].iter()));
+        assert!(!qs.is_quorum([2, 5, 6].iter()));
+    }
+
+    // Flexible: replication quorum 3 and election quorum 4 out of 6
+    {
+        let qs = Weighted::<u64, Flexible>::from(vec![1, 2, 3, 4, 5, 6]);
+
+        assert!(!qs.is_quorum([1, 2].iter()));
+        assert!(qs.is_quorum([1, 2, 3].iter()));
+        assert!(!qs.is_election_quorum([1, 2, 3].iter()));
+        assert!(qs.is_election_quorum([1, 2, 3, 4].iter()));
+    }
+
+    // An empty config has no quorum
+    assert!(!Weighted::<u64>::default().is_quorum([1].iter()));
+    assert!(!Weighted::<u64>::default().is_election_quorum([1].iter()));
+
+    Ok(())
+}
+
+#[test]
+fn test_quorum_set_properties() -> anyhow::Result<()> {
+    for n in 1..=6 {
+        let ids = (1..=n).collect::<Vec<u64>>();
+
+        assert_quorum_properties(&ids, &ids);
+        assert_quorum_properties(&Majority::from(ids.clone()), &ids);
+        assert_quorum_properties(&Weighted::<u64>::from(ids.clone()), &ids);
+        assert_quorum_properties(&Weighted::<u64, DoubleNode1>::from(ids.clone()), &ids);
+        assert_quorum_properties(&Weighted::<u64, Flexible>::from(ids.clone()), &ids);
+    }
+
+    // Joint configs of every built-in quorum set
+    let all = [1, 2, 3, 4, 5, 6];
+    let (c1, c2) = (vec![1, 2, 3, 4], vec![3, 4, 5, 6]);
+
+    let majority = Joint::from(vec![Majority::from(c1.clone()), Majority::from(c2.clone())]);
+    assert_quorum_properties(&majority, &all);
+
+    let weighted = Joint::from(vec![
+        Weighted::<u64, DoubleNode1>::from(c1.clone()),
+        Weighted::from(c2.clone()),
+    ]);
+    assert_quorum_properties(&weighted, &all);
+
+    let flexible = Joint::from(vec![Weighted::<u64, Flexible>::from(c1), Weighted::from(c2)]);
+    assert_quorum_properties(&flexible, &all);
+
+    Ok(())
+}
+
+#[test]
+fn test_joint_weighted_quorum_set() -> anyhow::Result<()> {
+    // A joint quorum has to be a quorum in every config.
+    let qs = Joint::<u64, Weighted<u64, DoubleNode1>, _>::from(vec![
+        Weighted::from(vec![1, 2, 3, 4]),
+        Weighted::from(vec![5, 6, 7]),
+    ]);
+
+    assert!(!qs.is_quorum([1, 2].iter()));
+    assert!(!qs.is_quorum([5, 6].iter()));
+    assert!(qs.is_quorum([1, 2, 5, 6].iter()));
+    assert!(!qs.is_quorum([2, 3, 5, 6].iter()));
+    assert!(qs.is_quorum([2, 3, 4, 6, 7].iter()));
+
+    assert!(qs.is_election_quorum([1, 2, 5, 6].iter()));
+    assert!(!qs.is_election_quorum([1, 2, 5].iter()));
+
+    assert_eq!(btreeset! {1,2,3,4,5,6,7}, qs.ids().collect());
+
+    // A joint of flexible configs uses the election quorum of every config.
+    let qs = Joint::<u64, Weighted<u64, Flexible>, _>::from(vec![
+        Weighted::from(vec![1, 2, 3, 4]),
+        Weighted::from(vec![3, 4, 5, 6]),
+    ]);
+
+    assert!(qs.is_quorum([1, 2, 5, 6].iter()));
+    assert!(!qs.is_election_quorum([1, 2, 5, 6].iter()));
+    assert!(qs.is_election_quorum([1, 2, 3, 4, 5].iter()));
+
+    Ok(())
+}
+
 #[test]
 fn test_joint_quorum_set_impl() -> anyhow::Result<()> {
     // Vec<BTreeSet> as majority quorum set
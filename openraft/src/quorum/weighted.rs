@@ -0,0 +1,140 @@
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::quorum::QuorumSet;
+
+/// Defines the weight of every node and the quorum sizes of a [`Weighted`] quorum set.
+///
+/// It is defined on a type so that it can be selected with [`RaftTypeConfig::QuorumSet`], e.g.:
+/// ```ignore
+/// #[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// struct DoubleNode1;
+///
+/// impl Weights<u64> for DoubleNode1 {
+///     fn weight(id: &u64) -> u64 {
+///         if *id == 1 { 2 } else { 1 }
+///     }
+/// }
+///
+/// openraft::declare_raft_types!(
+///    pub Config: D = Request, R = Response, NodeId = u64, Node = BasicNode,
+///                QuorumSet = Weighted<u64, DoubleNode1>
+/// );
+/// ```
+///
+/// [`RaftTypeConfig::QuorumSet`]: crate::RaftTypeConfig::QuorumSet
+pub trait Weights<ID>: Debug + Clone + Default + PartialEq + Eq + Send + Sync + 'static {
+    /// The weight of the vote of node `id`.
+    fn weight(id: &ID) -> u64;
+
+    /// The least weight of a replication quorum, given the total weight of a config.
+    ///
+    /// By default it is more than half of the total weight.
+    fn replication_quorum(total: u64) -> u64 {
+        total / 2 + 1
+    }
+
+    /// The least weight of an election quorum, given the total weight of a config.
+    ///
+    /// By default it is more than half of the total weight.
+    /// A flexible quorum set may trade a smaller replication quorum for a larger election quorum.
+    /// For safety, `election_quorum(t) + replication_quorum(t)` has to be greater than `t`.
+    fn election_quorum(total: u64) -> u64 {
+        total / 2 + 1
+    }
+}
+
+/// Every node has a weight of 1: a majority quorum set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EqualWeights;
+
+impl<ID> Weights<ID> for EqualWeights {
+    fn weight(_id: &ID) -> u64 {
+        1
+    }
+}
+
+/// A weighted quorum set: a quorum is a set of nodes whose total weight reaches the quorum size.
+///
+/// The weights and the quorum sizes are defined by `W`.
+pub struct Weighted<ID, W = EqualWeights> {
+    ids: Vec<ID>,
+    _p: PhantomData<W>,
+}
+
+impl<ID: Debug, W> Debug for Weighted<ID, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Weighted").field("ids", &self.ids).finish()
+    }
+}
+
+impl<ID: Clone, W> Clone for Weighted<ID, W> {
+    fn clone(&self) -> Self {
+        Self {
+            ids: self.ids.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<ID: PartialEq, W> PartialEq for Weighted<ID, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ids == other.ids
+    }
+}
+
+impl<ID: Eq, W> Eq for Weighted<ID, W> {}
+
+impl<ID, W> Default for Weighted<ID, W> {
+    fn default() -> Self {
+        Self {
+            ids: vec![],
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<ID, W> From<Vec<ID>> for Weighted<ID, W> {
+    fn from(ids: Vec<ID>) -> Self {
+        Self { ids, _p: PhantomData }
+    }
+}
+
+impl<ID, W> Weighted<ID, W>
+where
+    ID: PartialOrd + Ord + Copy + 'static,
+    W: Weights<ID>,
+{
+    fn total_weight(&self) -> u64 {
+        self.ids.iter().map(W::weight).sum()
+    }
+
+    /// Sum the weight of distinct nodes in `ids` that belong to this quorum set.
+    fn weight_of<'a, I: Iterator<Item = &'a ID>>(&self, ids: I) -> u64 {
+        let mut seen = BTreeSet::new();
+        ids.filter(|id| self.ids.contains(id) && seen.insert(**id)).map(W::weight).sum()
+    }
+}
+
+impl<ID, W> QuorumSet<ID> for Weighted<ID, W>
+where
+    ID: PartialOrd + Ord + Copy + 'static,
+    W: Weights<ID>,
+{
+    type Iter = std::collections::btree_set::IntoIter<ID>;
+
+    fn is_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        let total = self.total_weight();
+        total > 0 && self.weight_of(ids) >= W::replication_quorum(total)
+    }
+
+    fn is_election_quorum<'a, I: Iterator<Item = &'a ID> + Clone>(&self, ids: I) -> bool {
+        let total = self.total_weight();
+        total > 0 && self.weight_of(ids) >= W::election_quorum(total)
+    }
+
+    fn ids(&self) -> Self::Iter {
+        BTreeSet::from_iter(self.ids.iter().copied()).into_iter()
+    }
+}
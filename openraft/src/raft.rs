@@ -1,12 +1,20 @@
 //! Public Raft interface and data types.
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::io::SeekFrom;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::RwLock;
 use std::time::Duration;
 
+use anyerror::AnyError;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
@@ -21,10 +29,12 @@ use tracing::Level;
 use crate::config::Config;
 use crate::config::RuntimeConfig;
 use crate::core::replication_lag;
+use crate::core::ApplyResult;
 use crate::core::Expectation;
 use crate::core::RaftCore;
 use crate::core::SnapshotResult;
 use crate::core::SnapshotState;
+use crate::core::StateMachineWorker;
 use crate::core::Tick;
 use crate::core::TickHandle;
 use crate::core::VoteWiseTime;
@@ -34,31 +44,56 @@ use crate::error::AddLearnerError;
 use crate::error::AppendEntriesError;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::ExportSnapshotError;
 use crate::error::Fatal;
+use crate::error::ForceResetMembershipError;
+use crate::error::ImportSnapshotError;
 use crate::error::InitializeError;
 use crate::error::InstallSnapshotError;
+use crate::error::NewRaftError;
+use crate::error::TimeoutNowError;
+use crate::error::TransferLeaderError;
+use crate::error::TransferLeaderTimeout;
+use crate::error::UnchangeableConfig;
+use crate::error::UpdateConfigError;
 use crate::error::VoteError;
+use crate::event::EventReceiver;
+use crate::event::EventSender;
+use crate::event::EventSubscriber;
 use crate::membership::IntoNodes;
+use crate::metrics::LatencyMetrics;
+use crate::metrics::LatencyRecorder;
 use crate::metrics::RaftMetrics;
+use crate::metrics::SnapshotTransferCounter;
 use crate::metrics::Wait;
+use crate::metrics::WaitError;
 use crate::node::Node;
 use crate::progress::entry::ProgressEntry;
+use crate::quorum::QuorumSet;
 use crate::replication::ReplicationSessionId;
 use crate::storage::Snapshot;
 use crate::AppData;
 use crate::AppDataResponse;
+use crate::AppSnapshotMetadata;
 use crate::ChangeMembers;
+use crate::DataSize;
 use crate::Entry;
 use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
 use crate::NodeId;
+use crate::RaftLogStorage;
 use crate::RaftNetworkFactory;
 use crate::RaftState;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
+use crate::SnapshotCompression;
 use crate::SnapshotMeta;
+use crate::StorageError;
 use crate::StorageHelper;
+use crate::StorageIOError;
 use crate::Vote;
 
 /// Configuration of types used by the [`Raft`] core engine.
@@ -77,7 +112,7 @@ use crate::Vote;
 /// ```ignore
 /// openraft::declare_raft_types!(
 ///    /// Declare the type configuration for `MemStore`.
-///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId
+///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = BasicNode
 /// );
 /// ```
 pub trait RaftTypeConfig:
@@ -94,6 +129,37 @@ pub trait RaftTypeConfig:
 
     /// Raft application level node data
     type Node: Node;
+
+    /// The quorum set of a config of voters, built from the voter ids.
+    ///
+    /// It decides which set of nodes constitute a quorum to elect a leader or to commit a log, e.g.,
+    /// [`Majority`](crate::quorum::Majority) or [`Weighted`](crate::quorum::Weighted).
+    /// In a joint membership config, a quorum has to be a quorum of every config.
+    ///
+    /// [`declare_raft_types!`](crate::declare_raft_types) uses `Majority` if it is not declared.
+    type QuorumSet: QuorumSet<Self::NodeId>
+        + From<Vec<Self::NodeId>>
+        + Debug
+        + Clone
+        + Default
+        + Eq
+        + Send
+        + Sync
+        + 'static;
+
+    /// Estimates the size of an application data [`Self::D`], e.g., to bound a batch of client write requests.
+    ///
+    /// [`declare_raft_types!`](crate::declare_raft_types) uses [`StackSize`](crate::StackSize) if it is not
+    /// declared, which does not count the data on the heap.
+    type DataSize: DataSize<Self::D>;
+
+    /// Application-specific metadata of a snapshot, carried by [`SnapshotMeta::metadata`].
+    ///
+    /// It is sent to a follower along with every chunk of a snapshot, so that the follower is able to reject an
+    /// incompatible snapshot before receiving the data. Use `()` if there is no such metadata.
+    ///
+    /// [`declare_raft_types!`](crate::declare_raft_types) uses `()` if it is not declared.
+    type SnapshotMetadata: AppSnapshotMetadata;
 }
 
 /// Define types for a Raft type configuration.
@@ -104,11 +170,15 @@ pub trait RaftTypeConfig:
 ///
 /// This macro does exactly that.
 ///
+/// `QuorumSet` is optional, it is [`Majority`](crate::quorum::Majority) if not declared.
+/// `DataSize` is optional, it is [`StackSize`](crate::StackSize) if not declared.
+/// `SnapshotMetadata` is optional, it is `()` if not declared.
+///
 /// Example:
 /// ```ignore
 /// openraft::declare_raft_types!(
 ///    /// Declare the type configuration for `MemStore`.
-///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId
+///    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = BasicNode
 /// );
 /// ```
 #[macro_export]
@@ -119,11 +189,104 @@ macro_rules! declare_raft_types {
         #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
         $visibility struct $id {}
 
+        $crate::declare_raft_types!(
+            @impl $id,
+            types: {},
+            quorum_set: { type QuorumSet = $crate::quorum::Majority<<$id as $crate::RaftTypeConfig>::NodeId>; },
+            data_size: { type DataSize = $crate::StackSize; },
+            snapshot_metadata: { type SnapshotMetadata = (); },
+            rest: [ $( $(#[$inner])* $type_id = $type, )+ ]
+        );
+    };
+
+    // A declared `QuorumSet` replaces the default one.
+    (
+        @impl $id:ident,
+        types: { $($types:tt)* },
+        quorum_set: $quorum_set:tt,
+        data_size: $data_size:tt,
+        snapshot_metadata: $snapshot_metadata:tt,
+        rest: [ $(#[$inner:meta])* QuorumSet = $type:ty, $($rest:tt)* ]
+    ) => {
+        $crate::declare_raft_types!(
+            @impl $id,
+            types: { $($types)* $(#[$inner])* type QuorumSet = $type; },
+            quorum_set: {},
+            data_size: $data_size,
+            snapshot_metadata: $snapshot_metadata,
+            rest: [ $($rest)* ]
+        );
+    };
+
+    // A declared `DataSize` replaces the default one.
+    (
+        @impl $id:ident,
+        types: { $($types:tt)* },
+        quorum_set: $quorum_set:tt,
+        data_size: $data_size:tt,
+        snapshot_metadata: $snapshot_metadata:tt,
+        rest: [ $(#[$inner:meta])* DataSize = $type:ty, $($rest:tt)* ]
+    ) => {
+        $crate::declare_raft_types!(
+            @impl $id,
+            types: { $($types)* $(#[$inner])* type DataSize = $type; },
+            quorum_set: $quorum_set,
+            data_size: {},
+            snapshot_metadata: $snapshot_metadata,
+            rest: [ $($rest)* ]
+        );
+    };
+
+    // A declared `SnapshotMetadata` replaces the default one.
+    (
+        @impl $id:ident,
+        types: { $($types:tt)* },
+        quorum_set: $quorum_set:tt,
+        data_size: $data_size:tt,
+        snapshot_metadata: $snapshot_metadata:tt,
+        rest: [ $(#[$inner:meta])* SnapshotMetadata = $type:ty, $($rest:tt)* ]
+    ) => {
+        $crate::declare_raft_types!(
+            @impl $id,
+            types: { $($types)* $(#[$inner])* type SnapshotMetadata = $type; },
+            quorum_set: $quorum_set,
+            data_size: $data_size,
+            snapshot_metadata: {},
+            rest: [ $($rest)* ]
+        );
+    };
+
+    (
+        @impl $id:ident,
+        types: { $($types:tt)* },
+        quorum_set: $quorum_set:tt,
+        data_size: $data_size:tt,
+        snapshot_metadata: $snapshot_metadata:tt,
+        rest: [ $(#[$inner:meta])* $type_id:ident = $type:ty, $($rest:tt)* ]
+    ) => {
+        $crate::declare_raft_types!(
+            @impl $id,
+            types: { $($types)* $(#[$inner])* type $type_id = $type; },
+            quorum_set: $quorum_set,
+            data_size: $data_size,
+            snapshot_metadata: $snapshot_metadata,
+            rest: [ $($rest)* ]
+        );
+    };
+
+    (
+        @impl $id:ident,
+        types: { $($types:tt)* },
+        quorum_set: { $($quorum_set:tt)* },
+        data_size: { $($data_size:tt)* },
+        snapshot_metadata: { $($snapshot_metadata:tt)* },
+        rest: []
+    ) => {
         impl $crate::RaftTypeConfig for $id {
-            $(
-                $(#[$inner])*
-                type $type_id = $type;
-            )+
+            $($types)*
+            $($quorum_set)*
+            $($data_size)*
+            $($snapshot_metadata)*
         }
     };
 }
@@ -139,18 +302,33 @@ where NID: NodeId
     Done(Result<(), Fatal<NID>>),
 }
 
-struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+struct RaftInner<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     id: C::NodeId,
-    config: Arc<Config>,
+
+    /// The config in use, replaced by [`Raft::update_config`].
+    config: RwLock<Arc<Config>>,
+
+    /// Serializes [`Raft::update_config`] calls, so that RaftCore, the tick task and `config` are updated as a whole.
+    update_config_lock: Mutex<()>,
+
     runtime_config: Arc<RuntimeConfig>,
     tick_handle: TickHandle,
-    tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+    tx_api: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
     rx_metrics: watch::Receiver<RaftMetrics<C::NodeId, C::Node>>,
+    event_subscriber: EventSubscriber<C::NodeId, C::Node>,
+    latency: Arc<LatencyRecorder<C::NodeId>>,
     // TODO(xp): it does not need to be a async mutex.
     #[allow(clippy::type_complexity)]
     tx_shutdown: Mutex<Option<oneshot::Sender<()>>>,
     marker_n: std::marker::PhantomData<N>,
-    marker_s: std::marker::PhantomData<S>,
+    marker_ls: std::marker::PhantomData<LS>,
+    marker_sm: std::marker::PhantomData<SM>,
     core_state: Mutex<CoreState<C::NodeId>>,
 }
 
@@ -175,11 +353,23 @@ struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>>
 /// is shutting down (potentially for data safety reasons due to a storage error), and the `shutdown`
 /// method should be called on this type to await the shutdown of the node. If the parent
 /// application needs to shutdown the Raft node for any reason, calling `shutdown` will do the trick.
-pub struct Raft<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
-    inner: Arc<RaftInner<C, N, S>>,
+pub struct Raft<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
+    inner: Arc<RaftInner<C, N, LS, SM>>,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N, S> {
+impl<C, N, LS, SM> Raft<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// Create and spawn a new Raft task.
     ///
     /// ### `id`
@@ -195,11 +385,29 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// An implementation of the `RaftNetworkFactory` trait which will be used by Raft for sending RPCs to
     /// peer nodes within the cluster. See the docs on the `RaftNetworkFactory` trait for more details.
     ///
-    /// ### `storage`
-    /// An implementation of the `RaftStorage` trait which will be used by Raft for data storage.
-    /// See the docs on the `RaftStorage` trait for more details.
-    #[tracing::instrument(level="debug", skip(config, network, storage), fields(cluster=%config.cluster_name))]
-    pub async fn new(id: C::NodeId, config: Arc<Config>, network: N, mut storage: S) -> Result<Self, Fatal<C::NodeId>> {
+    /// ### `log_store`
+    /// An implementation of the `RaftLogStorage` trait which will be used by Raft for storing vote and logs.
+    /// See the docs on the `RaftLogStorage` trait for more details.
+    ///
+    /// ### `state_machine`
+    /// An implementation of the `RaftStateMachine` trait which will be used by Raft for applying logs and building
+    /// snapshots. See the docs on the `RaftStateMachine` trait for more details.
+    ///
+    /// An existing `RaftStorage` implementation can be used with [`Adaptor`](crate::storage::Adaptor), which
+    /// splits it into a log store and a state machine.
+    ///
+    /// It returns [`NewRaftError::InvalidConfig`] if `config` can not be used with the types of this node, e.g., a
+    /// [`SnapshotPolicy::custom()`](crate::SnapshotPolicy::custom) built for another `NodeId` or `Node`.
+    #[tracing::instrument(level="debug", skip(config, network, log_store, state_machine), fields(cluster=%config.cluster_name))]
+    pub async fn new(
+        id: C::NodeId,
+        config: Arc<Config>,
+        network: N,
+        mut log_store: LS,
+        mut state_machine: SM,
+    ) -> Result<Self, NewRaftError<C::NodeId>> {
+        config.snapshot_policy.check_types::<C::NodeId, C::Node>()?;
+
         let (tx_api, rx_api) = mpsc::unbounded_channel();
         let (tx_metrics, rx_metrics) = watch::channel(RaftMetrics::new_initial(id));
         let (tx_shutdown, rx_shutdown) = oneshot::channel();
@@ -221,39 +429,52 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         );
 
         let state = {
-            let mut helper = StorageHelper::new(&mut storage);
+            let mut helper = StorageHelper::new(&mut log_store, &mut state_machine);
             helper.get_initial_state().await?
         };
 
         // TODO(xp): this is not necessary.
-        storage.save_vote(&state.vote).await?;
+        log_store.save_vote(&state.vote).await?;
 
-        let engine = Engine::new(state, EngineConfig {
-            id,
-            max_in_snapshot_log_to_keep: config.max_in_snapshot_log_to_keep,
-            purge_batch_size: config.purge_batch_size,
-            max_payload_entries: config.max_payload_entries,
-        });
+        let last_applied = state.committed;
+        let log_reader = log_store.get_log_reader().await;
+        let sm_handle = StateMachineWorker::spawn(state_machine, log_reader, tx_api.clone());
+
+        let latency = Arc::new(LatencyRecorder::default());
+        let (events, event_subscriber) = EventSender::new(config.event_buffer_size as usize, state.vote.term);
+
+        let engine = Engine::new(state, EngineConfig::new(id, &config));
 
         let core = RaftCore {
             id,
             config: config.clone(),
             runtime_config: runtime_config.clone(),
             network,
-            storage,
+            log_store,
+            sm_handle,
+            last_applied,
 
             engine,
             leader_data: None,
+            client_resp_channels: BTreeMap::new(),
+            client_batch_resp_channels: BTreeMap::new(),
 
             snapshot_state: SnapshotState::None,
             received_snapshot: BTreeMap::new(),
+            log_bytes_since_snapshot: 0,
+            last_snapshot_at: Instant::now(),
 
             next_election_time: VoteWiseTime::new(Vote::default(), Instant::now() + Duration::from_secs(86400)),
+            leader_lease_granted_until: None,
 
             tx_api: tx_api.clone(),
             rx_api,
 
             tx_metrics,
+            snapshots_built: 0,
+            snapshot_transfer: Arc::new(SnapshotTransferCounter::default()),
+            latency: latency.clone(),
+            events,
 
             span: core_span,
         };
@@ -262,14 +483,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         let inner = RaftInner {
             id,
-            config,
+            config: RwLock::new(config),
             runtime_config,
             tick_handle,
             tx_api,
             rx_metrics,
+            event_subscriber,
+            latency,
+            update_config_lock: Mutex::new(()),
             tx_shutdown: Mutex::new(Some(tx_shutdown)),
             marker_n: std::marker::PhantomData,
-            marker_s: std::marker::PhantomData,
+            marker_ls: std::marker::PhantomData,
+            marker_sm: std::marker::PhantomData,
             core_state: Mutex::new(CoreState::Running(core_handle)),
         };
 
@@ -292,6 +517,97 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.inner.runtime_config.enable_elect.store(enabled, Ordering::Relaxed);
     }
 
+    /// Return the config this node is running with.
+    pub fn config(&self) -> Arc<Config> {
+        self.inner.config.read().unwrap().clone()
+    }
+
+    /// Subscribe to the state transition events of this node.
+    ///
+    /// The returned receiver receives every [`RaftEvent`](`crate::RaftEvent`) that happens after this call, in the
+    /// order they happen, e.g., becoming or losing leadership, a term change, a membership change, a snapshot being
+    /// built or installed, or logs being purged. Unlike [`Raft::metrics`], a transient state is not skipped.
+    ///
+    /// Events are buffered up to [`Config::event_buffer_size`]. A receiver that falls too far behind gets a
+    /// [`RecvEventError::Lagged`](`crate::error::RecvEventError::Lagged`) telling how many events it missed.
+    /// After RaftCore quits, the receiver gets the remaining buffered events and then
+    /// [`RecvEventError::Closed`](`crate::error::RecvEventError::Closed`).
+    pub fn subscribe(&self) -> EventReceiver<C::NodeId, C::Node> {
+        self.inner.event_subscriber.subscribe()
+    }
+
+    /// Replace the config of this running node, without restarting it.
+    ///
+    /// The new config is validated with [`Config::validate`] and then takes effect at once in the RaftCore, the tick
+    /// task and every running replication stream, e.g., timeouts, the heartbeat interval, `max_payload_entries` or
+    /// the snapshot policy. A timer that is already started, such as the next election timeout, is not reset.
+    ///
+    /// - `enable_tick`, `enable_heartbeat` and `enable_elect` override the values set by [`Raft::enable_tick`],
+    ///   [`Raft::enable_heartbeat`] and [`Raft::enable_elect`].
+    /// - `cluster_name`, `max_inflight_append_requests` and `event_buffer_size` can not be changed, an
+    ///   [`UnchangeableConfig`] error is returned.
+    ///
+    /// Concurrent calls are serialized: the config returned by [`Raft::config`] is the one in use by RaftCore.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn update_config(&self, config: Config) -> Result<(), UpdateConfigError<C::NodeId>> {
+        let config = config.validate()?;
+        config.snapshot_policy.check_types::<C::NodeId, C::Node>()?;
+
+        let _guard = self.inner.update_config_lock.lock().await;
+
+        let current = self.config();
+
+        let unchangeable = [
+            (
+                "cluster_name",
+                current.cluster_name.clone(),
+                config.cluster_name.clone(),
+            ),
+            (
+                "max_inflight_append_requests",
+                current.max_inflight_append_requests.to_string(),
+                config.max_inflight_append_requests.to_string(),
+            ),
+            (
+                "event_buffer_size",
+                current.event_buffer_size.to_string(),
+                config.event_buffer_size.to_string(),
+            ),
+        ];
+
+        for (name, current, new) in unchangeable {
+            if current != new {
+                return Err(UnchangeableConfig {
+                    name: name.to_string(),
+                    current,
+                    new,
+                }
+                .into());
+            }
+        }
+
+        let config = Arc::new(config);
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(
+            RaftMsg::UpdateConfig {
+                config: config.clone(),
+                tx,
+            },
+            rx,
+        )
+        .await?;
+
+        self.inner.tick_handle.set_interval(Duration::from_millis(config.heartbeat_interval * 3 / 2));
+        self.inner.tick_handle.enable(config.enable_tick);
+        self.enable_heartbeat(config.enable_heartbeat);
+        self.enable_elect(config.enable_elect);
+
+        *self.inner.config.write().unwrap() = config;
+
+        Ok(())
+    }
+
     /// Trigger election at once and return at once.
     ///
     /// Returns error when RaftCore has Fatal error, e.g. shut down or having storage error.
@@ -300,6 +616,32 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.send_external_command(ExternalCommand::Elect, "trigger_elect").await
     }
 
+    /// Transfer the leadership of this node to another voter `to`.
+    ///
+    /// The leader stops accepting client writes, replicates all of its logs to `to`, and then sends a TimeoutNow RPC
+    /// to let `to` start an election at once.
+    ///
+    /// It returns when `to` becomes the leader seen by this node, or a [`TransferLeaderTimeout`] error if it does not
+    /// happen within `election_timeout_max`. After the timeout this node accepts client writes again, if it is still
+    /// the leader.
+    ///
+    /// With leader lease enabled, the election of `to` may be delayed until the lease granted by other nodes to this
+    /// leader expires.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn transfer_leader(&self, to: C::NodeId) -> Result<(), TransferLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TransferLeader { to, tx }, rx).await?;
+
+        let timeout = Duration::from_millis(self.config().election_timeout_max);
+        let res = self.wait(Some(timeout)).current_leader(to, "transfer_leader").await;
+
+        match res {
+            Ok(_) => Ok(()),
+            Err(WaitError::Timeout(_, _)) => Err(TransferLeaderTimeout { target: to, timeout }.into()),
+            Err(WaitError::ShuttingDown) => Err(Fatal::Stopped.into()),
+        }
+    }
+
     /// Trigger a heartbeat at once and return at once.
     ///
     /// Returns error when RaftCore has Fatal error, e.g. shut down or having storage error.
@@ -355,6 +697,33 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.call_core(RaftMsg::RequestVote { rpc, tx }, rx).await
     }
 
+    /// Submit a PreVote RPC to this Raft node.
+    ///
+    /// These RPCs are sent by cluster peers which are about to start an election, to check if a quorum would grant
+    /// their vote. Handling a PreVote request never changes the state of this node.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn pre_vote(&self, rpc: VoteRequest<C::NodeId>) -> Result<VoteResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::pre_vote()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::RequestPreVote { rpc, tx }, rx).await
+    }
+
+    /// Submit a TimeoutNow RPC to this Raft node.
+    ///
+    /// These RPCs are sent by a leader that is transferring its leadership to this node.
+    /// If this node is a voter and its log is as up-to-date as the leader's, it starts an election at once.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn timeout_now(
+        &self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, TimeoutNowError<C::NodeId>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::timeout_now()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TimeoutNow { rpc, tx }, rx).await
+    }
+
     /// Submit an InstallSnapshot RPC to this Raft node.
     ///
     /// These RPCs are sent by the cluster leader in order to bring a new node or a slow node up-to-speed
@@ -390,6 +759,23 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await
     }
 
+    /// Confirm this node is still the leader and return the log id a linearizable read has to wait for.
+    ///
+    /// The returned log id is the greater one of the `committed` log id at the time this request is received and the
+    /// first log id proposed by this leader.
+    /// Before serving a read, the application has to wait until `last_applied` reaches it, e.g.:
+    ///
+    /// ```ignore
+    /// let read_log_id = raft.read_index().await?;
+    /// raft.wait(None).applied_index_at_least(read_log_id.index(), "read").await?;
+    /// // read from the state machine
+    /// ```
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn read_index(&self) -> Result<Option<LogId<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ReadIndex { tx }, rx).await
+    }
+
     /// Submit a mutating client request to Raft to update the state of the system (§5.1).
     ///
     /// It will be appended to the log, committed to the cluster, and then applied to the
@@ -423,6 +809,30 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         .await
     }
 
+    /// Submit a batch of mutating client requests to Raft to update the state of the system (§5.1).
+    ///
+    /// All of the requests are appended atomically to the log as consecutive entries, in the given order.
+    /// It returns the responses, in the same order, after all of the entries are applied to the state machine.
+    ///
+    /// It fails as a whole, e.g., with `ForwardToLeader` if this node is not the leader, or if the leadership is
+    /// lost before all of the entries are applied. In the latter case some of the entries may still be committed,
+    /// thus as with [`Raft::client_write`], the application should be able to deal with duplicated requests.
+    #[tracing::instrument(level = "debug", skip(self, app_data), fields(n = app_data.len()))]
+    pub async fn client_write_many(
+        &self,
+        app_data: Vec<C::D>,
+    ) -> Result<Vec<ClientWriteResponse<C>>, ClientWriteError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(
+            RaftMsg::ClientWriteManyRequest {
+                payloads: app_data.into_iter().map(EntryPayload::Normal).collect(),
+                tx,
+            },
+            rx,
+        )
+        .await
+    }
+
     /// Initialize a pristine Raft node with the given config.
     ///
     /// This command should be called on pristine nodes — where the log index is 0 and the node is
@@ -457,6 +867,90 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         .await
     }
 
+    /// Get the current snapshot of this node, e.g., to take a backup of it.
+    ///
+    /// It returns `None` if no snapshot has been built or installed yet.
+    /// Call [`Raft::trigger_snapshot`] and wait for it to be built, to export the latest state.
+    ///
+    /// The returned [`Snapshot`] contains the metadata and a reader of the snapshot data.
+    /// They can be imported to a pristine node with [`Raft::import_snapshot`].
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn export_snapshot(
+        &self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ExportSnapshot { tx }, rx).await
+    }
+
+    /// Write the data of the current snapshot of this node to `w`, e.g., a file, and return the metadata of it.
+    ///
+    /// It returns `None` and writes nothing if there is no snapshot. See [`Raft::export_snapshot`].
+    ///
+    /// The returned metadata has to be stored along with the data: it is required by [`Raft::import_snapshot`].
+    #[tracing::instrument(level = "debug", skip(self, w))]
+    pub async fn export_snapshot_to<W>(
+        &self,
+        w: &mut W,
+    ) -> Result<Option<SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>>, ExportSnapshotError<C::NodeId>>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        let snapshot = match self.export_snapshot().await? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        let Snapshot { meta, mut snapshot } = snapshot;
+
+        let io_err = |verb: ErrorVerb| {
+            let subject = ErrorSubject::Snapshot(meta.signature());
+            move |e: std::io::Error| StorageIOError::new(subject, verb, AnyError::new(&e))
+        };
+
+        snapshot.seek(SeekFrom::Start(0)).await.map_err(io_err(ErrorVerb::Read))?;
+        tokio::io::copy(&mut snapshot, w).await.map_err(io_err(ErrorVerb::Write))?;
+        w.flush().await.map_err(io_err(ErrorVerb::Write))?;
+
+        Ok(Some(meta))
+    }
+
+    /// Install a snapshot on a pristine node, e.g., to restore a cluster from a backup.
+    ///
+    /// `meta` and the data read from `snapshot` are usually exported by [`Raft::export_snapshot`].
+    /// The snapshot is checked with [`RaftStateMachine::check_snapshot_meta`] and is installed in the same way as one
+    /// received from a leader.
+    ///
+    /// It has to be called on a pristine node, i.e., before [`Raft::initialize`], otherwise
+    /// `ImportSnapshotError::NotAllowed` is returned.
+    /// After importing, the node uses the membership in the snapshot and does not need to be initialized:
+    /// it elects a leader with the other voters in this membership, which have to import the same snapshot.
+    #[tracing::instrument(level = "debug", skip(self, snapshot))]
+    pub async fn import_snapshot<R>(
+        &self,
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        mut snapshot: R,
+    ) -> Result<(), ImportSnapshotError<C::NodeId>>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let (tx, rx) = oneshot::channel();
+        let mut data = self.call_core(RaftMsg::BeginImportSnapshot { meta: meta.clone(), tx }, rx).await?;
+
+        let io_err = |e: std::io::Error| {
+            StorageIOError::new(
+                ErrorSubject::Snapshot(meta.signature()),
+                ErrorVerb::Write,
+                AnyError::new(&e),
+            )
+        };
+
+        tokio::io::copy(&mut snapshot, &mut *data).await.map_err(io_err)?;
+        data.shutdown().await.map_err(io_err)?;
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ImportSnapshot { meta, data, tx }, rx).await
+    }
+
     /// Add a new learner raft node, optionally, blocking until up-to-speed.
     ///
     /// - Add a node as learner into the cluster.
@@ -564,7 +1058,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         let distance = replication_lag(&Some(matched.index), &metrics.last_log_index);
 
-        if distance <= self.inner.config.replication_lag_threshold {
+        if distance <= self.config().replication_lag_threshold {
             // replication became up to date.
             return Ok(Some(matched));
         }
@@ -576,6 +1070,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// Propose a cluster configuration change.
     ///
     /// A node in the proposed config has to be a learner, otherwise it fails with LearnerNotFound error.
+    /// A node added with `ChangeMembers::AddWitness` must not be a voter yet, otherwise it fails with AlreadyVoter
+    /// error.
     ///
     /// Internally:
     /// - It proposes a **joint** config.
@@ -666,9 +1162,44 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         Ok(res)
     }
 
+    /// **Unsafe**: force this node to become the leader of a new membership that consists of `voters`, without the
+    /// consent of a quorum of the current membership.
+    ///
+    /// It is meant only to recover a cluster that has permanently lost a quorum of voters, in which case
+    /// [`Raft::change_membership`] can never succeed. It should be called on only one of the surviving voters, while
+    /// the lost voters must never come back with their old data.
+    ///
+    /// This node votes for itself with a term greater than any voter in `voters`, appends a uniform membership log of
+    /// `voters`, and becomes the leader of it at once. The log is committed when a quorum of `voters` accepts it.
+    /// It returns the log id of the membership log.
+    ///
+    /// Guard rails, a [`ForceResetMembershipError`] is returned if:
+    /// - `voters` is empty, or a node in it is not a node in the current membership;
+    /// - this node is not a voter of both the current membership and `voters`, or it is a witness;
+    /// - there is still a live leader: this node is a leader acknowledged by a quorum, or it has granted a leader
+    ///   lease, or it has heard from the leader within the election timeout, whether or not the leader lease is
+    ///   enabled;
+    /// - any other node in `voters` is unreachable, or it has more logs than this node: the logs of this node have to
+    ///   be the most up-to-date ones among the survivors;
+    /// - any other node in `voters` rejects the PreVote request sent by this node, e.g., it is a leader or it has heard
+    ///   from a live leader.
+    ///
+    /// Learners in the current membership are kept as learners.
+    /// Logs that are not in this node, even if committed, are lost.
+    #[tracing::instrument(level = "info", skip(self))]
+    pub async fn force_reset_membership(
+        &self,
+        voters: BTreeSet<C::NodeId>,
+    ) -> Result<LogId<C::NodeId>, ForceResetMembershipError<C::NodeId, C::Node>> {
+        tracing::warn!(voters = debug(&voters), "force reset membership");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ForceResetMembership { voters, tx }, rx).await
+    }
+
     /// Invoke RaftCore by sending a RaftMsg and blocks waiting for response.
     #[tracing::instrument(level = "debug", skip(self, mes, rx))]
-    pub(crate) async fn call_core<T, E>(&self, mes: RaftMsg<C, N, S>, rx: RaftRespRx<T, E>) -> Result<T, E>
+    pub(crate) async fn call_core<T, E>(&self, mes: RaftMsg<C, N, LS, SM>, rx: RaftRespRx<T, E>) -> Result<T, E>
     where E: From<Fatal<C::NodeId>> + Debug {
         let sum = if tracing::enabled!(Level::DEBUG) {
             None
@@ -758,7 +1289,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
     /// Send a request to the Raft core loop in a fire-and-forget manner.
     ///
-    /// The request functor will be called with a mutable reference to both the state machine
+    /// The request functor will be called with a mutable reference to both the log store
     /// and the network factory and serialized with other Raft core loop processing (e.g., client
     /// requests or general state changes). The current state of the system is passed as well.
     ///
@@ -768,7 +1299,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     ///
     /// If the API channel is already closed (Raft is in shutdown), then the request functor is
     /// destroyed right away and not called at all.
-    pub fn external_request<F: FnOnce(&RaftState<C::NodeId, C::Node>, &mut S, &mut N) + Send + 'static>(&self, req: F) {
+    pub fn external_request<F: FnOnce(&RaftState<C::NodeId, C::Node>, &mut LS, &mut N) + Send + 'static>(
+        &self,
+        req: F,
+    ) {
         let _ignore_error = self.inner.tx_api.send(RaftMsg::ExternalRequest { req: Box::new(req) });
     }
 
@@ -777,6 +1311,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.inner.rx_metrics.clone()
     }
 
+    /// Get the latencies recorded by this node since it started.
+    ///
+    /// The latencies of proposing, appending, committing and applying logs are recorded when this node is the
+    /// leader, and so are the AppendEntries RPCs to every target. See [`LatencyMetrics`].
+    ///
+    /// ```ignore
+    /// let latency = raft.latency_metrics();
+    /// println!("commit: {}", latency.commit);
+    /// println!("p99 of apply: {:?}", latency.apply.percentile(99.0));
+    /// ```
+    pub fn latency_metrics(&self) -> LatencyMetrics<C::NodeId> {
+        self.inner.latency.metrics()
+    }
+
     /// Get a handle to wait for the metrics to satisfy some condition.
     ///
     /// ```ignore
@@ -821,7 +1369,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Clone for Raft<C, N, S> {
+impl<C, N, LS, SM> Clone for Raft<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
@@ -857,8 +1411,17 @@ pub(crate) type AppendEntriesTx<NID> = RaftRespTx<AppendEntriesResponse<NID>, Ap
 /// TX for Client Write Response
 pub(crate) type ClientWriteTx<C, NID, N> = RaftRespTx<ClientWriteResponse<C>, ClientWriteError<NID, N>>;
 
+/// TX for the Response of writing a batch of client requests
+pub(crate) type ClientWriteManyTx<C, NID, N> = RaftRespTx<Vec<ClientWriteResponse<C>>, ClientWriteError<NID, N>>;
+
 /// A message coming from the Raft API.
-pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+pub(crate) enum RaftMsg<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     AppendEntries {
         rpc: AppendEntriesRequest<C>,
         tx: AppendEntriesTx<C::NodeId>,
@@ -874,6 +1437,21 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         /// Which ServerState sent this message. It is also the requested vote.
         vote: Vote<C::NodeId>,
     },
+    RequestPreVote {
+        rpc: VoteRequest<C::NodeId>,
+        tx: VoteTx<C::NodeId>,
+    },
+    PreVoteResponse {
+        target: C::NodeId,
+        resp: VoteResponse<C::NodeId>,
+
+        /// The vote of this node when the pre-vote request is sent.
+        vote: Vote<C::NodeId>,
+    },
+    TimeoutNow {
+        rpc: TimeoutNowRequest<C::NodeId>,
+        tx: RaftRespTx<TimeoutNowResponse<C::NodeId>, TimeoutNowError<C::NodeId>>,
+    },
     InstallSnapshot {
         rpc: InstallSnapshotRequest<C>,
         tx: InstallSnapshotTx<C::NodeId>,
@@ -885,18 +1463,74 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         result: SnapshotResult<C::NodeId, C::Node>,
     },
 
+    /// The result of applying committed logs to the state machine, sent by the state machine worker.
+    ApplyResult {
+        result: Result<ApplyResult<C>, StorageError<C::NodeId>>,
+    },
+
+    /// Logs appended by the leader are flushed to disk, sent by the callback passed to
+    /// [`RaftLogStorage::append`].
+    LogFlushed {
+        /// The vote of the leader when the logs are appended.
+        vote: Vote<C::NodeId>,
+
+        /// The last flushed log id, or an error if the logs failed to flush.
+        result: Result<Option<LogId<C::NodeId>>, StorageError<C::NodeId>>,
+    },
+
     ClientWriteRequest {
         payload: EntryPayload<C>,
         tx: ClientWriteTx<C, C::NodeId, C::Node>,
     },
+    ClientWriteManyRequest {
+        payloads: Vec<EntryPayload<C>>,
+        tx: ClientWriteManyTx<C, C::NodeId, C::Node>,
+    },
     CheckIsLeaderRequest {
         tx: RaftRespTx<(), CheckIsLeaderError<C::NodeId, C::Node>>,
     },
 
+    ReadIndex {
+        tx: RaftRespTx<Option<LogId<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>>,
+    },
+
+    /// Transfer leadership to another voter `to`.
+    ///
+    /// `tx` is replied once the transfer is started.
+    TransferLeader {
+        to: C::NodeId,
+        tx: RaftRespTx<(), TransferLeaderError<C::NodeId, C::Node>>,
+    },
+
+    /// Replace the config in use by RaftCore and replication streams.
+    UpdateConfig {
+        config: Arc<Config>,
+        tx: RaftRespTx<(), Fatal<C::NodeId>>,
+    },
+
     Initialize {
         members: BTreeMap<C::NodeId, C::Node>,
         tx: RaftRespTx<(), InitializeError<C::NodeId, C::Node>>,
     },
+
+    /// Get the current snapshot.
+    ExportSnapshot {
+        tx: RaftRespTx<Option<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>>, Fatal<C::NodeId>>,
+    },
+
+    /// Check if a snapshot can be imported and return a writable handle for the application to write the data into.
+    BeginImportSnapshot {
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        tx: RaftRespTx<Box<SM::SnapshotData>, ImportSnapshotError<C::NodeId>>,
+    },
+
+    /// Install a snapshot whose data is written by the application.
+    ImportSnapshot {
+        meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        data: Box<SM::SnapshotData>,
+        tx: RaftRespTx<(), ImportSnapshotError<C::NodeId>>,
+    },
+
     /// Request raft core to setup a new replication to a learner.
     AddLearner {
         id: C::NodeId,
@@ -920,9 +1554,22 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>,
     },
 
+    /// Check the other voters in `voters` and then force this node to become the leader of the new membership.
+    ForceResetMembership {
+        voters: BTreeSet<C::NodeId>,
+        tx: RaftRespTx<LogId<C::NodeId>, ForceResetMembershipError<C::NodeId, C::Node>>,
+    },
+
+    /// The responses of the other voters to a `ForceResetMembership` request.
+    ForceResetMembershipChecked {
+        voters: BTreeSet<C::NodeId>,
+        responses: BTreeMap<C::NodeId, VoteResponse<C::NodeId>>,
+        tx: RaftRespTx<LogId<C::NodeId>, ForceResetMembershipError<C::NodeId, C::Node>>,
+    },
+
     ExternalRequest {
         #[allow(clippy::type_complexity)]
-        req: Box<dyn FnOnce(&RaftState<C::NodeId, C::Node>, &mut S, &mut N) + Send + 'static>,
+        req: Box<dyn FnOnce(&RaftState<C::NodeId, C::Node>, &mut LS, &mut N) + Send + 'static>,
     },
 
     ExternalCommand {
@@ -950,6 +1597,15 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         session_id: ReplicationSessionId<C::NodeId>,
     },
 
+    /// The status of a replication target has changed, RaftCore copies it into the replication metrics.
+    /// Sent by a replication task `ReplicationCore`.
+    UpdateReplicationStatus {
+        target: C::NodeId,
+
+        /// In which session this message is sent.
+        session_id: ReplicationSessionId<C::NodeId>,
+    },
+
     /// ReplicationCore has seen a higher `vote`.
     /// Sent by a replication task `ReplicationCore`.
     HigherVote {
@@ -972,7 +1628,7 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         target: C::NodeId,
 
         /// The response channel for delivering the snapshot data.
-        tx: oneshot::Sender<Snapshot<C::NodeId, C::Node, S::SnapshotData>>,
+        tx: oneshot::Sender<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>>,
 
         /// Which replication session sent this message
         session_id: ReplicationSessionId<C::NodeId>,
@@ -983,11 +1639,12 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
     ReplicationFatal,
 }
 
-impl<C, N, S> MessageSummary<RaftMsg<C, N, S>> for RaftMsg<C, N, S>
+impl<C, N, LS, SM> MessageSummary<RaftMsg<C, N, LS, SM>> for RaftMsg<C, N, LS, SM>
 where
     C: RaftTypeConfig,
     N: RaftNetworkFactory<C>,
-    S: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
     fn summary(&self) -> String {
         match self {
@@ -1000,19 +1657,58 @@ where
             RaftMsg::VoteResponse { target, resp, vote } => {
                 format!("VoteResponse: from: {}: {}, res-vote: {}", target, resp.summary(), vote)
             }
+            RaftMsg::RequestPreVote { rpc, .. } => {
+                format!("RequestPreVote: {}", rpc.summary())
+            }
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                format!(
+                    "PreVoteResponse: from: {}: {}, res-vote: {}",
+                    target,
+                    resp.summary(),
+                    vote
+                )
+            }
+            RaftMsg::TimeoutNow { rpc, .. } => {
+                format!("TimeoutNow: {}", rpc.summary())
+            }
             RaftMsg::InstallSnapshot { rpc, .. } => {
                 format!("InstallSnapshot: {}", rpc.summary())
             }
             RaftMsg::BuildingSnapshotResult { result: update } => {
                 format!("BuildingSnapshotResult: {:?}", update)
             }
+            RaftMsg::ApplyResult { result } => match result {
+                Ok(res) => format!("ApplyResult: {}", res.summary()),
+                Err(err) => format!("ApplyResult: {}", err),
+            },
+            RaftMsg::LogFlushed { vote, result } => match result {
+                Ok(log_id) => format!("LogFlushed: vote: {}, log_id: {:?}", vote, log_id),
+                Err(err) => format!("LogFlushed: vote: {}, {}", vote, err),
+            },
             RaftMsg::ClientWriteRequest { payload: rpc, .. } => {
                 format!("ClientWriteRequest: {}", rpc.summary())
             }
+            RaftMsg::ClientWriteManyRequest { payloads, .. } => {
+                format!("ClientWriteManyRequest: n: {}", payloads.len())
+            }
             RaftMsg::CheckIsLeaderRequest { .. } => "CheckIsLeaderRequest".to_string(),
+            RaftMsg::ReadIndex { .. } => "ReadIndex".to_string(),
+            RaftMsg::TransferLeader { to, .. } => {
+                format!("TransferLeader: to: {}", to)
+            }
+            RaftMsg::UpdateConfig { config, .. } => {
+                format!("UpdateConfig: {:?}", config)
+            }
             RaftMsg::Initialize { members, .. } => {
                 format!("Initialize: {:?}", members)
             }
+            RaftMsg::ExportSnapshot { .. } => "ExportSnapshot".to_string(),
+            RaftMsg::BeginImportSnapshot { meta, .. } => {
+                format!("BeginImportSnapshot: {}", meta.summary())
+            }
+            RaftMsg::ImportSnapshot { meta, .. } => {
+                format!("ImportSnapshot: {}", meta.summary())
+            }
             RaftMsg::AddLearner { id, node, .. } => {
                 format!("AddLearner: id: {}, node: {:?}", id, node)
             }
@@ -1027,6 +1723,15 @@ where
                     members, when, turn_to_learner,
                 )
             }
+            RaftMsg::ForceResetMembership { voters, .. } => {
+                format!("ForceResetMembership: voters: {:?}", voters)
+            }
+            RaftMsg::ForceResetMembershipChecked { voters, responses, .. } => {
+                format!(
+                    "ForceResetMembershipChecked: voters: {:?}, responses: {:?}",
+                    voters, responses
+                )
+            }
             RaftMsg::ExternalRequest { .. } => "External Request".to_string(),
             RaftMsg::ExternalCommand { cmd } => {
                 format!("ExternalCommand: {:?}", cmd)
@@ -1047,6 +1752,17 @@ where
                     session_id.membership_log_id.summary()
                 )
             }
+            RaftMsg::UpdateReplicationStatus {
+                ref target,
+                ref session_id,
+            } => {
+                format!(
+                    "UpdateReplicationStatus: target: {}, server_state_vote: {}, membership_log_id: {}",
+                    target,
+                    session_id.vote,
+                    session_id.membership_log_id.summary()
+                )
+            }
             RaftMsg::HigherVote {
                 ref target,
                 higher: ref new_vote,
@@ -1169,17 +1885,34 @@ impl<NID: NodeId> MessageSummary<AppendEntriesResponse<NID>> for AppendEntriesRe
 pub struct VoteRequest<NID: NodeId> {
     pub vote: Vote<NID>,
     pub last_log_id: Option<LogId<NID>>,
+
+    /// The vote of the leader that asked the candidate to elect with a `TimeoutNow` request, if any.
+    ///
+    /// A voter grants such a vote even if the leader lease it granted has not expired,
+    /// only if the lease is granted to this leader and the candidate elects in the term right after it,
+    /// because the leader that holds the lease is the one handing over its leadership.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub leader_transfer: Option<Vote<NID>>,
 }
 
 impl<NID: NodeId> MessageSummary<VoteRequest<NID>> for VoteRequest<NID> {
     fn summary(&self) -> String {
-        format!("{}, last_log:{:?}", self.vote, self.last_log_id.map(|x| x.to_string()))
+        format!(
+            "{}, last_log:{:?}, leader_transfer:{:?}",
+            self.vote,
+            self.last_log_id.map(|x| x.to_string()),
+            self.leader_transfer.map(|x| x.to_string())
+        )
     }
 }
 
 impl<NID: NodeId> VoteRequest<NID> {
     pub fn new(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
-        Self { vote, last_log_id }
+        Self {
+            vote,
+            last_log_id,
+            leader_transfer: None,
+        }
     }
 }
 
@@ -1209,20 +1942,74 @@ impl<NID: NodeId> MessageSummary<VoteResponse<NID>> for VoteResponse<NID> {
     }
 }
 
+/// An RPC sent by a leader to let the target start an election at once, when transferring leadership to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowRequest<NID: NodeId> {
+    /// The vote of the leader.
+    pub vote: Vote<NID>,
+
+    /// The last log id on the leader. The target starts an election only if its log is as up-to-date as it.
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowRequest<NID>> for TimeoutNowRequest<NID> {
+    fn summary(&self) -> String {
+        format!("{}, last_log:{:?}", self.vote, self.last_log_id.map(|x| x.to_string()))
+    }
+}
+
+/// The response to a `TimeoutNowRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowResponse<NID: NodeId> {
+    /// The vote of the target after handling the request.
+    ///
+    /// If an election is started, it is the vote the target is campaigning with.
+    pub vote: Vote<NID>,
+
+    /// Whether the target has started an election.
+    pub elected: bool,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowResponse<NID>> for TimeoutNowResponse<NID> {
+    fn summary(&self) -> String {
+        format!("{{elected:{}, {}}}", self.elected, self.vote)
+    }
+}
+
 /// An RPC sent by the Raft leader to send chunks of a snapshot to a follower (§7).
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct InstallSnapshotRequest<C: RaftTypeConfig> {
     pub vote: Vote<C::NodeId>,
 
-    /// Metadata of a snapshot: snapshot_id, last_log_ed membership etc.
-    pub meta: SnapshotMeta<C::NodeId, C::Node>,
+    /// Metadata of a snapshot: snapshot_id, last_log_ed membership and the application-specific metadata etc.
+    pub meta: SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
 
     /// The byte offset where this chunk of data is positioned in the snapshot file.
     pub offset: u64,
-    /// The raw bytes of the snapshot chunk, starting at `offset`.
+    /// The bytes of the snapshot chunk, starting at `offset`, encoded with `compression`.
     pub data: Vec<u8>,
 
+    /// How `data` is compressed.
+    ///
+    /// `offset` is always a position in the raw, uncompressed snapshot data.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compression: SnapshotCompression,
+
+    /// The CRC32 checksum of `data`, as it is sent, i.e., compressed.
+    ///
+    /// The chunk is not verified if it is `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checksum: Option<u32>,
+
+    /// The CRC32 checksum of the entire snapshot, which is set only in the last chunk.
+    ///
+    /// The snapshot is not verified if it is `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub snapshot_checksum: Option<u32>,
+
     /// Will be `true` if this is the last chunk in the snapshot.
     pub done: bool,
 }
@@ -1230,11 +2017,12 @@ pub struct InstallSnapshotRequest<C: RaftTypeConfig> {
 impl<C: RaftTypeConfig> MessageSummary<InstallSnapshotRequest<C>> for InstallSnapshotRequest<C> {
     fn summary(&self) -> String {
         format!(
-            "vote={}, meta={:?}, offset={}, len={}, done={}",
+            "vote={}, meta={:?}, offset={}, len={}, compression={:?}, done={}",
             self.vote,
             self.meta,
             self.offset,
             self.data.len(),
+            self.compression,
             self.done
         )
     }
@@ -1245,6 +2033,22 @@ impl<C: RaftTypeConfig> MessageSummary<InstallSnapshotRequest<C>> for InstallSna
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct InstallSnapshotResponse<NID: NodeId> {
     pub vote: Vote<NID>,
+
+    /// The number of bytes of the snapshot `meta.snapshot_id` in the request, that the follower has received
+    /// continuously from the start.
+    ///
+    /// The leader resumes sending the snapshot from this offset.
+    /// It is `None` if the follower does not report it, e.g., when the request is rejected.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub received: Option<u64>,
+
+    /// The compressions this node is able to decode.
+    ///
+    /// The leader sends chunks uncompressed until the target reports that it is able to decode the configured
+    /// [`Config::snapshot_compression`](crate::Config::snapshot_compression).
+    /// It is empty if the node does not report it, e.g., an older version that only accepts uncompressed chunks.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compressions: Vec<SnapshotCompression>,
 }
 
 /// The response to a client-request.
@@ -2,9 +2,15 @@
 
 mod replication_session_id;
 use std::io::SeekFrom;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
 
+use futures::future::BoxFuture;
 use futures::future::FutureExt;
+use futures::stream::FuturesOrdered;
+use futures::StreamExt;
 pub(crate) use replication_session_id::ReplicationSessionId;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncSeekExt;
@@ -13,38 +19,57 @@ use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tokio::time::timeout;
+use tokio::time::timeout_at;
 use tokio::time::Duration;
+use tokio::time::Instant;
 use tracing_futures::Instrument;
 
 use crate::config::Config;
+use crate::error::AppendEntriesError;
 use crate::error::CommittedAdvanceTooMany;
 use crate::error::HigherVote;
+use crate::error::InstallSnapshotError;
 use crate::error::LackEntry;
 use crate::error::RPCError;
 use crate::error::ReplicationError;
 use crate::error::Timeout;
+use crate::metrics::Histogram;
+use crate::metrics::ReplicationStatus;
+use crate::metrics::SnapshotProgress;
+use crate::metrics::SnapshotTransferCounter;
 use crate::progress::entry::ProgressEntry;
+use crate::progress::Inflight;
+use crate::progress::InflightWindow;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::InstallSnapshotRequest;
 use crate::raft::RaftMsg;
 use crate::raft_types::LogIdOptionExt;
 use crate::raft_types::LogIndexOptionExt;
+use crate::storage::chunk_checksum;
+use crate::storage::encode_chunk;
 use crate::storage::RaftLogReader;
 use crate::storage::Snapshot;
+use crate::storage::SnapshotDigest;
+use crate::EntryPayload;
 use crate::ErrorSubject;
 use crate::ErrorVerb;
 use crate::LogId;
 use crate::MessageSummary;
 use crate::NodeId;
 use crate::RPCTypes;
+use crate::RaftLogStorage;
 use crate::RaftNetwork;
 use crate::RaftNetworkFactory;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
+use crate::SnapshotCompression;
 use crate::SnapshotPolicy;
 use crate::ToStorageResult;
 
+/// The backoff of resending a snapshot the target rejected grows up to this many times of `install_snapshot_timeout`.
+const MAX_REJECTED_BACKOFF_FACTOR: u32 = 32;
+
 /// The handle to a spawned replication stream.
 pub(crate) struct ReplicationHandle<NID: NodeId> {
     /// The spawn handle the `ReplicationCore` task.
@@ -52,32 +77,68 @@ pub(crate) struct ReplicationHandle<NID: NodeId> {
 
     /// The channel used for communicating with the replication task.
     pub(crate) tx_repl: mpsc::UnboundedSender<Replicate<NID>>,
+
+    /// The status updated by the replication task, to be copied into the replication metrics.
+    pub(crate) status: Arc<Mutex<ReplicationStatus<NID>>>,
+
+    /// Whether a status change is notified to RaftCore and RaftCore has not yet handled it.
+    pub(crate) status_notified: Arc<AtomicBool>,
+}
+
+/// An AppendEntries request that is sent to the target and its result.
+struct InflightAppend<C: RaftTypeConfig, N: RaftNetworkFactory<C>> {
+    /// The network client that sent the request.
+    network: N::Network,
+
+    prev_log_id: Option<LogId<C::NodeId>>,
+
+    /// The last log id sent by the request, or `prev_log_id` if there is no log.
+    last_log_id: Option<LogId<C::NodeId>>,
+
+    /// The time when the request was sent.
+    sending_time: Instant,
+
+    result: Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, C::Node, AppendEntriesError<C::NodeId>>>,
 }
 
 /// A task responsible for sending replication events to a target follower in the Raft cluster.
 ///
-/// NOTE: we do not stack replication requests to targets because this could result in
-/// out-of-order delivery. We always buffer until we receive a success response, then send the
-/// next payload from the buffer.
-pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
+/// Up to `Config::max_inflight_append_requests` AppendEntries requests are sent without waiting for a response,
+/// and the responses are handled in the order the requests are sent.
+/// If a request arrives at the target out of order, it is rejected with a conflict,
+/// and the inflight logs are sent again from the replication progress.
+pub(crate) struct ReplicationCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// The ID of the target Raft node which replication events are to be sent to.
     target: C::NodeId,
 
+    /// Whether the target is a witness, to which only log ids and metadata are replicated.
+    ///
+    /// A snapshot is sent to a witness only when the logs it needs are purged.
+    is_witness: bool,
+
     /// Identifies which session this replication belongs to.
     session_id: ReplicationSessionId<C::NodeId>,
 
     /// A channel for sending events to the RaftCore.
     #[allow(clippy::type_complexity)]
-    tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+    tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
 
     /// A channel for receiving events from the RaftCore.
     rx_repl: mpsc::UnboundedReceiver<Replicate<C::NodeId>>,
 
-    /// The `RaftNetwork` interface.
-    network: N::Network,
+    /// The `RaftNetwork` clients to the target that are not in use by an inflight request.
+    ///
+    /// There are `max_inflight_append_requests` clients, an inflight AppendEntries request takes one of them.
+    networks: Vec<N::Network>,
 
-    /// The `RaftLogReader` of a `RaftStorage` interface.
-    log_reader: S::LogReader,
+    /// The `RaftLogReader` of a `RaftLogStorage` interface.
+    log_reader: LS::LogReader,
 
     /// The Raft's runtime config.
     config: Arc<Config>,
@@ -91,24 +152,56 @@ pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S
     /// Replication progress
     progress: ProgressEntry<C::NodeId>,
 
+    /// The logs being sent by the inflight AppendEntries requests.
+    inflight: InflightWindow<C::NodeId>,
+
+    /// The inflight AppendEntries requests, in the order they are sent.
+    sending: FuturesOrdered<BoxFuture<'static, InflightAppend<C, N>>>,
+
     /// if or not need to replicate log entries or states, e.g., `commit_index` etc.
     need_to_replicate: bool,
+
+    /// When to resend the logs of a failed AppendEntries RPC, if no event from RaftCore triggers it earlier.
+    retry_at: Option<Instant>,
+
+    /// Counts the snapshot data sent to the target.
+    snapshot_transfer: Arc<SnapshotTransferCounter>,
+
+    /// The status of this replication stream, shared with RaftCore, which copies it into the replication metrics.
+    status: Arc<Mutex<ReplicationStatus<C::NodeId>>>,
+
+    /// Set when a status change is notified to RaftCore, and cleared by RaftCore when it handles it.
+    ///
+    /// Status changes are coalesced until RaftCore handles the pending notification.
+    status_notified: Arc<AtomicBool>,
+
+    /// Records the round trip time of successful AppendEntries RPCs to the target.
+    append_entries_latency: Arc<Histogram>,
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> ReplicationCore<C, N, S> {
+impl<C, N, LS, SM> ReplicationCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     /// Spawn a new replication task for the target node.
     #[tracing::instrument(level = "trace", skip_all,fields(target=display(target), session_id=display(session_id)))]
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn spawn(
         target: C::NodeId,
+        is_witness: bool,
         session_id: ReplicationSessionId<C::NodeId>,
         config: Arc<Config>,
         committed: Option<LogId<C::NodeId>>,
         progress_entry: ProgressEntry<C::NodeId>,
-        network: N::Network,
-        log_reader: S::LogReader,
-        tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+        networks: Vec<N::Network>,
+        log_reader: LS::LogReader,
+        tx_raft_core: mpsc::UnboundedSender<RaftMsg<C, N, LS, SM>>,
+        snapshot_transfer: Arc<SnapshotTransferCounter>,
+        append_entries_latency: Arc<Histogram>,
         span: tracing::Span,
     ) -> ReplicationHandle<C::NodeId> {
         tracing::debug!(
@@ -120,24 +213,39 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         );
         // other component to ReplicationStream
         let (tx_repl, rx_repl) = mpsc::unbounded_channel();
+        let status = Arc::new(Mutex::new(ReplicationStatus::default()));
+        let status_notified = Arc::new(AtomicBool::new(false));
 
         let this = Self {
             target,
+            is_witness,
             session_id,
-            network,
+            networks,
             log_reader,
             config,
             target_repl_state: TargetReplState::LineRate,
             committed,
             progress: progress_entry,
+            inflight: InflightWindow::default(),
+            sending: FuturesOrdered::new(),
             tx_raft_core,
             rx_repl,
             need_to_replicate: true,
+            retry_at: None,
+            snapshot_transfer,
+            status: status.clone(),
+            status_notified: status_notified.clone(),
+            append_entries_latency,
         };
 
         let join_handle = tokio::spawn(this.main().instrument(span));
 
-        ReplicationHandle { join_handle, tx_repl }
+        ReplicationHandle {
+            join_handle,
+            tx_repl,
+            status,
+            status_notified,
+        }
     }
 
     #[tracing::instrument(level="debug", skip(self), fields(session=%self.session_id, target=display(self.target), cluster=%self.config.cluster_name))]
@@ -189,13 +297,30 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         }
     }
 
-    /// Send an AppendEntries RPC to the target.
+    /// Whether another AppendEntries request can be sent before the inflight ones are responded.
+    ///
+    /// Requests are pipelined only when the matching log is determined, i.e., no binary search is in progress.
+    /// At most `max_inflight_append_requests` requests are inflight, each of which uses its own network client.
+    fn can_send(&self) -> bool {
+        if self.sending.is_empty() {
+            return true;
+        }
+
+        self.progress.searching.is_none() && !self.networks.is_empty()
+    }
+
+    /// Send an AppendEntries RPC to the target, without waiting for the response.
     ///
+    /// It sends the logs following all of the inflight logs.
+    /// The response is handled by [`Self::handle_append_entries_response()`], in the order requests are sent.
     /// This request will timeout if no response is received within the
     /// configured heartbeat interval.
     #[tracing::instrument(level = "debug", skip(self))]
     async fn send_append_entries(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
-        let (start, _right) = self.progress.sending_start();
+        let start = match self.inflight.next_index() {
+            Some(next) => next,
+            None => self.progress.sending_start().0,
+        };
 
         let mut prev_index = if start == 0 { None } else { Some(start - 1) };
 
@@ -255,10 +380,23 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 logs
             };
 
+            let logs = if self.is_witness {
+                // A witness stores only log ids and metadata: strip the application data.
+                logs.into_iter()
+                    .map(|mut ent| {
+                        if let EntryPayload::Normal(_) = ent.payload {
+                            ent.payload = EntryPayload::Blank;
+                        }
+                        ent
+                    })
+                    .collect()
+            } else {
+                logs
+            };
+
             break (prev_log_id, logs, end < last_log_index);
         };
 
-        let conflict = prev_log_id;
         let matched = if logs.is_empty() {
             prev_log_id
         } else {
@@ -276,33 +414,127 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         // Send the payload.
         tracing::debug!(
             payload=%payload.summary(),
+            inflight_requests = self.sending.len(),
+            inflight_logs = self.inflight.len(),
             "start sending append_entries, timeout: {:?}",
             self.config.heartbeat_interval
         );
 
+        self.inflight.push(Inflight::logs(prev_log_id, matched));
+        self.update_inflight_status();
+
+        // Set the need_to_replicate flag if there is more log to send.
+        self.need_to_replicate = has_more_logs;
+
+        // Safe unwrap(): `can_send()` ensures there is an idle network client.
+        let mut network = self.networks.pop().unwrap();
+
         let the_timeout = Duration::from_millis(self.config.heartbeat_interval);
-        let res = timeout(the_timeout, self.network.send_append_entries(payload)).await;
+        let leader_id = self.session_id.vote.node_id;
+        let target = self.target;
+        let sending_time = Instant::now();
+        let latency = self.append_entries_latency.clone();
 
-        let append_res = res.map_err(|_e| {
-            let to = Timeout {
-                action: RPCTypes::AppendEntries,
-                id: self.session_id.vote.node_id,
-                target: self.target,
-                timeout: the_timeout,
+        let fu = async move {
+            let res = timeout(the_timeout, network.send_append_entries(payload)).await;
+
+            if let Ok(Ok(_)) = &res {
+                latency.record(sending_time.elapsed());
+            }
+
+            let result = match res {
+                Ok(r) => r,
+                Err(_e) => {
+                    let to = Timeout {
+                        action: RPCTypes::AppendEntries,
+                        id: leader_id,
+                        target,
+                        timeout: the_timeout,
+                    };
+                    Err(RPCError::Timeout(to))
+                }
             };
-            RPCError::Timeout(to)
-        })?;
-        let append_resp = append_res?;
+
+            InflightAppend {
+                network,
+                prev_log_id,
+                last_log_id: matched,
+                sending_time,
+                result,
+            }
+        };
+
+        self.sending.push_back(Box::pin(fu));
+
+        Ok(())
+    }
+
+    /// Handle the response of the earliest inflight AppendEntries request.
+    #[tracing::instrument(level = "debug", skip_all)]
+    fn handle_append_entries_response(
+        &mut self,
+        append: InflightAppend<C, N>,
+    ) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let InflightAppend {
+            network,
+            prev_log_id,
+            last_log_id: matched,
+            sending_time,
+            result,
+        } = append;
+
+        self.networks.push(network);
+
+        let append_resp = match result {
+            Ok(x) => x,
+            Err(e) => {
+                // For transport error, just keep retrying.
+                tracing::error!(%e, "RPCError when replicating to target={}", self.target);
+                self.update_status(|s| s.error(e.to_string()));
+                let _ = self.tx_raft_core.send(RaftMsg::UpdateReplicationProgress {
+                    target: self.target,
+                    result: Err(e.to_string()),
+                    session_id: self.session_id,
+                });
+
+                // The logs sent after the failed request can not be accepted by the target. Resend them.
+                //
+                // While searching for the matching log, retry at once. Otherwise they are resent after a heartbeat
+                // interval, or upon an earlier event from RaftCore, e.g., new logs: retrying at once would flood
+                // RaftCore with errors while the target is unreachable.
+                self.inflight.rollback();
+                self.update_inflight_status();
+                self.need_to_replicate = self.progress.searching.is_some();
+                if !self.need_to_replicate {
+                    self.retry_at = Some(Instant::now() + Duration::from_millis(self.config.heartbeat_interval));
+                }
+                return Ok(());
+            }
+        };
 
         tracing::debug!("append_entries resp: {:?}", append_resp);
 
+        if !matches!(append_resp, AppendEntriesResponse::HigherVote(_)) {
+            self.update_status(|s| s.ack(Instant::now()));
+        }
+
         match append_resp {
             AppendEntriesResponse::Success => {
-                self.update_matched(matched);
+                self.progress.update_last_ack(sending_time);
+                self.inflight.ack(matched);
+                self.update_inflight_status();
+
+                if self.tracks_ack_time() && self.progress.matching >= matched {
+                    // Matching does not change, but the time of the acknowledgement has to be reported.
+                    self.report_progress();
+                } else {
+                    self.update_matched(matched);
+                }
 
-                // Set the need_to_replicate flag if there is more log to send.
-                // Otherwise leave it as is.
-                self.need_to_replicate = has_more_logs;
+                // Keep on searching for the matching log.
+                if self.progress.searching.is_some() {
+                    self.need_to_replicate = true;
+                }
                 Ok(())
             }
             AppendEntriesResponse::HigherVote(vote) => {
@@ -318,15 +550,62 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 }))
             }
             AppendEntriesResponse::Conflict => {
-                debug_assert!(conflict.is_some(), "prev_log_id=None never conflict");
-                let conflict = conflict.unwrap();
+                debug_assert!(prev_log_id.is_some(), "prev_log_id=None never conflict");
+                let conflict = prev_log_id.unwrap();
                 self.progress.update_conflicting(conflict.index);
 
+                // The logs sent after the conflicting one are rejected by the target.
+                // Resend from the updated progress.
+                self.inflight.rollback();
+                self.update_inflight_status();
+                self.need_to_replicate = true;
+
+                // A conflict response is also an acknowledgement of the leader.
+                self.progress.update_last_ack(sending_time);
+                if self.tracks_ack_time() {
+                    self.report_progress();
+                }
+
                 Ok(())
             }
         }
     }
 
+    /// Wait for all inflight AppendEntries requests to finish, to take back the network clients they use.
+    ///
+    /// The responses are discarded.
+    async fn cancel_inflight_append_entries(&mut self) {
+        while let Some(append) = self.sending.next().await {
+            self.networks.push(append.network);
+        }
+        self.inflight.rollback();
+        self.update_inflight_status();
+    }
+
+    /// Publish the range of inflight logs to the replication metrics.
+    fn update_inflight_status(&self) {
+        let range = self.inflight.range();
+        self.update_status(|s| s.inflight = range);
+    }
+
+    /// Update the status shared with RaftCore, and notify RaftCore to copy it into the replication metrics.
+    ///
+    /// Only one notification is pending at a time: a change made before RaftCore handles it is reported along with it.
+    fn update_status(&self, f: impl FnOnce(&mut ReplicationStatus<C::NodeId>)) {
+        {
+            // Safe unwrap(): the lock is never held across a panic.
+            let mut status = self.status.lock().unwrap();
+            f(&mut status);
+        }
+
+        if !self.status_notified.swap(true, Ordering::AcqRel) {
+            let _ = self.tx_raft_core.send(RaftMsg::UpdateReplicationStatus {
+                target: self.target,
+                session_id: self.session_id,
+            });
+        }
+    }
+
     /// max_possible_matched_index is the least index for `prev_log_id` to form a consecutive log sequence
     #[tracing::instrument(level = "trace", skip_all)]
     fn check_consecutive(&self, last_purged: Option<LogId<C::NodeId>>) -> Result<(), LackEntry<C::NodeId>> {
@@ -360,25 +639,41 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
             tracing::debug!(target=%self.target, progress=display(&self.progress), "matched updated");
 
-            let _ = self.tx_raft_core.send(RaftMsg::UpdateReplicationProgress {
-                session_id: self.session_id,
-                target: self.target,
-                result: Ok(self.progress),
-            });
+            self.report_progress();
         }
     }
 
+    /// Whether RaftCore needs the time of every acknowledgement from the target, to maintain a leader lease or to
+    /// check if the leader is still acknowledged by a quorum.
+    fn tracks_ack_time(&self) -> bool {
+        self.config.enable_leader_lease || self.config.enable_check_quorum
+    }
+
+    /// Report the replication progress to RaftCore.
+    fn report_progress(&mut self) {
+        let _ = self.tx_raft_core.send(RaftMsg::UpdateReplicationProgress {
+            session_id: self.session_id,
+            target: self.target,
+            result: Ok(self.progress),
+        });
+    }
+
     /// Perform a check to see if this replication stream is lagging behind far enough that a
     /// snapshot is warranted.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(self) fn needs_snapshot(&self) -> bool {
+        // A lagging witness is sent the log ids, which are much smaller than a snapshot.
+        if self.is_witness {
+            return false;
+        }
+
         let c = self.committed.next_index();
         let m = self.progress.matching.next_index();
         let distance = c.saturating_sub(m);
 
-        #[allow(clippy::infallible_destructuring_match)]
-        let snapshot_threshold = match self.config.snapshot_policy {
-            SnapshotPolicy::LogsSinceLast(n) => n,
+        let snapshot_threshold = match &self.config.snapshot_policy {
+            SnapshotPolicy::LogsSinceLast(n) => *n,
+            _ => 0,
         };
 
         let lagging_threshold = self.config.replication_lag_threshold;
@@ -434,10 +729,19 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 }
             }
             Replicate::Entries(last) => {
-                if last.index() > self.progress.matching.index() {
+                // Logs before `sending_end` are already sent or are being sent.
+                let sending_end = match self.inflight.next_index() {
+                    Some(next) => next,
+                    None => self.progress.matching.next_index(),
+                };
+
+                if last.next_index() > sending_end {
                     self.need_to_replicate = true;
                 }
             }
+            Replicate::UpdateConfig(config) => {
+                self.config = config;
+            }
         }
     }
 }
@@ -463,6 +767,9 @@ pub(crate) enum Replicate<NID: NodeId> {
     ///
     /// This message contains the last log id on this leader
     Entries(Option<LogId<NID>>),
+
+    /// Inform replication stream to use a new config, e.g., a different heartbeat interval or `max_payload_entries`.
+    UpdateConfig(Arc<Config>),
 }
 
 impl<NID: NodeId> MessageSummary<Replicate<NID>> for Replicate<NID> {
@@ -474,74 +781,95 @@ impl<NID: NodeId> MessageSummary<Replicate<NID>> for Replicate<NID> {
             Replicate::Entries(last) => {
                 format!("Replicate::Entries: upto: {:?}", last)
             }
+            Replicate::UpdateConfig(config) => {
+                format!("Replicate::UpdateConfig: {:?}", config)
+            }
         }
     }
 }
 
-impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> ReplicationCore<C, N, S> {
+impl<C, N, LS, SM> ReplicationCore<C, N, LS, SM>
+where
+    C: RaftTypeConfig,
+    N: RaftNetworkFactory<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
+{
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn line_rate_loop(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        // Always send at least one request when entering line rate, e.g., after a snapshot is installed.
+        self.need_to_replicate = true;
+
         loop {
-            loop {
+            // Send as many requests as the inflight window allows.
+            while self.need_to_replicate && self.can_send() {
                 tracing::debug!("progress: {}", self.progress);
+                self.retry_at = None;
+                self.send_append_entries().await?;
+            }
 
-                let res = self.send_append_entries().await;
-                tracing::debug!(target = display(self.target), res = debug(&res), "replication res",);
-
-                if let Err(err) = res {
-                    tracing::error!(error=%err, "error replication to target={}", self.target);
-
-                    // For transport error, just keep retrying.
-                    match err {
-                        ReplicationError::RPCError(e) => {
-                            tracing::error!(%e, "RPCError");
-                            let _ = self.tx_raft_core.send(RaftMsg::UpdateReplicationProgress {
-                                target: self.target,
-                                result: Err(e.to_string()),
-                                session_id: self.session_id,
-                            });
-                            break;
-                        }
-                        _ => {
-                            return Err(err);
-                        }
-                    }
+            if self.sending.is_empty() {
+                if self.progress.searching.is_none() && self.needs_snapshot() {
+                    return Err(ReplicationError::CommittedAdvanceTooMany(CommittedAdvanceTooMany {
+                        // TODO(xp) fill them
+                        committed_index: 0,
+                        target_index: 0,
+                    }));
                 }
 
-                if self.progress.searching.is_none() {
-                    break;
+                // Check raft channel to ensure we are staying up-to-date
+                self.try_drain_raft_rx().await?;
+                tracing::debug!(
+                    target = display(self.target),
+                    need = display(self.need_to_replicate),
+                    "need_to_replicate"
+                );
+                if self.need_to_replicate {
+                    // if there is more log, continue to send_append_entries
+                    continue;
                 }
-            }
 
-            if self.needs_snapshot() {
-                return Err(ReplicationError::CommittedAdvanceTooMany(CommittedAdvanceTooMany {
-                    // TODO(xp) fill them
-                    committed_index: 0,
-                    target_index: 0,
-                }));
-            }
+                let event_or_none = match self.retry_at {
+                    None => self.rx_repl.recv().await,
+                    Some(at) => match timeout_at(at, self.rx_repl.recv()).await {
+                        Ok(x) => x,
+                        Err(_elapsed) => {
+                            self.need_to_replicate = true;
+                            continue;
+                        }
+                    },
+                };
 
-            // Check raft channel to ensure we are staying up-to-date
-            self.try_drain_raft_rx().await?;
-            tracing::debug!(
-                target = display(self.target),
-                need = display(self.need_to_replicate),
-                "need_to_replicate"
-            );
-            if self.need_to_replicate {
-                // if there is more log, continue to send_append_entries
+                match event_or_none {
+                    Some(event) => {
+                        self.process_raft_event(event);
+                        self.try_drain_raft_rx().await?;
+                    }
+                    None => {
+                        tracing::debug!("received: RaftEvent::Terminate: closed");
+                        return Err(ReplicationError::Closed);
+                    }
+                }
                 continue;
             }
 
-            let event_or_none = self.rx_repl.recv().await;
-            match event_or_none {
-                Some(event) => {
-                    self.process_raft_event(event);
-                    self.try_drain_raft_rx().await?;
+            // Wait for a response to the earliest inflight request, or an event from RaftCore.
+            tokio::select! {
+                append = self.sending.next() => {
+                    // Safe unwrap(): `sending` is not empty.
+                    let append = append.unwrap();
+                    let res = self.handle_append_entries_response(append);
+                    tracing::debug!(target = display(self.target), res = debug(&res), "replication res",);
+                    res?;
                 }
-                None => {
-                    tracing::debug!("received: RaftEvent::Terminate: closed");
-                    return Err(ReplicationError::Closed);
+                event_or_none = self.rx_repl.recv() => {
+                    match event_or_none {
+                        Some(event) => self.process_raft_event(event),
+                        None => {
+                            tracing::debug!("received: RaftEvent::Terminate: closed");
+                            return Err(ReplicationError::Closed);
+                        }
+                    }
                 }
             }
         }
@@ -549,17 +877,47 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
     #[tracing::instrument(level = "debug", skip(self), fields(state = "snapshotting"))]
     pub async fn replicate_snapshot(&mut self) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        // A snapshot replaces the inflight logs. Take back the network clients they use.
+        self.cancel_inflight_append_entries().await;
+
         let snapshot = self.wait_for_snapshot().await?;
-        self.stream_snapshot(snapshot).await?;
+        let res = self.stream_snapshot(snapshot).await;
 
-        Ok(())
+        self.update_status(|s| s.snapshot = None);
+
+        res
+    }
+
+    /// Sleep for `d`, while handling the events from RaftCore.
+    ///
+    /// It returns [`ReplicationError::Closed`] at once if the replication is closed.
+    async fn sleep_and_drain_raft_rx(&mut self, d: Duration) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
+        let until = Instant::now() + d;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(until) => {
+                    return Ok(());
+                }
+                event_or_none = self.rx_repl.recv() => {
+                    match event_or_none {
+                        Some(event) => self.process_raft_event(event),
+                        None => {
+                            tracing::debug!("received: RaftEvent::Terminate: closed");
+                            return Err(ReplicationError::Closed);
+                        }
+                    }
+                }
+            }
+        }
     }
 
     /// Ask RaftCore for a snapshot
     #[tracing::instrument(level = "debug", skip(self))]
     async fn wait_for_snapshot(
         &mut self,
-    ) -> Result<Snapshot<C::NodeId, C::Node, S::SnapshotData>, ReplicationError<C::NodeId, C::Node>> {
+    ) -> Result<Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>, ReplicationError<C::NodeId, C::Node>>
+    {
         // Ask raft core for a snapshot.
         //
         // RaftCore must have a ready snapshot:
@@ -587,7 +945,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
     #[tracing::instrument(level = "trace", skip(self, snapshot))]
     async fn stream_snapshot(
         &mut self,
-        mut snapshot: Snapshot<C::NodeId, C::Node, S::SnapshotData>,
+        mut snapshot: Snapshot<C::NodeId, C::Node, SM::SnapshotData, C::SnapshotMetadata>,
     ) -> Result<(), ReplicationError<C::NodeId, C::Node>> {
         let err_x = || (ErrorSubject::Snapshot(snapshot.meta.signature()), ErrorVerb::Read);
 
@@ -595,24 +953,63 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         let end = snapshot.snapshot.seek(SeekFrom::End(0)).await.sto_res(err_x)?;
         let mut buf = Vec::with_capacity(self.config.snapshot_max_chunk_size as usize);
 
+        // Digest of the snapshot data, for the target to verify the entire snapshot.
+        let mut digest = SnapshotDigest::default();
+
+        // Chunks are sent uncompressed until the target reports it is able to decode the configured compression.
+        let mut compression = SnapshotCompression::None;
+
+        // How long to wait before resending a snapshot the target rejected. It doubles on every rejection.
+        let mut rejected_backoff = self.config.install_snapshot_timeout();
+
+        self.update_status(|s| {
+            s.snapshot = Some(SnapshotProgress {
+                sent_bytes: 0,
+                total_bytes: end,
+            })
+        });
+
         loop {
             // Build the RPC.
             snapshot.snapshot.seek(SeekFrom::Start(offset)).await.sto_res(err_x)?;
             let n_read = snapshot.snapshot.read_buf(&mut buf).await.sto_res(err_x)?;
+            let data = Vec::from(&buf[..n_read]);
+            buf.clear();
 
             let done = (offset + n_read as u64) == end;
+
+            // The data skipped because the target already has it, is not yet digested.
+            while done && digest.len() < offset {
+                let start = digest.len();
+                snapshot.snapshot.seek(SeekFrom::Start(start)).await.sto_res(err_x)?;
+                let n = snapshot.snapshot.read_buf(&mut buf).await.sto_res(err_x)?;
+                if n == 0 {
+                    break;
+                }
+                digest.update(start, &buf[..n]);
+                buf.clear();
+            }
+            digest.update(offset, &data);
+
+            let raw_len = data.len() as u64;
+            let data = encode_chunk(compression, data);
+            let sent_len = data.len() as u64;
+
             let req = InstallSnapshotRequest {
                 vote: self.session_id.vote,
                 meta: snapshot.meta.clone(),
                 offset,
-                data: Vec::from(&buf[..n_read]),
+                compression,
+                checksum: Some(chunk_checksum(&data)),
+                snapshot_checksum: if done { Some(digest.finalize()) } else { None },
+                data,
                 done,
             };
-            buf.clear();
 
             // Send the RPC over to the target.
             tracing::debug!(
                 snapshot_size = req.data.len(),
+                raw_size = raw_len,
                 req.offset,
                 end,
                 req.done,
@@ -625,13 +1022,45 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 self.config.send_snapshot_timeout()
             };
 
-            let res = timeout(snap_timeout, self.network.send_install_snapshot(req)).await;
+            let res = timeout(snap_timeout, self.networks[0].send_install_snapshot(req)).await;
 
             let res = match res {
                 Ok(outer_res) => match outer_res {
                     Ok(res) => res,
                     Err(err) => {
                         tracing::warn!(error=%err, "error sending InstallSnapshot RPC to target");
+                        self.update_status(|s| s.error(err.to_string()));
+
+                        // If the target has lost the partially received snapshot, e.g., it restarted,
+                        // start over from the offset it expects.
+                        // If the target received corrupted data and discarded it, restart the transfer.
+                        if let RPCError::RemoteError(remote_err) = &err {
+                            match &remote_err.source {
+                                InstallSnapshotError::SnapshotMismatch(mismatch) => {
+                                    offset = mismatch.expect.offset;
+                                }
+                                InstallSnapshotError::SnapshotChecksumMismatch(_) => {
+                                    offset = 0;
+                                }
+                                InstallSnapshotError::SnapshotDecodeError(_) => {}
+                                InstallSnapshotError::IncompatibleSnapshot(incompatible) => {
+                                    // The target may be upgraded to accept it, check again later.
+                                    // Back off to avoid resending it over and over to a target that never accepts it.
+                                    tracing::warn!(
+                                        error = display(incompatible),
+                                        "target rejected the snapshot, retry after {:?}",
+                                        rejected_backoff
+                                    );
+                                    offset = 0;
+                                    self.sleep_and_drain_raft_rx(rejected_backoff).await?;
+                                    rejected_backoff = std::cmp::min(
+                                        rejected_backoff * 2,
+                                        self.config.install_snapshot_timeout() * MAX_REJECTED_BACKOFF_FACTOR,
+                                    );
+                                }
+                                InstallSnapshotError::Fatal(_) => {}
+                            }
+                        }
 
                         // Sleep a short time otherwise in test environment it is a dead-loop that never yields.
                         // Because network implementation does not yield.
@@ -641,6 +1070,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 },
                 Err(err) => {
                     tracing::warn!(error=%err, "timeout while sending InstallSnapshot RPC to target");
+                    self.update_status(|s| s.error(format!("timeout sending InstallSnapshot: {}", err)));
 
                     // Sleep a short time otherwise in test environment it is a dead-loop that never yields.
                     // Because network implementation does not yield.
@@ -657,6 +1087,13 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 }));
             }
 
+            self.snapshot_transfer.add_sent(sent_len, raw_len);
+            self.update_status(|s| s.ack(Instant::now()));
+
+            if res.compressions.contains(&self.config.snapshot_compression) {
+                compression = self.config.snapshot_compression;
+            }
+
             // If we just sent the final chunk of the snapshot, then transition to lagging state.
             if done {
                 tracing::debug!(
@@ -665,13 +1102,25 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                     self.progress,
                 );
 
+                self.snapshot_transfer.add_sent_snapshot();
                 self.update_matched(snapshot.meta.last_log_id);
 
                 return Ok(());
             }
 
             // Everything is good, so update offset for sending the next chunk.
-            offset += n_read as u64;
+            // If the target reports the number of bytes it has received, resume from there: it may already hold more
+            // than this chunk, if a previous replication stream has sent part of this snapshot.
+            offset = match res.received {
+                Some(received) => std::cmp::min(received, end),
+                None => offset + n_read as u64,
+            };
+
+            self.update_status(|s| {
+                if let Some(p) = &mut s.snapshot {
+                    p.sent_bytes = offset;
+                }
+            });
 
             // Check raft channel to ensure we are staying up-to-date, then loop.
             self.try_drain_raft_rx().await?;
@@ -0,0 +1,182 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+use async_trait::async_trait;
+
+use crate::error::IncompatibleSnapshot;
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftLogStorage;
+use crate::storage::RaftStateMachine;
+use crate::storage::Snapshot;
+use crate::EffectiveMembership;
+use crate::Entry;
+use crate::LogId;
+use crate::RaftStorage;
+use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
+use crate::StorageError;
+use crate::Vote;
+
+/// Adapts a [`RaftStorage`] implementation to the v2 storage API: [`RaftLogStorage`] and [`RaftStateMachine`].
+///
+/// [`Adaptor::new`] builds a log store and a state machine, each of which holds a clone of the `RaftStorage`,
+/// so that appending logs and applying logs run in parallel.
+/// Thus the clones of a `RaftStorage` must share the same underlying storage, e.g., `Arc<T>`.
+///
+/// ```ignore
+/// let (log_store, state_machine) = Adaptor::new(store);
+/// let raft = Raft::new(id, config, network, log_store, state_machine).await?;
+/// ```
+pub struct Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    storage: S,
+    c: PhantomData<C>,
+}
+
+impl<C, S> Clone for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            c: PhantomData,
+        }
+    }
+}
+
+impl<C, S> Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C> + Clone,
+{
+    /// Create a log store and a state machine backed by the same `RaftStorage`.
+    pub fn new(storage: S) -> (Self, Self) {
+        let log_store = Adaptor {
+            storage: storage.clone(),
+            c: PhantomData,
+        };
+        let state_machine = Adaptor {
+            storage,
+            c: PhantomData,
+        };
+
+        (log_store, state_machine)
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftLogReader<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.storage.get_log_state().await
+    }
+
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.storage.try_get_log_entries(range).await
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftLogStorage<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    type LogReader = S::LogReader;
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.storage.read_vote().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.storage.get_log_reader().await
+    }
+
+    async fn append(
+        &mut self,
+        entries: &[&Entry<C>],
+        callback: LogFlushed<C::NodeId>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        // `RaftStorage::append_to_log` persists logs before returning.
+        self.storage.append_to_log(entries).await?;
+        callback.log_io_completed(Ok(()));
+
+        Ok(())
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.delete_conflict_logs_since(log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.purge_logs_upto(log_id).await
+    }
+}
+
+#[async_trait]
+impl<C, S> RaftStateMachine<C> for Adaptor<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C>,
+{
+    type SnapshotData = S::SnapshotData;
+    type SnapshotBuilder = S::SnapshotBuilder;
+
+    async fn applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.storage.last_applied_state().await
+    }
+
+    async fn apply(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        self.storage.apply_to_state_machine(entries).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.storage.get_snapshot_builder().await
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>> {
+        self.storage.begin_receiving_snapshot().await
+    }
+
+    async fn check_snapshot_meta(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<(), IncompatibleSnapshot> {
+        self.storage.check_snapshot_meta(meta).await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        snapshot: Box<Self::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.storage.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData, C::SnapshotMetadata>>, StorageError<C::NodeId>>
+    {
+        self.storage.get_current_snapshot().await
+    }
+}
@@ -0,0 +1,49 @@
+//! Callbacks used by a log store to notify Raft of the completion of an IO operation.
+
+use std::io;
+
+use tokio::sync::oneshot;
+
+use crate::LogId;
+use crate::NodeId;
+
+/// A callback of [`RaftLogStorage::append`](crate::RaftLogStorage::append), to notify Raft that the appended logs are
+/// persisted on disk.
+///
+/// A log store may return from `append` before the logs are flushed, and call
+/// [`log_io_completed`](Self::log_io_completed) later, e.g., after a single `fsync` for several appends.
+/// Logs must be flushed in the order they are appended.
+pub struct LogFlushed<NID>
+where NID: NodeId
+{
+    last_log_id: Option<LogId<NID>>,
+    tx: oneshot::Sender<Result<(), io::Error>>,
+}
+
+impl<NID> LogFlushed<NID>
+where NID: NodeId
+{
+    pub(crate) fn new(last_log_id: Option<LogId<NID>>, tx: oneshot::Sender<Result<(), io::Error>>) -> Self {
+        Self { last_log_id, tx }
+    }
+
+    /// The id of the last log to persist.
+    pub fn last_log_id(&self) -> Option<LogId<NID>> {
+        self.last_log_id
+    }
+
+    /// Report the result of persisting the logs.
+    ///
+    /// It must be called after all of the logs are persisted on disk, or the log store failed to persist them.
+    pub fn log_io_completed(self, result: Result<(), io::Error>) {
+        let res = self.tx.send(result);
+
+        if let Err(e) = res {
+            tracing::error!(
+                "failed to report flushed logs upto: {:?}, Raft may have shut down: {:?}",
+                self.last_log_id,
+                e
+            );
+        }
+    }
+}
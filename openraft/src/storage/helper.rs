@@ -7,29 +7,41 @@ use crate::EntryPayload;
 use crate::LogId;
 use crate::LogIdOptionExt;
 use crate::MembershipState;
+use crate::RaftLogStorage;
 use crate::RaftState;
-use crate::RaftStorage;
+use crate::RaftStateMachine;
 use crate::RaftTypeConfig;
 use crate::StorageError;
 
-/// StorageHelper provides additional methods to access a RaftStorage implementation.
-pub struct StorageHelper<'a, C, Sto>
+/// StorageHelper provides additional methods to access a log store and a state machine.
+///
+/// A [`RaftStorage`](crate::RaftStorage) implementation can be accessed with [`Adaptor`](crate::storage::Adaptor):
+///
+/// ```ignore
+/// let (mut log_store, mut state_machine) = Adaptor::new(store);
+/// let membership = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
+/// ```
+pub struct StorageHelper<'a, C, LS, SM>
 where
     C: RaftTypeConfig,
-    Sto: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
-    pub(crate) sto: &'a mut Sto,
+    pub(crate) log_store: &'a mut LS,
+    pub(crate) state_machine: &'a mut SM,
     _p: PhantomData<C>,
 }
 
-impl<'a, C, Sto> StorageHelper<'a, C, Sto>
+impl<'a, C, LS, SM> StorageHelper<'a, C, LS, SM>
 where
     C: RaftTypeConfig,
-    Sto: RaftStorage<C>,
+    LS: RaftLogStorage<C>,
+    SM: RaftStateMachine<C>,
 {
-    pub fn new(sto: &'a mut Sto) -> Self {
+    pub fn new(log_store: &'a mut LS, state_machine: &'a mut SM) -> Self {
         Self {
-            sto,
+            log_store,
+            state_machine,
             _p: Default::default(),
         }
     }
@@ -39,16 +51,16 @@ where
     /// When the Raft node is first started, it will call this interface to fetch the last known state from stable
     /// storage.
     pub async fn get_initial_state(&mut self) -> Result<RaftState<C::NodeId, C::Node>, StorageError<C::NodeId>> {
-        let vote = self.sto.read_vote().await?;
-        let st = self.sto.get_log_state().await?;
+        let vote = self.log_store.read_vote().await?;
+        let st = self.log_store.get_log_state().await?;
         let mut last_purged_log_id = st.last_purged_log_id;
         let mut last_log_id = st.last_log_id;
-        let (last_applied, _) = self.sto.last_applied_state().await?;
+        let (last_applied, _) = self.state_machine.applied_state().await?;
         let mem_state = self.get_membership().await?;
 
         // Clean up dirty state: snapshot is installed but logs are not cleaned.
         if last_log_id < last_applied {
-            self.sto.purge_logs_upto(last_applied.unwrap()).await?;
+            self.log_store.purge_logs_upto(last_applied.unwrap()).await?;
             last_log_id = last_applied;
             last_purged_log_id = last_applied;
         }
@@ -58,7 +70,8 @@ where
         let log_ids = LogIdList::load_log_ids(last_purged_log_id, last_log_id, self).await?;
         println!("log_ids: {:?}", log_ids);
 
-        let snapshot_meta = self.sto.get_current_snapshot().await?.map(|x| x.meta).unwrap_or_default();
+        let snapshot_meta =
+            self.state_machine.get_current_snapshot().await?.map(|x| x.meta.split().0).unwrap_or_default();
 
         Ok(RaftState {
             committed: last_applied,
@@ -77,13 +90,13 @@ where
 
     /// Get the log id of the entry at `index`.
     pub async fn get_log_id(&mut self, log_index: u64) -> Result<LogId<C::NodeId>, StorageError<C::NodeId>> {
-        let st = self.sto.get_log_state().await?;
+        let st = self.log_store.get_log_state().await?;
 
         if Some(log_index) == st.last_purged_log_id.index() {
             return Ok(st.last_purged_log_id.unwrap());
         }
 
-        let entries = self.sto.get_log_entries(log_index..=log_index).await?;
+        let entries = self.log_store.get_log_entries(log_index..=log_index).await?;
 
         Ok(entries[0].log_id)
     }
@@ -105,7 +118,7 @@ where
     ///
     /// Thus a raft node will only need to store at most two recent membership logs.
     pub async fn get_membership(&mut self) -> Result<MembershipState<C::NodeId, C::Node>, StorageError<C::NodeId>> {
-        let (_, sm_mem) = self.sto.last_applied_state().await?;
+        let (_, sm_mem) = self.state_machine.applied_state().await?;
 
         let sm_mem_next_index = sm_mem.log_id.next_index();
 
@@ -143,7 +156,7 @@ where
         &mut self,
         since_index: u64,
     ) -> Result<Vec<EffectiveMembership<C::NodeId, C::Node>>, StorageError<C::NodeId>> {
-        let st = self.sto.get_log_state().await?;
+        let st = self.log_store.get_log_state().await?;
 
         let mut end = st.last_log_id.next_index();
         let start = std::cmp::max(st.last_purged_log_id.next_index(), since_index);
@@ -153,7 +166,7 @@ where
 
         while start < end {
             let step_start = std::cmp::max(start, end.saturating_sub(step));
-            let entries = self.sto.try_get_log_entries(step_start..end).await?;
+            let entries = self.log_store.try_get_log_entries(step_start..end).await?;
 
             for ent in entries.iter().rev() {
                 if let EntryPayload::Membership(ref mem) = ent.payload {
@@ -1,21 +1,37 @@
 //! The Raft storage interface and data types.
 
+mod adaptor;
+mod callback;
 mod helper;
+mod snapshot_codec;
+mod snapshot_digest;
 mod snapshot_signature;
+mod v2;
 use std::fmt::Debug;
 use std::ops::RangeBounds;
 
+pub use adaptor::Adaptor;
 use async_trait::async_trait;
+pub use callback::LogFlushed;
 pub use helper::StorageHelper;
+pub(crate) use snapshot_codec::decode_chunk;
+pub(crate) use snapshot_codec::encode_chunk;
+pub use snapshot_codec::MAX_DECODED_CHUNK_SIZE;
+pub(crate) use snapshot_digest::chunk_checksum;
+pub(crate) use snapshot_digest::SnapshotDigest;
 pub use snapshot_signature::SnapshotSignature;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncSeek;
 use tokio::io::AsyncWrite;
+pub use v2::RaftLogStorage;
+pub use v2::RaftStateMachine;
 
 use crate::defensive::check_range_matches_entries;
+use crate::error::IncompatibleSnapshot;
 use crate::membership::EffectiveMembership;
 use crate::node::Node;
 use crate::raft_types::SnapshotId;
+use crate::AppSnapshotMetadata;
 use crate::Entry;
 use crate::LogId;
 use crate::MessageSummary;
@@ -24,12 +40,17 @@ use crate::RaftTypeConfig;
 use crate::StorageError;
 use crate::Vote;
 
+/// The metadata of a snapshot.
+///
+/// `M` is the application-specific metadata, i.e., [`RaftTypeConfig::SnapshotMetadata`].
+/// Raft itself does not use it, and a `SnapshotMeta` used internally by Raft does not carry it, with `M = ()`.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
-pub struct SnapshotMeta<NID, N>
+pub struct SnapshotMeta<NID, N, M = ()>
 where
     NID: NodeId,
     N: Node,
+    M: AppSnapshotMetadata,
 {
     /// Log entries upto which this snapshot includes, inclusive.
     pub last_log_id: Option<LogId<NID>>,
@@ -40,27 +61,34 @@ where
     /// To identify a snapshot when transferring.
     /// Caveat: even when two snapshot is built with the same `last_log_id`, they still could be different in bytes.
     pub snapshot_id: SnapshotId,
+
+    /// The application-specific metadata, e.g., the format version of the snapshot data.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metadata: M,
 }
 
-impl<NID, N> MessageSummary<SnapshotMeta<NID, N>> for SnapshotMeta<NID, N>
+impl<NID, N, M> MessageSummary<SnapshotMeta<NID, N, M>> for SnapshotMeta<NID, N, M>
 where
     NID: NodeId,
     N: Node,
+    M: AppSnapshotMetadata,
 {
     fn summary(&self) -> String {
         format!(
-            "{{snapshot_id: {}, last_membership: {}, last_log_id: {}}}",
+            "{{snapshot_id: {}, last_membership: {}, last_log_id: {}, metadata: {:?}}}",
             self.snapshot_id,
             self.last_log_id.summary(),
-            self.last_membership.summary()
+            self.last_membership.summary(),
+            self.metadata
         )
     }
 }
 
-impl<NID, N> SnapshotMeta<NID, N>
+impl<NID, N, M> SnapshotMeta<NID, N, M>
 where
     NID: NodeId,
     N: Node,
+    M: AppSnapshotMetadata,
 {
     pub fn signature(&self) -> SnapshotSignature<NID> {
         SnapshotSignature {
@@ -74,18 +102,46 @@ where
     pub fn last_log_id(&self) -> Option<&LogId<NID>> {
         self.last_log_id.as_ref()
     }
+
+    /// Split it into the metadata used by Raft and the application-specific metadata.
+    pub(crate) fn split(self) -> (SnapshotMeta<NID, N>, M) {
+        let meta = SnapshotMeta {
+            last_log_id: self.last_log_id,
+            last_membership: self.last_membership,
+            snapshot_id: self.snapshot_id,
+            metadata: (),
+        };
+        (meta, self.metadata)
+    }
+}
+
+impl<NID, N> SnapshotMeta<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    /// Attach the application-specific metadata to the metadata used by Raft.
+    pub(crate) fn with_metadata<M: AppSnapshotMetadata>(self, metadata: M) -> SnapshotMeta<NID, N, M> {
+        SnapshotMeta {
+            last_log_id: self.last_log_id,
+            last_membership: self.last_membership,
+            snapshot_id: self.snapshot_id,
+            metadata,
+        }
+    }
 }
 
 /// The data associated with the current snapshot.
 #[derive(Debug)]
-pub struct Snapshot<NID, N, S>
+pub struct Snapshot<NID, N, S, M = ()>
 where
     NID: NodeId,
     N: Node,
     S: AsyncRead + AsyncSeek + Send + Unpin + 'static,
+    M: AppSnapshotMetadata,
 {
     /// metadata of a snapshot
-    pub meta: SnapshotMeta<NID, N>,
+    pub meta: SnapshotMeta<NID, N, M>,
 
     /// A read handle to the associated snapshot.
     pub snapshot: Box<S>,
@@ -178,7 +234,9 @@ where
     /// Building snapshot can be done by:
     /// - Performing log compaction, e.g. merge log entries that operates on the same key, like a LSM-tree does,
     /// - or by fetching a snapshot from the state machine.
-    async fn build_snapshot(&mut self) -> Result<Snapshot<C::NodeId, C::Node, SD>, StorageError<C::NodeId>>;
+    async fn build_snapshot(
+        &mut self,
+    ) -> Result<Snapshot<C::NodeId, C::Node, SD, C::SnapshotMetadata>, StorageError<C::NodeId>>;
 
     // NOTES:
     // This interface is geared toward small file-based snapshots. However, not all snapshots can
@@ -194,10 +252,13 @@ where
 /// Typically, the storage implementation as such will be hidden behind a `Box<T>`, `Arc<T>` or
 /// a similar, more advanced reference type and this interface implemented on that reference type.
 ///
-/// All methods on the storage are called inside of Raft core task. There is no concurrency on the
-/// storage, except concurrency with snapshot builder and log reader, both created by this API.
-/// The implementation of the API has to cope with (infrequent) concurrent access from these two
-/// components.
+/// A `RaftStorage` is used by Raft through [`Adaptor`], which splits it into a log store ([`RaftLogStorage`]) and a
+/// state machine ([`RaftStateMachine`]), each of which holds a clone of it.
+///
+/// Except the state machine methods, all methods on the storage are called inside of Raft core task.
+/// The state machine methods are called by a dedicated task that applies committed logs.
+/// The implementation of the API has to cope with concurrent access from these two tasks, and (infrequent)
+/// concurrent access from snapshot builder and log reader, both created by this API.
 #[async_trait]
 pub trait RaftStorage<C>: RaftLogReader<C> + Send + Sync + 'static
 where C: RaftTypeConfig
@@ -263,11 +324,10 @@ where C: RaftTypeConfig
     /// - Store the last applied log id.
     /// - Deal with the EntryPayload::Normal() log, which is business logic log.
     /// - Deal with EntryPayload::Membership, store the membership config.
-    // TODO The reply should happen asynchronously, somehow. Make this method synchronous and
-    // instead of using the result, pass a channel where to post the completion. The Raft core can
-    // then collect completions on this channel and update the client with the result once all
-    // the preceding operations have been applied to the state machine. This way we'll reach
-    // operation pipelining w/o the need to wait for the completion of each operation inline.
+    ///
+    /// It is called by the state machine task, not by Raft core task. Thus a slow state machine does not block Raft
+    /// core from sending heartbeats or replicating logs. Logs are applied in order and a call does not overlap with
+    /// another one.
     async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>>;
 
     // --- Snapshot
@@ -287,6 +347,22 @@ where C: RaftTypeConfig
     /// for details on log compaction / snapshotting.
     async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>>;
 
+    /// Check whether a snapshot sent by the leader can be installed, before receiving its data.
+    ///
+    /// It is called with the metadata of a snapshot, including the application-specific
+    /// [`SnapshotMeta::metadata`], when the first chunk of it arrives.
+    /// Returning an error rejects the snapshot: no data of it is received, and the leader is informed with
+    /// [`InstallSnapshotError::IncompatibleSnapshot`](`crate::error::InstallSnapshotError::IncompatibleSnapshot`).
+    ///
+    /// By default every snapshot is accepted.
+    async fn check_snapshot_meta(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<(), IncompatibleSnapshot> {
+        let _ = meta;
+        Ok(())
+    }
+
     /// Install a snapshot which has finished streaming from the leader.
     ///
     /// All other snapshots should be deleted at this point.
@@ -295,7 +371,7 @@ where C: RaftTypeConfig
     /// A snapshot created from an earlier call to `begin_receiving_snapshot` which provided the snapshot.
     async fn install_snapshot(
         &mut self,
-        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
         snapshot: Box<Self::SnapshotData>,
     ) -> Result<(), StorageError<C::NodeId>>;
 
@@ -312,7 +388,7 @@ where C: RaftTypeConfig
     /// of the snapshot, which should be decoded for creating this method's response data.
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>>;
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData, C::SnapshotMetadata>>, StorageError<C::NodeId>>;
 }
 
 /// APIs for debugging a store.
@@ -0,0 +1,102 @@
+use anyerror::AnyError;
+
+use crate::SnapshotCompression;
+
+/// The max size of a compressed snapshot chunk once it is decoded.
+///
+/// It is a protocol constant rather than a local config value, so that a target accepts the chunks from a leader
+/// configured with a different `snapshot_max_chunk_size`.
+pub const MAX_DECODED_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Encode a chunk of snapshot data to send it to a target.
+///
+/// Every chunk is encoded independently, so that a target is able to decode a chunk without the previous ones.
+pub(crate) fn encode_chunk(compression: SnapshotCompression, data: Vec<u8>) -> Vec<u8> {
+    match compression {
+        SnapshotCompression::None => data,
+        SnapshotCompression::Lz4 => lz4_flex::block::compress_prepend_size(&data),
+    }
+}
+
+/// Decode a chunk of snapshot data encoded by [`encode_chunk`], and returns the raw bytes.
+///
+/// The decoded size a compressed chunk declares is not trusted: a chunk that declares more than
+/// [`MAX_DECODED_CHUNK_SIZE`] bytes is rejected before allocating any buffer for it.
+pub(crate) fn decode_chunk(compression: SnapshotCompression, data: Vec<u8>) -> Result<Vec<u8>, AnyError> {
+    match compression {
+        SnapshotCompression::None => Ok(data),
+        SnapshotCompression::Lz4 => {
+            if data.len() < 4 {
+                return Err(AnyError::error(format!(
+                    "lz4 chunk of {} bytes has no size header",
+                    data.len()
+                )));
+            }
+
+            let size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as u64;
+            if size > MAX_DECODED_CHUNK_SIZE {
+                return Err(AnyError::error(format!(
+                    "lz4 chunk declares {} decoded bytes, more than the max decoded chunk size {}",
+                    size, MAX_DECODED_CHUNK_SIZE
+                )));
+            }
+
+            lz4_flex::block::decompress(&data[4..], size as usize).map_err(|e| AnyError::new(&e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::snapshot_codec::decode_chunk;
+    use crate::storage::snapshot_codec::encode_chunk;
+    use crate::storage::snapshot_codec::MAX_DECODED_CHUNK_SIZE;
+    use crate::SnapshotCompression;
+
+    #[test]
+    fn test_codec_none() -> anyhow::Result<()> {
+        let data = b"foo".to_vec();
+
+        let encoded = encode_chunk(SnapshotCompression::None, data.clone());
+        assert_eq!(data, encoded);
+        assert_eq!(data, decode_chunk(SnapshotCompression::None, encoded)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_lz4() -> anyhow::Result<()> {
+        let data = br#"{"last_applied_log":null,"data":{"foo":"bar","foo1":"bar","foo2":"bar"}}"#.repeat(100);
+
+        let encoded = encode_chunk(SnapshotCompression::Lz4, data.clone());
+        assert!(encoded.len() < data.len());
+        assert_eq!(data, decode_chunk(SnapshotCompression::Lz4, encoded)?);
+
+        // An empty chunk
+        let encoded = encode_chunk(SnapshotCompression::Lz4, vec![]);
+        assert_eq!(Vec::<u8>::new(), decode_chunk(SnapshotCompression::Lz4, encoded)?);
+
+        // Data that is not lz4 encoded
+        let res = decode_chunk(SnapshotCompression::Lz4, vec![10, 0, 0, 0, 0xff]);
+        assert!(res.is_err());
+
+        // No size header
+        let res = decode_chunk(SnapshotCompression::Lz4, vec![10, 0]);
+        assert!(res.is_err());
+
+        // The declared size exceeds the max decoded chunk size
+        let res = decode_chunk(SnapshotCompression::Lz4, vec![0xff, 0xff, 0xff, 0xff, 0]);
+        let err = res.unwrap_err().to_string();
+        assert!(
+            err.contains(&format!(
+                "lz4 chunk declares {} decoded bytes, more than the max decoded chunk size {}",
+                u32::MAX,
+                MAX_DECODED_CHUNK_SIZE
+            )),
+            "{}",
+            err
+        );
+
+        Ok(())
+    }
+}
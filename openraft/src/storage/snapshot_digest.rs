@@ -0,0 +1,89 @@
+/// Checksum of a chunk of snapshot data.
+pub(crate) fn chunk_checksum(data: &[u8]) -> u32 {
+    crc32fast::hash(data)
+}
+
+/// Digest of the snapshot data that is received or sent continuously from the start.
+///
+/// A chunk may be sent more than once, e.g., when a replication stream restarts.
+/// Only the part of a chunk after the digested bytes is fed into the digest, and a chunk after a gap is ignored.
+/// The chunks at the same offset of the same snapshot must have the same data.
+#[derive(Clone, Default)]
+pub(crate) struct SnapshotDigest {
+    hasher: crc32fast::Hasher,
+
+    /// The number of bytes fed into the digest.
+    len: u64,
+}
+
+impl SnapshotDigest {
+    /// The number of bytes fed into the digest, i.e., the offset of the first byte that is not yet digested.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Feed a chunk of data at `offset` into the digest.
+    pub(crate) fn update(&mut self, offset: u64, data: &[u8]) {
+        let end = offset + data.len() as u64;
+
+        if offset > self.len || end <= self.len {
+            return;
+        }
+
+        let start = (self.len - offset) as usize;
+        self.hasher.update(&data[start..]);
+        self.len = end;
+    }
+
+    /// Returns the digest of all the bytes fed.
+    pub(crate) fn finalize(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::chunk_checksum;
+    use crate::storage::SnapshotDigest;
+
+    #[test]
+    fn test_snapshot_digest_update() -> anyhow::Result<()> {
+        let want = chunk_checksum(b"abcdef");
+
+        tracing::info!("--- continuous chunks");
+        {
+            let mut d = SnapshotDigest::default();
+            d.update(0, b"ab");
+            d.update(2, b"cdef");
+            assert_eq!(6, d.len());
+            assert_eq!(want, d.finalize());
+        }
+
+        tracing::info!("--- overlapping and resent chunks");
+        {
+            let mut d = SnapshotDigest::default();
+            d.update(0, b"abc");
+            d.update(0, b"ab");
+            d.update(1, b"bcd");
+            d.update(0, b"abcd");
+            d.update(4, b"ef");
+            assert_eq!(6, d.len());
+            assert_eq!(want, d.finalize());
+        }
+
+        tracing::info!("--- a chunk after a gap is ignored");
+        {
+            let mut d = SnapshotDigest::default();
+            d.update(0, b"ab");
+            d.update(3, b"def");
+            assert_eq!(2, d.len());
+            assert_eq!(chunk_checksum(b"ab"), d.finalize());
+
+            d.update(2, b"c");
+            d.update(3, b"def");
+            assert_eq!(want, d.finalize());
+        }
+
+        Ok(())
+    }
+}
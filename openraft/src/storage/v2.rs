@@ -0,0 +1,128 @@
+//! The v2 storage API, in which the log store and the state machine are two separate objects.
+//!
+//! The log store is used by `RaftCore` and the state machine is used by the task applying committed logs.
+//! They do not have to be synchronized with each other.
+
+use async_trait::async_trait;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
+
+use crate::error::IncompatibleSnapshot;
+use crate::storage::LogFlushed;
+use crate::storage::RaftLogReader;
+use crate::storage::RaftSnapshotBuilder;
+use crate::storage::Snapshot;
+use crate::EffectiveMembership;
+use crate::Entry;
+use crate::LogId;
+use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
+use crate::StorageError;
+use crate::Vote;
+
+/// A trait defining the interface for the storage of vote and logs.
+///
+/// All methods are called inside of Raft core task. There is no concurrency on it, except concurrency with the log
+/// readers created by [`RaftLogStorage::get_log_reader`].
+///
+/// See [`RaftStorage`](crate::RaftStorage) for the details of every method, which have the same semantics.
+#[async_trait]
+pub trait RaftLogStorage<C>: RaftLogReader<C> + Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// Log reader type.
+    type LogReader: RaftLogReader<C>;
+
+    /// Save vote to storage.
+    ///
+    /// The vote must be persisted on disk before returning.
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>>;
+
+    /// Get the log reader.
+    ///
+    /// The method is intentionally async to give the implementation a chance to use asynchronous
+    /// sync primitives to serialize access to the common internal object, if needed.
+    async fn get_log_reader(&mut self) -> Self::LogReader;
+
+    /// Append a payload of entries to the log.
+    ///
+    /// Though the entries will always be presented in order, each entry's index should be used to
+    /// determine its location to be written in the log.
+    ///
+    /// The entries do not have to be persisted when this method returns, but they must be readable by the log
+    /// readers. When the entries are persisted on disk, the implementation must call
+    /// [`LogFlushed::log_io_completed`], so that Raft can update the local progress. This allows an implementation to
+    /// flush several appends with one `fsync`, and allows the leader to replicate logs while they are being flushed.
+    ///
+    /// Logs must be persisted in the order they are appended.
+    async fn append(
+        &mut self,
+        entries: &[&Entry<C>],
+        callback: LogFlushed<C::NodeId>,
+    ) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Delete conflict log entries since `log_id`, inclusive.
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Delete applied log entries upto `log_id`, inclusive.
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>>;
+}
+
+/// A trait defining the interface for the state machine and its snapshot.
+///
+/// All methods are called inside of the task that applies committed logs, one at a time. There is no concurrency on
+/// it, except concurrency with the snapshot builders created by [`RaftStateMachine::get_snapshot_builder`].
+///
+/// See [`RaftStorage`](crate::RaftStorage) for the details of every method, which have the same semantics.
+#[async_trait]
+pub trait RaftStateMachine<C>: Send + Sync + 'static
+where C: RaftTypeConfig
+{
+    /// The storage engine's associated type used for exposing a snapshot for reading & writing.
+    type SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Send + Sync + Unpin + 'static;
+
+    /// Snapshot builder type.
+    type SnapshotBuilder: RaftSnapshotBuilder<C, Self::SnapshotData>;
+
+    /// Returns the last applied log id which is recorded in state machine, and the last applied membership config.
+    async fn applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>>;
+
+    /// Apply the given payload of entries to the state machine.
+    ///
+    /// It returns the result of applying every entry, in the same order as `entries`.
+    async fn apply(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>>;
+
+    /// Get the snapshot builder for the state machine.
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder;
+
+    /// Create a new blank snapshot, returning a writable handle to the snapshot object.
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>>;
+
+    /// Check whether a snapshot sent by the leader can be installed, before receiving its data.
+    ///
+    /// By default every snapshot is accepted.
+    async fn check_snapshot_meta(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<(), IncompatibleSnapshot> {
+        let _ = meta;
+        Ok(())
+    }
+
+    /// Install a snapshot which has finished streaming from the leader.
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+        snapshot: Box<Self::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>>;
+
+    /// Get a readable handle to the current snapshot, along with its metadata.
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData, C::SnapshotMetadata>>, StorageError<C::NodeId>>;
+}
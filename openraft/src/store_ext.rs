@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use crate::async_trait::async_trait;
 use crate::defensive::DefensiveCheckBase;
+use crate::error::IncompatibleSnapshot;
 use crate::membership::EffectiveMembership;
 use crate::storage::LogState;
 use crate::storage::RaftLogReader;
@@ -172,10 +173,18 @@ where
         self.inner().begin_receiving_snapshot().await
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn check_snapshot_meta(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
+    ) -> Result<(), IncompatibleSnapshot> {
+        self.inner().check_snapshot_meta(meta).await
+    }
+
     #[tracing::instrument(level = "trace", skip(self, snapshot))]
     async fn install_snapshot(
         &mut self,
-        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        meta: &SnapshotMeta<C::NodeId, C::Node, C::SnapshotMetadata>,
         snapshot: Box<Self::SnapshotData>,
     ) -> Result<(), StorageError<C::NodeId>> {
         self.inner().install_snapshot(meta, snapshot).await
@@ -184,7 +193,8 @@ where
     #[tracing::instrument(level = "trace", skip(self))]
     async fn get_current_snapshot(
         &mut self,
-    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData, C::SnapshotMetadata>>, StorageError<C::NodeId>>
+    {
         self.inner().get_current_snapshot().await
     }
 
@@ -231,7 +241,7 @@ impl<C: RaftTypeConfig, T: RaftStorage<C>> RaftSnapshotBuilder<C, T::SnapshotDat
     #[tracing::instrument(level = "trace", skip(self))]
     async fn build_snapshot(
         &mut self,
-    ) -> Result<Snapshot<C::NodeId, C::Node, T::SnapshotData>, StorageError<C::NodeId>> {
+    ) -> Result<Snapshot<C::NodeId, C::Node, T::SnapshotData, C::SnapshotMetadata>, StorageError<C::NodeId>> {
         self.inner.build_snapshot().await
     }
 }
@@ -0,0 +1,360 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use anyerror::AnyError;
+use tokio::sync::oneshot;
+
+use crate::storage::LogFlushed;
+use crate::storage::LogState;
+use crate::testing::suite::blank;
+use crate::testing::suite::run_fut;
+use crate::testing::suite::NODE_ID;
+use crate::testing::StoreBuilder;
+use crate::AppData;
+use crate::AppDataResponse;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::ErrorSubject;
+use crate::ErrorVerb;
+use crate::LeaderId;
+use crate::LogId;
+use crate::RaftLogStorage;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+use crate::StorageIOError;
+use crate::Vote;
+
+/// Test suite to ensure a [`RaftLogStorage`] impl works as expected, independent of the state machine.
+///
+/// Usage:
+///
+/// ```ignore
+/// LogStoreSuite::test_all(&builder)?;
+/// ```
+pub struct LogStoreSuite<C, LS>
+where
+    C: RaftTypeConfig,
+    LS: RaftLogStorage<C>,
+{
+    c: PhantomData<C>,
+    p: PhantomData<LS>,
+}
+
+impl<C, LS> LogStoreSuite<C, LS>
+where
+    C: RaftTypeConfig,
+    C::D: AppData + Debug,
+    C::R: AppDataResponse + Debug,
+    C::NodeId: From<u64>,
+    LS: RaftLogStorage<C>,
+{
+    pub fn test_all<B: StoreBuilder<C, LS>>(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        run_fut(builder.run_test(Self::save_vote))?;
+        run_fut(builder.run_test(Self::get_log_entries))?;
+        run_fut(builder.run_test(Self::try_get_log_entry))?;
+        run_fut(builder.run_test(Self::initial_logs))?;
+        run_fut(builder.run_test(Self::get_log_state))?;
+        run_fut(builder.run_test(Self::purge_logs_upto_0))?;
+        run_fut(builder.run_test(Self::purge_logs_upto_5))?;
+        run_fut(builder.run_test(Self::purge_logs_upto_20))?;
+        run_fut(builder.run_test(Self::delete_logs_since_11))?;
+        run_fut(builder.run_test(Self::delete_logs_since_0))?;
+        run_fut(builder.run_test(Self::append))?;
+
+        Ok(())
+    }
+
+    pub async fn save_vote(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        store
+            .save_vote(&Vote {
+                term: 100,
+                node_id: NODE_ID.into(),
+                committed: false,
+            })
+            .await?;
+
+        let got = store.read_vote().await?;
+
+        assert_eq!(
+            Some(Vote {
+                term: 100,
+                node_id: NODE_ID.into(),
+                committed: false,
+            }),
+            got,
+        );
+        Ok(())
+    }
+
+    pub async fn get_log_entries(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        tracing::info!("--- get start == stop");
+        {
+            let logs = store.get_log_entries(3..3).await?;
+            assert_eq!(logs.len(), 0, "expected no logs to be returned");
+        }
+
+        tracing::info!("--- get start < stop");
+        {
+            let logs = store.get_log_entries(5..7).await?;
+
+            assert_eq!(logs.len(), 2);
+            assert_eq!(logs[0].log_id, LogId::new(LeaderId::new(1, NODE_ID.into()), 5));
+            assert_eq!(logs[1].log_id, LogId::new(LeaderId::new(1, NODE_ID.into()), 6));
+        }
+
+        Ok(())
+    }
+
+    pub async fn try_get_log_entry(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)).await?;
+
+        let ent = store.try_get_log_entry(3).await?;
+        assert_eq!(
+            Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
+            ent.map(|x| x.log_id)
+        );
+
+        let ent = store.try_get_log_entry(0).await?;
+        assert_eq!(None, ent.map(|x| x.log_id));
+
+        let ent = store.try_get_log_entry(11).await?;
+        assert_eq!(None, ent.map(|x| x.log_id));
+
+        Ok(())
+    }
+
+    pub async fn initial_logs(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        let ent = store.try_get_log_entry(0).await?;
+        assert!(ent.is_none(), "store initialized");
+
+        Ok(())
+    }
+
+    pub async fn get_log_state(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        let st = store.get_log_state().await?;
+
+        assert_eq!(None, st.last_purged_log_id);
+        assert_eq!(None, st.last_log_id);
+
+        tracing::info!("--- only logs");
+        {
+            Self::append_logs(&mut store, &[&blank(0, 0), &blank(1, 1), &blank(1, 2)]).await?;
+
+            let st = store.get_log_state().await?;
+            assert_eq!(None, st.last_purged_log_id);
+            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
+        }
+
+        tracing::info!("--- delete log 0-0");
+        {
+            store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
+
+            let st = store.get_log_state().await?;
+            assert_eq!(
+                Some(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)),
+                st.last_purged_log_id
+            );
+            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
+        }
+
+        tracing::info!("--- delete all log");
+        {
+            store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)).await?;
+
+            let st = store.get_log_state().await?;
+            assert_eq!(
+                Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)),
+                st.last_purged_log_id
+            );
+            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
+        }
+
+        tracing::info!("--- delete advance last present logs");
+        {
+            store.purge_logs_upto(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)).await?;
+
+            let st = store.get_log_state().await?;
+            assert_eq!(
+                Some(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)),
+                st.last_purged_log_id
+            );
+            assert_eq!(Some(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)), st.last_log_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn purge_logs_upto_0(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- delete (-oo, 0]");
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
+
+        let logs = store.try_get_log_entries(0..100).await?;
+        assert_eq!(logs.len(), 10);
+        assert_eq!(logs[0].log_id.index, 1);
+
+        assert_eq!(
+            LogState {
+                last_purged_log_id: Some(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)),
+                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
+            },
+            store.get_log_state().await?
+        );
+        Ok(())
+    }
+
+    pub async fn purge_logs_upto_5(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- delete (-oo, 5]");
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)).await?;
+
+        let logs = store.try_get_log_entries(0..100).await?;
+        assert_eq!(logs.len(), 5);
+        assert_eq!(logs[0].log_id.index, 6);
+
+        assert_eq!(
+            LogState {
+                last_purged_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)),
+                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
+            },
+            store.get_log_state().await?
+        );
+        Ok(())
+    }
+
+    pub async fn purge_logs_upto_20(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- delete (-oo, 20]");
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)).await?;
+
+        let logs = store.try_get_log_entries(0..100).await?;
+        assert_eq!(logs.len(), 0);
+
+        assert_eq!(
+            LogState {
+                last_purged_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)),
+                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)),
+            },
+            store.get_log_state().await?
+        );
+        Ok(())
+    }
+
+    pub async fn delete_logs_since_11(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- delete [11, +oo)");
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.delete_conflict_logs_since(LogId::new(LeaderId::new(1, NODE_ID.into()), 11)).await?;
+
+        let logs = store.try_get_log_entries(0..100).await?;
+        assert_eq!(logs.len(), 11);
+
+        assert_eq!(
+            LogState {
+                last_purged_log_id: None,
+                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
+            },
+            store.get_log_state().await?
+        );
+        Ok(())
+    }
+
+    pub async fn delete_logs_since_0(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- delete [0, +oo)");
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.delete_conflict_logs_since(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
+
+        let logs = store.try_get_log_entries(0..100).await?;
+        assert_eq!(logs.len(), 0);
+
+        assert_eq!(
+            LogState {
+                last_purged_log_id: None,
+                last_log_id: None,
+            },
+            store.get_log_state().await?
+        );
+
+        Ok(())
+    }
+
+    pub async fn append(mut store: LS) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
+
+        Self::append_logs(&mut store, &[&blank(2, 10)]).await?;
+
+        let l = store.try_get_log_entries(0..).await?.len();
+        let last = store.try_get_log_entries(0..).await?.last().cloned().unwrap();
+
+        assert_eq!(l, 10, "expected 10 entries to exist in the log");
+        assert_eq!(
+            last.log_id,
+            LogId::new(LeaderId::new(2, NODE_ID.into()), 10),
+            "unexpected log id"
+        );
+        Ok(())
+    }
+
+    pub async fn feed_10_logs_vote_self(sto: &mut LS) -> Result<(), StorageError<C::NodeId>> {
+        Self::append_logs(sto, &[&blank(0, 0)]).await?;
+
+        for i in 1..=10 {
+            Self::append_logs(sto, &[&Entry {
+                log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), i),
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+        }
+
+        Self::default_vote(sto).await?;
+
+        Ok(())
+    }
+
+    /// Append logs and wait until they are flushed.
+    async fn append_logs(sto: &mut LS, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        let last_log_id = entries.last().map(|e| e.log_id);
+
+        let (tx, rx) = oneshot::channel();
+        let callback = LogFlushed::new(last_log_id, tx);
+
+        sto.append(entries, callback).await?;
+
+        let res = rx.await.map_err(|_e| {
+            StorageIOError::new(
+                ErrorSubject::Logs,
+                ErrorVerb::Write,
+                AnyError::error("LogFlushed callback is dropped"),
+            )
+        })?;
+        res.map_err(|e| StorageError::from_io_error(ErrorSubject::Logs, ErrorVerb::Write, e))?;
+
+        Ok(())
+    }
+
+    pub async fn default_vote(sto: &mut LS) -> Result<(), StorageError<C::NodeId>> {
+        sto.save_vote(&Vote {
+            term: 1,
+            node_id: NODE_ID.into(),
+            committed: false,
+        })
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,20 @@
+/// Helper to construct a `BTreeSet` of `C::NodeId` from numbers.
+macro_rules! btreeset {
+    ($($key:expr,)+) => (btreeset!($($key),+));
+    ( $($key:expr),* ) => {{
+        let mut _set = ::std::collections::BTreeSet::new();
+        $( _set.insert($key.into()); )*
+        _set
+    }};
+}
+
+mod log_store_suite;
+mod state_machine_suite;
 mod store_builder;
 mod suite;
 
+pub use log_store_suite::LogStoreSuite;
+pub use state_machine_suite::StateMachineSuite;
 pub use store_builder::DefensiveStoreBuilder;
 pub use store_builder::StoreBuilder;
 pub use suite::Suite;
@@ -10,5 +24,6 @@ use crate::BasicNode;
 crate::declare_raft_types!(
     /// Dummy Raft types for the purpose of testing internal structures requiring
     /// `RaftTypeConfig`, like `MembershipConfig`.
-    pub(crate) DummyConfig: D = u64, R = u64, NodeId = u64, Node = BasicNode
+    pub(crate) DummyConfig: D = u64, R = u64, NodeId = u64, Node = BasicNode,
+        QuorumSet = crate::quorum::Majority<u64>, SnapshotMetadata = ()
 );
@@ -0,0 +1,151 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::membership::EffectiveMembership;
+use crate::testing::suite::blank;
+use crate::testing::suite::log_id;
+use crate::testing::suite::run_fut;
+use crate::testing::suite::NODE_ID;
+use crate::testing::StoreBuilder;
+use crate::AppData;
+use crate::AppDataResponse;
+use crate::Entry;
+use crate::EntryPayload;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::RaftSnapshotBuilder;
+use crate::RaftStateMachine;
+use crate::RaftTypeConfig;
+use crate::StorageError;
+
+/// Test suite to ensure a [`RaftStateMachine`] impl works as expected, independent of the log store.
+///
+/// Usage:
+///
+/// ```ignore
+/// StateMachineSuite::test_all(&builder)?;
+/// ```
+pub struct StateMachineSuite<C, SM>
+where
+    C: RaftTypeConfig,
+    SM: RaftStateMachine<C>,
+{
+    c: PhantomData<C>,
+    p: PhantomData<SM>,
+}
+
+impl<C, SM> StateMachineSuite<C, SM>
+where
+    C: RaftTypeConfig,
+    C::D: AppData + Debug,
+    C::R: AppDataResponse + Debug,
+    C::NodeId: From<u64>,
+    SM: RaftStateMachine<C>,
+{
+    pub fn test_all<B: StoreBuilder<C, SM>>(builder: &B) -> Result<(), StorageError<C::NodeId>> {
+        run_fut(builder.run_test(Self::applied_state))?;
+        run_fut(builder.run_test(Self::snapshot_meta))?;
+
+        Ok(())
+    }
+
+    pub async fn applied_state(mut store: SM) -> Result<(), StorageError<C::NodeId>> {
+        let (applied, membership) = store.applied_state().await?;
+        assert_eq!(None, applied);
+        assert_eq!(EffectiveMembership::default(), membership);
+
+        tracing::info!("--- with last_applied and last_membership");
+        {
+            store
+                .apply(&[&Entry {
+                    log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
+                    payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
+                }])
+                .await?;
+
+            let (applied, membership) = store.applied_state().await?;
+            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)), applied);
+            assert_eq!(
+                EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
+                    Membership::new(vec![btreeset! {1,2}], None)
+                ),
+                membership
+            );
+        }
+
+        tracing::info!("--- no logs, return default");
+        {
+            store
+                .apply(&[&Entry {
+                    log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 5),
+                    payload: EntryPayload::Blank,
+                }])
+                .await?;
+
+            let (applied, membership) = store.applied_state().await?;
+            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)), applied);
+            assert_eq!(
+                EffectiveMembership::new(
+                    Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
+                    Membership::new(vec![btreeset! {1,2}], None)
+                ),
+                membership
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn snapshot_meta(mut store: SM) -> Result<(), StorageError<C::NodeId>> {
+        tracing::info!("--- just initialized");
+        {
+            store
+                .apply(&[
+                    //
+                    &Entry {
+                        log_id: log_id(0, 0),
+                        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
+                    },
+                ])
+                .await?;
+
+            let mut b = store.get_snapshot_builder().await;
+            let snap = b.build_snapshot().await?;
+            let meta = snap.meta;
+            assert_eq!(Some(log_id(0, 0)), meta.last_log_id);
+            assert_eq!(Some(log_id(0, 0)), meta.last_membership.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {1,2}], None),
+                meta.last_membership.membership
+            );
+        }
+
+        tracing::info!("--- one app log, one membership log");
+        {
+            store
+                .apply(&[
+                    //
+                    &blank(1, 1),
+                    &Entry {
+                        log_id: log_id(2, 2),
+                        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4}], None)),
+                    },
+                ])
+                .await?;
+
+            let mut b = store.get_snapshot_builder().await;
+            let snap = b.build_snapshot().await?;
+            let meta = snap.meta;
+            assert_eq!(Some(log_id(2, 2)), meta.last_log_id);
+            assert_eq!(Some(log_id(2, 2)), meta.last_membership.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {3,4}], None),
+                meta.last_membership.membership
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -12,12 +12,13 @@ use crate::RaftTypeConfig;
 use crate::StorageError;
 use crate::StoreExt;
 
-/// The trait to build a [`RaftStorage`] implementation.
+/// The trait to build a store for testing, e.g., a [`RaftStorage`], a [`RaftLogStorage`](crate::RaftLogStorage) or a
+/// [`RaftStateMachine`](crate::RaftStateMachine) implementation.
 #[async_trait]
 pub trait StoreBuilder<C, S>: Send + Sync
 where
     C: RaftTypeConfig,
-    S: RaftStorage<C>,
+    S: Send + Sync + 'static,
 {
     async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<C::NodeId>>
     where
@@ -31,7 +32,7 @@ where
     C: RaftTypeConfig,
     C::D: AppData + Debug,
     C::R: AppDataResponse + Debug,
-    BaseStore: RaftStorage<C>,
+    BaseStore: RaftStorage<C> + Clone,
     BaseBuilder: StoreBuilder<C, BaseStore>,
 {
     pub base_builder: BaseBuilder,
@@ -46,7 +47,7 @@ where
     C: RaftTypeConfig,
     C::D: AppData + Debug,
     C::R: AppDataResponse + Debug,
-    BaseStore: RaftStorage<C>,
+    BaseStore: RaftStorage<C> + Clone,
     BaseBuilder: StoreBuilder<C, BaseStore>,
 {
     async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<C::NodeId>>
@@ -3,14 +3,14 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::option::Option::None;
 
-use maplit::btreeset;
-
 use crate::membership::EffectiveMembership;
 use crate::raft_state::LogStateReader;
 use crate::raft_state::RaftState;
-use crate::storage::LogState;
+use crate::storage::Adaptor;
 use crate::storage::StorageHelper;
 use crate::testing::DefensiveStoreBuilder;
+use crate::testing::LogStoreSuite;
+use crate::testing::StateMachineSuite;
 use crate::testing::StoreBuilder;
 use crate::AppData;
 use crate::AppDataResponse;
@@ -22,34 +22,26 @@ use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::NodeId;
-use crate::RaftSnapshotBuilder;
 use crate::RaftStorage;
 use crate::RaftTypeConfig;
 use crate::StorageError;
 use crate::Violation;
 use crate::Vote;
 
-const NODE_ID: u64 = 0;
-
-/// Helper to consturct a `BTreeSet` of `C::NodeId` from numbers.
-macro_rules! btreeset {
-    ($($key:expr,)+) => (btreeset!($($key),+));
-    ( $($key:expr),* ) => {{
-        let mut _set = ::std::collections::BTreeSet::new();
-        $( _set.insert($key.into()); )*
-        _set
-    }};
-}
+pub(crate) const NODE_ID: u64 = 0;
 
 /// Test suite to ensure a `RaftStore` impl works as expected.
 ///
+/// The tests of the log store part and the state machine part are run with [`LogStoreSuite`] and
+/// [`StateMachineSuite`], on a `RaftStorage` that is split by [`Adaptor`].
+///
 /// Usage:
 pub struct Suite<C, S, B>
 where
     C: RaftTypeConfig,
     C::D: AppData + Debug,
     C::R: AppDataResponse + Debug,
-    S: RaftStorage<C>,
+    S: RaftStorage<C> + Clone,
     B: StoreBuilder<C, S>,
 {
     c: PhantomData<C>,
@@ -63,7 +55,7 @@ where
     C::D: AppData + Debug,
     C::R: AppDataResponse + Debug,
     C::NodeId: From<u64>,
-    S: RaftStorage<C>,
+    S: RaftStorage<C> + Clone,
     B: StoreBuilder<C, S>,
 {
     pub fn test_all(builder: B) -> Result<(), StorageError<C::NodeId>> {
@@ -93,21 +85,23 @@ where
         run_fut(builder.run_test(Self::get_initial_state_last_log_gt_sm))?;
         run_fut(builder.run_test(Self::get_initial_state_last_log_lt_sm))?;
         run_fut(builder.run_test(Self::get_initial_state_log_ids))?;
-        run_fut(builder.run_test(Self::save_vote))?;
-        run_fut(builder.run_test(Self::get_log_entries))?;
-        run_fut(builder.run_test(Self::try_get_log_entry))?;
-        run_fut(builder.run_test(Self::initial_logs))?;
-        run_fut(builder.run_test(Self::get_log_state))?;
         run_fut(builder.run_test(Self::get_log_id))?;
         run_fut(builder.run_test(Self::last_id_in_log))?;
-        run_fut(builder.run_test(Self::last_applied_state))?;
-        run_fut(builder.run_test(Self::purge_logs_upto_0))?;
-        run_fut(builder.run_test(Self::purge_logs_upto_5))?;
-        run_fut(builder.run_test(Self::purge_logs_upto_20))?;
-        run_fut(builder.run_test(Self::delete_logs_since_11))?;
-        run_fut(builder.run_test(Self::delete_logs_since_0))?;
-        run_fut(builder.run_test(Self::append_to_log))?;
-        run_fut(builder.run_test(Self::snapshot_meta))?;
+
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::save_vote(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::get_log_entries(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::try_get_log_entry(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::initial_logs(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::get_log_state(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::purge_logs_upto_0(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::purge_logs_upto_5(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::purge_logs_upto_20(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::delete_logs_since_11(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::delete_logs_since_0(Self::log_store(s))))?;
+        run_fut(builder.run_test(|s| LogStoreSuite::<C, Adaptor<C, S>>::append(Self::log_store(s))))?;
+
+        run_fut(builder.run_test(|s| StateMachineSuite::<C, Adaptor<C, S>>::applied_state(Self::state_machine(s))))?;
+        run_fut(builder.run_test(|s| StateMachineSuite::<C, Adaptor<C, S>>::snapshot_meta(Self::state_machine(s))))?;
 
         // run_fut(Suite::apply_single(builder))?;
         // run_fut(Suite::apply_multi(builder))?;
@@ -117,8 +111,10 @@ where
         Ok(())
     }
 
-    pub async fn last_membership_in_log_initial(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let membership = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+    pub async fn last_membership_in_log_initial(store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
+        let membership = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
 
         assert!(membership.is_empty());
 
@@ -126,6 +122,8 @@ where
     }
 
     pub async fn last_membership_in_log(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- no log, do not read membership from state machine");
         {
             store
@@ -141,7 +139,7 @@ where
                 ])
                 .await?;
 
-            let mem = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+            let mem = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
 
             assert!(mem.is_empty());
         }
@@ -155,17 +153,17 @@ where
                 }])
                 .await?;
 
-            let mem = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+            let mem = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
             assert_eq!(1, mem.len());
             let mem = mem[0].clone();
             assert_eq!(Membership::new(vec![btreeset! {1, 2, 3}], None), mem.membership,);
 
-            let mem = StorageHelper::new(&mut store).last_membership_in_log(1).await?;
+            let mem = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(1).await?;
             assert_eq!(1, mem.len());
             let mem = mem[0].clone();
             assert_eq!(Membership::new(vec![btreeset! {1, 2, 3}], None), mem.membership,);
 
-            let mem = StorageHelper::new(&mut store).last_membership_in_log(2).await?;
+            let mem = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(2).await?;
             assert!(mem.is_empty());
         }
 
@@ -188,7 +186,7 @@ where
                 ])
                 .await?;
 
-            let mems = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+            let mems = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
             assert_eq!(2, mems.len());
 
             let mem = mems[0].clone();
@@ -200,7 +198,7 @@ where
 
         tracing::info!("--- membership presents in log and > sm.last_applied, read from log but since_index is greater than the last");
         {
-            let mem = StorageHelper::new(&mut store).last_membership_in_log(4).await?;
+            let mem = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(4).await?;
             assert!(mem.is_empty());
         }
 
@@ -213,7 +211,7 @@ where
                 }])
                 .await?;
 
-            let mems = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+            let mems = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
             assert_eq!(2, mems.len());
 
             let mem = mems[0].clone();
@@ -227,6 +225,8 @@ where
     }
 
     pub async fn last_membership_in_log_multi_step(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- find membership log entry backwards, multiple steps");
         {
             store
@@ -254,7 +254,7 @@ where
                 }])
                 .await?;
 
-            let mems = StorageHelper::new(&mut store).last_membership_in_log(0).await?;
+            let mems = StorageHelper::new(&mut ls, &mut sm).last_membership_in_log(0).await?;
             assert_eq!(2, mems.len());
             let mem = mems[0].clone();
             assert_eq!(Membership::new(vec![btreeset! {3,4,5}], None), mem.membership,);
@@ -266,8 +266,10 @@ where
         Ok(())
     }
 
-    pub async fn get_membership_initial(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+    pub async fn get_membership_initial(store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
+        let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
         assert_eq!(&EffectiveMembership::default(), mem_state.committed.as_ref());
         assert_eq!(&EffectiveMembership::default(), mem_state.effective.as_ref());
@@ -276,6 +278,8 @@ where
     }
 
     pub async fn get_membership_from_log_and_empty_sm(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- no log, read membership from state machine");
         {
             // There is an empty membership config in an empty state machine.
@@ -287,7 +291,7 @@ where
                 }])
                 .await?;
 
-            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+            let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
             assert_eq!(&EffectiveMembership::default(), mem_state.committed.as_ref());
             assert_eq!(
@@ -300,6 +304,8 @@ where
     }
 
     pub async fn get_membership_from_log_and_sm(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- no log, read membership from state machine");
         {
             store
@@ -315,7 +321,7 @@ where
                 ])
                 .await?;
 
-            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+            let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -336,7 +342,7 @@ where
                 }])
                 .await?;
 
-            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+            let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -363,7 +369,7 @@ where
                 ])
                 .await?;
 
-            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+            let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -390,7 +396,7 @@ where
                 ])
                 .await?;
 
-            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+            let mem_state = StorageHelper::new(&mut ls, &mut sm).get_membership().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {7,8,9}], None),
@@ -405,13 +411,17 @@ where
         Ok(())
     }
 
-    pub async fn get_initial_state_without_init(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+    pub async fn get_initial_state_without_init(store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
+        let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
         assert_eq!(RaftState::default(), initial, "uninitialized state");
         Ok(())
     }
 
     pub async fn get_initial_state_with_state(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         Self::default_vote(&mut store).await?;
 
         store
@@ -428,7 +438,7 @@ where
             }])
             .await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
         assert_eq!(
             initial.last_log_id().copied(),
@@ -453,6 +463,8 @@ where
     }
 
     pub async fn get_initial_state_membership_from_log_and_sm(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         // It should never return membership from logs that are included in state machine present.
 
         Self::default_vote(&mut store).await?;
@@ -474,7 +486,7 @@ where
                 ])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -491,7 +503,7 @@ where
                 }])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {3,4,5}], None),
@@ -508,7 +520,7 @@ where
                 }])
                 .await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {1,2,3}], None),
@@ -520,6 +532,8 @@ where
     }
 
     pub async fn get_initial_state_last_log_gt_sm(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         Self::default_vote(&mut store).await?;
 
         store
@@ -542,7 +556,7 @@ where
             ])
             .await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
         assert_eq!(
             initial.last_log_id().copied(),
@@ -553,13 +567,15 @@ where
     }
 
     pub async fn get_initial_state_last_log_lt_sm(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         Self::default_vote(&mut store).await?;
 
         store.append_to_log(&[&blank(1, 2)]).await?;
 
         store.apply_to_state_machine(&[&blank(3, 1)]).await?;
 
-        let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+        let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
 
         assert_eq!(
             initial.last_log_id().copied(),
@@ -575,6 +591,8 @@ where
     }
 
     pub async fn get_initial_state_log_ids(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         let log_id = |t, n: u64, i| LogId::<C::NodeId> {
             leader_id: LeaderId {
                 term: t,
@@ -585,7 +603,7 @@ where
 
         tracing::info!("--- empty store, expect []");
         {
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(Vec::<LogId<C::NodeId>>::new(), initial.log_ids.key_log_ids());
         }
 
@@ -593,7 +611,7 @@ where
         {
             store.append_to_log(&[&blank(0, 0)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(vec![log_id(0, 0, 0)], initial.log_ids.key_log_ids());
         }
 
@@ -601,7 +619,7 @@ where
         {
             store.append_to_log(&[&blank(1, 1), &blank(1, 2), &blank(2, 3)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![log_id(0, 0, 0), log_id(1, 0, 1), log_id(2, 0, 3)],
                 initial.log_ids.key_log_ids()
@@ -614,7 +632,7 @@ where
         {
             store.append_to_log(&[&blank(2, 4), &blank(3, 5), &blank(3, 6)]).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![
                     log_id(0, 0, 0),
@@ -633,7 +651,7 @@ where
         {
             store.purge_logs_upto(log_id(0, 0, 0)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![
                     log_id(0, 0, 0),
@@ -650,7 +668,7 @@ where
         {
             store.purge_logs_upto(log_id(1, 0, 1)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![log_id(1, 0, 1), log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -661,7 +679,7 @@ where
         {
             store.purge_logs_upto(log_id(1, 0, 2)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![log_id(1, 0, 2), log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -672,7 +690,7 @@ where
         {
             store.purge_logs_upto(log_id(2, 0, 3)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(
                 vec![log_id(2, 0, 3), log_id(3, 0, 5), log_id(3, 0, 6)],
                 initial.log_ids.key_log_ids()
@@ -683,152 +701,30 @@ where
         {
             store.purge_logs_upto(log_id(3, 0, 6)).await?;
 
-            let initial = StorageHelper::new(&mut store).get_initial_state().await?;
+            let initial = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await?;
             assert_eq!(vec![log_id(3, 0, 6)], initial.log_ids.key_log_ids());
         }
 
         Ok(())
     }
 
-    pub async fn save_vote(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        store
-            .save_vote(&Vote {
-                term: 100,
-                node_id: NODE_ID.into(),
-                committed: false,
-            })
-            .await?;
-
-        let got = store.read_vote().await?;
-
-        assert_eq!(
-            Some(Vote {
-                term: 100,
-                node_id: NODE_ID.into(),
-                committed: false,
-            }),
-            got,
-        );
-        Ok(())
-    }
-
-    pub async fn get_log_entries(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        tracing::info!("--- get start == stop");
-        {
-            let logs = store.get_log_entries(3..3).await?;
-            assert_eq!(logs.len(), 0, "expected no logs to be returned");
-        }
-
-        tracing::info!("--- get start < stop");
-        {
-            let logs = store.get_log_entries(5..7).await?;
-
-            assert_eq!(logs.len(), 2);
-            assert_eq!(logs[0].log_id, LogId::new(LeaderId::new(1, NODE_ID.into()), 5));
-            assert_eq!(logs[1].log_id, LogId::new(LeaderId::new(1, NODE_ID.into()), 6));
-        }
-
-        Ok(())
-    }
-
-    pub async fn try_get_log_entry(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.purge_logs_upto(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)).await?;
-
-        let ent = store.try_get_log_entry(3).await?;
-        assert_eq!(
-            Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
-            ent.map(|x| x.log_id)
-        );
-
-        let ent = store.try_get_log_entry(0).await?;
-        assert_eq!(None, ent.map(|x| x.log_id));
-
-        let ent = store.try_get_log_entry(11).await?;
-        assert_eq!(None, ent.map(|x| x.log_id));
-
-        Ok(())
-    }
-
-    pub async fn initial_logs(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let ent = store.try_get_log_entry(0).await?;
-        assert!(ent.is_none(), "store initialized");
-
-        Ok(())
-    }
-
-    pub async fn get_log_state(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let st = store.get_log_state().await?;
-
-        assert_eq!(None, st.last_purged_log_id);
-        assert_eq!(None, st.last_log_id);
-
-        tracing::info!("--- only logs");
-        {
-            store.append_to_log(&[&blank(0, 0), &blank(1, 1), &blank(1, 2)]).await?;
-
-            let st = store.get_log_state().await?;
-            assert_eq!(None, st.last_purged_log_id);
-            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
-        }
-
-        tracing::info!("--- delete log 0-0");
-        {
-            store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
-
-            let st = store.get_log_state().await?;
-            assert_eq!(
-                Some(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)),
-                st.last_purged_log_id
-            );
-            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
-        }
-
-        tracing::info!("--- delete all log");
-        {
-            store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)).await?;
-
-            let st = store.get_log_state().await?;
-            assert_eq!(
-                Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)),
-                st.last_purged_log_id
-            );
-            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 2)), st.last_log_id);
-        }
-
-        tracing::info!("--- delete advance last present logs");
-        {
-            store.purge_logs_upto(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)).await?;
-
-            let st = store.get_log_state().await?;
-            assert_eq!(
-                Some(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)),
-                st.last_purged_log_id
-            );
-            assert_eq!(Some(LogId::new(LeaderId::new(2, NODE_ID.into()), 3)), st.last_log_id);
-        }
-
-        Ok(())
-    }
-
     pub async fn get_log_id(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         Self::feed_10_logs_vote_self(&mut store).await?;
 
         store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)).await?;
 
-        let res = StorageHelper::new(&mut store).get_log_id(0).await;
+        let res = StorageHelper::new(&mut ls, &mut sm).get_log_id(0).await;
         assert!(res.is_err());
 
-        let res = StorageHelper::new(&mut store).get_log_id(11).await;
+        let res = StorageHelper::new(&mut ls, &mut sm).get_log_id(11).await;
         assert!(res.is_err());
 
-        let res = StorageHelper::new(&mut store).get_log_id(3).await?;
+        let res = StorageHelper::new(&mut ls, &mut sm).get_log_id(3).await?;
         assert_eq!(LogId::new(LeaderId::new(1, NODE_ID.into()), 3), res);
 
-        let res = StorageHelper::new(&mut store).get_log_id(4).await?;
+        let res = StorageHelper::new(&mut ls, &mut sm).get_log_id(4).await?;
         assert_eq!(LogId::new(LeaderId::new(1, NODE_ID.into()), 4), res);
 
         Ok(())
@@ -869,227 +765,6 @@ where
         Ok(())
     }
 
-    pub async fn last_applied_state(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        let (applied, membership) = store.last_applied_state().await?;
-        assert_eq!(None, applied);
-        assert_eq!(EffectiveMembership::default(), membership);
-
-        tracing::info!("--- with last_applied and last_membership");
-        {
-            store
-                .apply_to_state_machine(&[&Entry {
-                    log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 3),
-                    payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
-                }])
-                .await?;
-
-            let (applied, membership) = store.last_applied_state().await?;
-            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)), applied);
-            assert_eq!(
-                EffectiveMembership::new(
-                    Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
-                    Membership::new(vec![btreeset! {1,2}], None)
-                ),
-                membership
-            );
-        }
-
-        tracing::info!("--- no logs, return default");
-        {
-            store
-                .apply_to_state_machine(&[&Entry {
-                    log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), 5),
-                    payload: EntryPayload::Blank,
-                }])
-                .await?;
-
-            let (applied, membership) = store.last_applied_state().await?;
-            assert_eq!(Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)), applied);
-            assert_eq!(
-                EffectiveMembership::new(
-                    Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)),
-                    Membership::new(vec![btreeset! {1,2}], None)
-                ),
-                membership
-            );
-        }
-
-        Ok(())
-    }
-
-    pub async fn purge_logs_upto_0(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- delete (-oo, 0]");
-
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
-
-        let logs = store.try_get_log_entries(0..100).await?;
-        assert_eq!(logs.len(), 10);
-        assert_eq!(logs[0].log_id.index, 1);
-
-        assert_eq!(
-            LogState {
-                last_purged_log_id: Some(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)),
-                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
-            },
-            store.get_log_state().await?
-        );
-        Ok(())
-    }
-
-    pub async fn purge_logs_upto_5(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- delete (-oo, 5]");
-
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)).await?;
-
-        let logs = store.try_get_log_entries(0..100).await?;
-        assert_eq!(logs.len(), 5);
-        assert_eq!(logs[0].log_id.index, 6);
-
-        assert_eq!(
-            LogState {
-                last_purged_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 5)),
-                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
-            },
-            store.get_log_state().await?
-        );
-        Ok(())
-    }
-
-    pub async fn purge_logs_upto_20(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- delete (-oo, 20]");
-
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)).await?;
-
-        let logs = store.try_get_log_entries(0..100).await?;
-        assert_eq!(logs.len(), 0);
-
-        assert_eq!(
-            LogState {
-                last_purged_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)),
-                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 20)),
-            },
-            store.get_log_state().await?
-        );
-        Ok(())
-    }
-
-    pub async fn delete_logs_since_11(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- delete [11, +oo)");
-
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.delete_conflict_logs_since(LogId::new(LeaderId::new(1, NODE_ID.into()), 11)).await?;
-
-        let logs = store.try_get_log_entries(0..100).await?;
-        assert_eq!(logs.len(), 11);
-
-        assert_eq!(
-            LogState {
-                last_purged_log_id: None,
-                last_log_id: Some(LogId::new(LeaderId::new(1, NODE_ID.into()), 10)),
-            },
-            store.get_log_state().await?
-        );
-        Ok(())
-    }
-
-    pub async fn delete_logs_since_0(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- delete [0, +oo)");
-
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.delete_conflict_logs_since(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
-
-        let logs = store.try_get_log_entries(0..100).await?;
-        assert_eq!(logs.len(), 0);
-
-        assert_eq!(
-            LogState {
-                last_purged_log_id: None,
-                last_log_id: None,
-            },
-            store.get_log_state().await?
-        );
-
-        Ok(())
-    }
-
-    pub async fn append_to_log(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        Self::feed_10_logs_vote_self(&mut store).await?;
-
-        store.purge_logs_upto(LogId::new(LeaderId::new(0, NODE_ID.into()), 0)).await?;
-
-        store.append_to_log(&[&blank(2, 10)]).await?;
-
-        let l = store.try_get_log_entries(0..).await?.len();
-        let last = store.try_get_log_entries(0..).await?.last().cloned().unwrap();
-
-        assert_eq!(l, 10, "expected 10 entries to exist in the log");
-        assert_eq!(
-            last.log_id,
-            LogId::new(LeaderId::new(2, NODE_ID.into()), 10),
-            "unexpected log id"
-        );
-        Ok(())
-    }
-
-    pub async fn snapshot_meta(mut store: S) -> Result<(), StorageError<C::NodeId>> {
-        tracing::info!("--- just initialized");
-        {
-            store
-                .apply_to_state_machine(&[
-                    //
-                    &Entry {
-                        log_id: log_id(0, 0),
-                        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2}], None)),
-                    },
-                ])
-                .await?;
-
-            let mut b = store.get_snapshot_builder().await;
-            let snap = b.build_snapshot().await?;
-            let meta = snap.meta;
-            assert_eq!(Some(log_id(0, 0)), meta.last_log_id);
-            assert_eq!(Some(log_id(0, 0)), meta.last_membership.log_id);
-            assert_eq!(
-                Membership::new(vec![btreeset! {1,2}], None),
-                meta.last_membership.membership
-            );
-        }
-
-        tracing::info!("--- one app log, one membership log");
-        {
-            store
-                .apply_to_state_machine(&[
-                    //
-                    &blank(1, 1),
-                    &Entry {
-                        log_id: log_id(2, 2),
-                        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {3,4}], None)),
-                    },
-                ])
-                .await?;
-
-            let mut b = store.get_snapshot_builder().await;
-            let snap = b.build_snapshot().await?;
-            let meta = snap.meta;
-            assert_eq!(Some(log_id(2, 2)), meta.last_log_id);
-            assert_eq!(Some(log_id(2, 2)), meta.last_membership.log_id);
-            assert_eq!(
-                Membership::new(vec![btreeset! {3,4}], None),
-                meta.last_membership.membership
-            );
-        }
-
-        Ok(())
-    }
-
     // pub async fn apply_single(mut store: S) -> Result<(), StorageError<C::NodeId>> {
 
     //
@@ -1205,30 +880,21 @@ where
     // }
 
     pub async fn feed_10_logs_vote_self(sto: &mut S) -> Result<(), StorageError<C::NodeId>> {
-        sto.append_to_log(&[&blank(0, 0)]).await?;
-
-        for i in 1..=10 {
-            sto.append_to_log(&[&Entry {
-                log_id: LogId::new(LeaderId::new(1, NODE_ID.into()), i),
-                payload: EntryPayload::Blank,
-            }])
-            .await?;
-        }
-
-        Self::default_vote(sto).await?;
-
-        Ok(())
+        LogStoreSuite::<C, Adaptor<C, S>>::feed_10_logs_vote_self(&mut Self::log_store(sto.clone())).await
     }
 
     pub async fn default_vote(sto: &mut S) -> Result<(), StorageError<C::NodeId>> {
-        sto.save_vote(&Vote {
-            term: 1,
-            node_id: NODE_ID.into(),
-            committed: false,
-        })
-        .await?;
+        LogStoreSuite::<C, Adaptor<C, S>>::default_vote(&mut Self::log_store(sto.clone())).await
+    }
 
-        Ok(())
+    /// The log store part of a `RaftStorage`.
+    fn log_store(store: S) -> Adaptor<C, S> {
+        Adaptor::new(store).0
+    }
+
+    /// The state machine part of a `RaftStorage`.
+    fn state_machine(store: S) -> Adaptor<C, S> {
+        Adaptor::new(store).1
     }
 }
 
@@ -1241,7 +907,7 @@ where
     C::D: AppData + Debug,
     C::R: AppDataResponse + Debug,
     C::NodeId: From<u64>,
-    S: RaftStorage<C>,
+    S: RaftStorage<C> + Clone,
     B: StoreBuilder<C, S>,
 {
     pub fn test_store_defensive(builder: &B) -> Result<(), StorageError<C::NodeId>> {
@@ -1265,6 +931,8 @@ where
     }
 
     pub async fn df_get_membership_config_dirty_log(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- dirty log: log.index > last_applied.index && log < last_applied");
         {
             store
@@ -1280,7 +948,7 @@ where
                 }])
                 .await?;
 
-            let res = StorageHelper::new(&mut store).get_membership().await;
+            let res = StorageHelper::new(&mut ls, &mut sm).get_membership().await;
 
             let e = res.unwrap_err().into_defensive().unwrap();
             assert!(matches!(e, DefensiveError {
@@ -1315,6 +983,8 @@ where
     }
 
     pub async fn df_get_initial_state_dirty_log(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let (mut ls, mut sm) = Adaptor::new(store.clone());
+
         tracing::info!("--- dirty log: log.index > last_applied.index && log < last_applied");
         {
             store
@@ -1331,7 +1001,7 @@ where
                 }])
                 .await?;
 
-            let state = StorageHelper::new(&mut store).get_initial_state().await;
+            let state = StorageHelper::new(&mut ls, &mut sm).get_initial_state().await;
             let e = state.unwrap_err().into_defensive().unwrap();
 
             assert!(matches!(e, DefensiveError {
@@ -1789,7 +1459,7 @@ where
     }
 }
 
-fn log_id<NID: NodeId>(term: u64, index: u64) -> LogId<NID>
+pub(crate) fn log_id<NID: NodeId>(term: u64, index: u64) -> LogId<NID>
 where NID: From<u64> {
     LogId {
         leader_id: LeaderId {
@@ -1801,7 +1471,7 @@ where NID: From<u64> {
 }
 
 /// Create a blank log entry for test
-fn blank<C: RaftTypeConfig>(term: u64, index: u64) -> Entry<C>
+pub(crate) fn blank<C: RaftTypeConfig>(term: u64, index: u64) -> Entry<C>
 where C::NodeId: From<u64> {
     Entry {
         log_id: LogId::new(LeaderId::new(term, NODE_ID.into()), index),
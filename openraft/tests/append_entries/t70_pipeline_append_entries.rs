@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Replicate logs with multiple inflight AppendEntries requests per target.
+///
+/// What does this test do?
+///
+/// - bring on a cluster of 3 voters and 1 learner, with `max_inflight_append_requests=4` and a small payload size.
+/// - write logs with a random network delay, so that requests may arrive at the target out of order.
+/// - all logs are replicated to every node.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pipeline_append_entries() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_payload_entries: 3,
+            max_inflight_append_requests: 4,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    tracing::info!("--- write logs with random network delay");
+    {
+        router.network_send_delay(20);
+
+        let n = 100;
+        router.client_request_many(0, "0", n).await?;
+        log_index += n as u64;
+
+        router
+            .wait_for_log(
+                &btreeset![0, 1, 2, 3],
+                Some(log_index),
+                timeout(),
+                "replicate logs with multiple inflight requests",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(5_000))
+}
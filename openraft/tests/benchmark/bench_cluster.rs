@@ -14,14 +14,20 @@ struct BenchConfig {
     pub worker_threads: usize,
     pub n_operations: usize,
     pub members: BTreeSet<u64>,
+
+    /// The number of clients sending write requests concurrently.
+    pub n_client: usize,
+
+    /// `Config::max_write_batch_entries`, 1 disables batching client write requests.
+    pub max_write_batch_entries: u64,
 }
 
 impl Display for BenchConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "worker: {}, n: {}, raft_members: {:?}",
-            self.worker_threads, self.n_operations, self.members
+            "worker: {}, n: {}, raft_members: {:?}, client: {}, write_batch: {}",
+            self.worker_threads, self.n_operations, self.members, self.n_client, self.max_write_batch_entries
         )
     }
 }
@@ -33,6 +39,8 @@ fn bench_cluster_of_1() -> anyhow::Result<()> {
         worker_threads: 8,
         n_operations: 100_000,
         members: btreeset! {0},
+        n_client: 1,
+        max_write_batch_entries: 1,
     })?;
     Ok(())
 }
@@ -44,6 +52,8 @@ fn bench_cluster_of_3() -> anyhow::Result<()> {
         worker_threads: 8,
         n_operations: 100_000,
         members: btreeset! {0,1,2},
+        n_client: 1,
+        max_write_batch_entries: 1,
     })?;
     Ok(())
 }
@@ -55,6 +65,36 @@ fn bench_cluster_of_5() -> anyhow::Result<()> {
         worker_threads: 8,
         n_operations: 100_000,
         members: btreeset! {0,1,2,3,4},
+        n_client: 1,
+        max_write_batch_entries: 1,
+    })?;
+    Ok(())
+}
+
+/// Concurrent client writes with client write requests appended in batch.
+#[test]
+#[ignore]
+fn bench_concurrent_write_batch() -> anyhow::Result<()> {
+    bench_with_config(&BenchConfig {
+        worker_threads: 8,
+        n_operations: 100_000,
+        members: btreeset! {0,1,2},
+        n_client: 256,
+        max_write_batch_entries: 256,
+    })?;
+    Ok(())
+}
+
+/// Concurrent client writes with every client write request appended alone, to compare with the batched one.
+#[test]
+#[ignore]
+fn bench_concurrent_no_write_batch() -> anyhow::Result<()> {
+    bench_with_config(&BenchConfig {
+        worker_threads: 8,
+        n_operations: 100_000,
+        members: btreeset! {0,1,2},
+        n_client: 256,
+        max_write_batch_entries: 1,
     })?;
     Ok(())
 }
@@ -71,7 +111,7 @@ fn bench_with_config(bench_config: &BenchConfig) -> anyhow::Result<()> {
     rt.block_on(do_bench(bench_config))
 }
 
-/// Benchmark client_write.
+/// Benchmark client_write, with `n_client` clients each of which writes `n_operations / n_client` entries.
 ///
 /// Cluster config:
 /// - Log: in-memory BTree
@@ -82,6 +122,7 @@ async fn do_bench(bench_config: &BenchConfig) -> anyhow::Result<()> {
             election_timeout_min: 200,
             election_timeout_max: 2000,
             purge_batch_size: 1024,
+            max_write_batch_entries: bench_config.max_write_batch_entries,
             ..Default::default()
         }
         .validate()?,
@@ -93,8 +134,22 @@ async fn do_bench(bench_config: &BenchConfig) -> anyhow::Result<()> {
 
     let now = Instant::now();
 
-    for i in 0..n {
-        router.client_request(0, "foo", i as u64).await?;
+    let mut handles = Vec::with_capacity(bench_config.n_client);
+    for c in 0..bench_config.n_client {
+        let router = router.clone();
+        let n_per_client = n / bench_config.n_client;
+
+        handles.push(tokio::spawn(async move {
+            let client_id = format!("foo-{}", c);
+            for i in 0..n_per_client {
+                router.client_request(0, &client_id, i as u64).await?;
+            }
+            Ok::<(), anyhow::Error>(())
+        }));
+    }
+
+    for h in handles {
+        h.await??;
     }
 
     let elapsed = now.elapsed();
@@ -107,5 +162,13 @@ async fn do_bench(bench_config: &BenchConfig) -> anyhow::Result<()> {
         (n as u128) / elapsed.as_millis(),
     );
 
+    let latency = router.get_raft_handle(&0)?.latency_metrics();
+    println!("    append:  {}", latency.append);
+    println!("    commit:  {}", latency.commit);
+    println!("    apply:   {}", latency.apply);
+    for (target, h) in latency.append_entries.iter() {
+        println!("    append_entries to {}: {}", target, h);
+    }
+
     Ok(())
 }
@@ -9,5 +9,7 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_client_writes;
+mod t11_client_write_many;
+mod t12_client_write_batch;
 mod t20_client_reads;
 mod t50_lagging_network_write;
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Write a batch of client requests with `client_write_many()`.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a batch to the leader: the entries are appended as consecutive logs and all responses are returned.
+/// - write an empty batch: it returns no response.
+/// - write a batch to a follower: it fails as a whole with `ForwardToLeader`.
+#[async_entry::test(worker_threads = 4, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_many() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    tracing::info!("--- write a batch to the leader");
+    {
+        let reqs = (0..10).map(|i| ClientRequest::make_request("foo", i)).collect::<Vec<_>>();
+        let resps = n0.client_write_many(reqs).await?;
+
+        assert_eq!(10, resps.len());
+        for (i, resp) in resps.iter().enumerate() {
+            assert_eq!(log_index + 1 + i as u64, resp.log_id.index);
+            assert!(resp.membership.is_none());
+        }
+
+        log_index += 10;
+        router.wait_for_log(&btreeset![0, 1, 2], Some(log_index), None, "write a batch").await?;
+    }
+
+    tracing::info!("--- write an empty batch");
+    {
+        let resps = n0.client_write_many(vec![]).await?;
+        assert!(resps.is_empty());
+    }
+
+    tracing::info!("--- write a batch to a follower");
+    {
+        let reqs = (0..10).map(|i| ClientRequest::make_request("bar", i)).collect::<Vec<_>>();
+        let res = n1.client_write_many(reqs).await;
+
+        match &res {
+            Err(ClientWriteError::ForwardToLeader(fwd)) => {
+                assert_eq!(Some(0), fwd.leader_id);
+            }
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(Some(log_index), m.last_log_index, "nothing is written");
+    }
+
+    Ok(())
+}
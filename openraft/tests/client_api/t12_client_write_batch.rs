@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::Config as MemConfig;
+use openraft::Config;
+use openraft::EntryPayload;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Client write requests queued in `RaftCore` are appended to the log in batches.
+///
+/// What does this test do?
+///
+/// - create a single node cluster.
+/// - block `RaftCore` and queue 10 small client write requests: they are appended with one storage call.
+/// - block `RaftCore` and queue 10 requests with a large payload: every batch is bounded by `max_write_batch_bytes`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_batch() -> Result<()> {
+    let small = EntryPayload::<MemConfig>::Normal(request(0, 10)).estimated_size();
+    let large = EntryPayload::<MemConfig>::Normal(request(0, 1_000)).estimated_size();
+
+    // A batch holds 10 small payloads, or 2 large payloads.
+    let max_write_batch_bytes = large * 5 / 2;
+    assert!(small * 10 <= max_write_batch_bytes);
+
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            // A `Tick` queued among the client write requests ends a batch.
+            enable_tick: false,
+            max_write_batch_bytes,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    tracing::info!("--- initializing cluster");
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let sto0 = router.get_storage_handle(&0)?;
+
+    tracing::info!("--- queued small requests are appended in one batch");
+    {
+        let before = sto0.append_batches().len();
+
+        write_queued(&router, 10, 10).await?;
+        log_index += 10;
+        n0.wait(timeout()).log(Some(log_index), "write 10 logs").await?;
+
+        assert_eq!(vec![10], sto0.append_batches()[before..].to_vec());
+    }
+
+    tracing::info!("--- queued large requests are split by max_write_batch_bytes");
+    {
+        let before = sto0.append_batches().len();
+
+        write_queued(&router, 10, 1_000).await?;
+        log_index += 10;
+        n0.wait(timeout()).log(Some(log_index), "write 10 large logs").await?;
+
+        assert_eq!(vec![2, 2, 2, 2, 2], sto0.append_batches()[before..].to_vec());
+    }
+
+    Ok(())
+}
+
+/// Block `RaftCore` with an external request, queue `n` client write requests with a `size` bytes status,
+/// and wait for all of them to be applied.
+async fn write_queued(router: &RaftRouter, n: u64, size: usize) -> Result<()> {
+    let n0 = router.get_raft_handle(&0)?;
+
+    n0.external_request(|_st, _sto, _net| std::thread::sleep(Duration::from_millis(500)));
+
+    let mut handles = vec![];
+    for i in 0..n {
+        let n0 = n0.clone();
+        let req = request(i, size);
+        handles.push(tokio::spawn(async move { n0.client_write(req).await }));
+
+        // Keep the requests in order in the queue.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    for h in handles {
+        h.await??;
+    }
+
+    Ok(())
+}
+
+fn request(serial: u64, size: usize) -> ClientRequest {
+    ClientRequest {
+        client: "foo".to_string(),
+        serial,
+        status: "x".repeat(size),
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
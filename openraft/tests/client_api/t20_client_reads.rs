@@ -3,6 +3,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use maplit::btreeset;
 use openraft::Config;
+use openraft::LogIdOptionExt;
 use openraft::ServerState;
 
 use crate::fixtures::init_default_ut_tracing;
@@ -15,6 +16,7 @@ use crate::fixtures::RaftRouter;
 /// - create a stable 3-node cluster.
 /// - call the is_leader interface on the leader, and assert success.
 /// - call the is_leader interface on the followers, and assert failure.
+/// - call the read_index interface on the leader, and assert it returns the last committed log id.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn client_reads() -> Result<()> {
     let config = Arc::new(
@@ -59,6 +61,14 @@ async fn client_reads() -> Result<()> {
     router.is_leader(1).await.expect_err("expected is_leader on follower node 1 to fail");
     router.is_leader(2).await.expect_err("expected is_leader on follower node 2 to fail");
 
+    tracing::info!("--- read_index returns the committed log id on leader");
+    {
+        let read_log_id = router.read_index(leader).await?;
+        assert_eq!(Some(log_index), read_log_id.index());
+
+        router.read_index(1).await.expect_err("expected read_index on follower node 1 to fail");
+    }
+
     tracing::info!("--- isolate node 1 then is_leader should work");
 
     router.isolate_node(1);
@@ -9,3 +9,7 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_elect_compare_last_log;
+mod t20_transfer_leader;
+mod t21_transfer_leader_retry;
+mod t30_check_quorum;
+mod t40_pre_vote_rejoin;
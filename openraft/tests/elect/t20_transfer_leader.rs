@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::NotAVoter;
+use openraft::error::TransferLeaderError;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Transfer leadership to another voter.
+///
+/// - Bring up a cluster of 3 voters and 1 learner.
+/// - Transferring leadership to a learner is refused.
+/// - Transferring leadership to node 1 makes node 1 the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transfer_leader() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- transfer to a learner is refused");
+    {
+        let res = n0.transfer_leader(3).await;
+        assert_eq!(Err(TransferLeaderError::NotAVoter(NotAVoter { node_id: 3 })), res);
+    }
+
+    tracing::info!("--- transfer to node 1");
+    {
+        n0.transfer_leader(1).await?;
+
+        router
+            .wait(&1, Some(Duration::from_millis(2_000)))
+            .state(ServerState::Leader, "node 1 becomes leader")
+            .await?;
+
+        for id in [0, 2, 3] {
+            router
+                .wait(&id, Some(Duration::from_millis(2_000)))
+                .current_leader(1, "node 1 is seen as the leader")
+                .await?;
+        }
+    }
+
+    tracing::info!("--- the new leader accepts writes");
+    {
+        router.client_request_many(1, "foo", 1).await?;
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index + 2), None, "write to new leader").await?;
+    }
+
+    Ok(())
+}
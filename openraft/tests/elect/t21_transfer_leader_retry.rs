@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A failed TimeoutNow is resent, and the election it triggers is granted within the leader lease.
+///
+/// - Bring up a cluster of 3 voters with leader lease enabled.
+/// - Isolate node 1 and transfer leadership to it, the first TimeoutNow fails.
+/// - Restore node 1 before the transfer deadline, it is elected by a resent TimeoutNow, although node 2 is still within
+///   the lease it granted to node 0.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn transfer_leader_retry() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_leader_lease: true,
+            heartbeat_interval: 50,
+            election_timeout_min: 1_000,
+            election_timeout_max: 2_000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write a log to renew the lease granted by the followers");
+    {
+        log_index += router.client_request_many(0, "foo", 1).await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "write to node 0").await?;
+    }
+
+    tracing::info!("--- isolate node 1 and transfer leadership to it");
+    {
+        router.isolate_node(1);
+
+        // `transfer_leader()` returns only when node 1 becomes the leader, thus it runs in another task.
+        let n0 = router.get_raft_handle(&0)?;
+        let transfer = tokio::spawn(async move { n0.transfer_leader(1).await });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let m1 = router.get_metrics(&1)?;
+        assert_eq!(
+            ServerState::Follower,
+            m1.state,
+            "node 1 has not yet received TimeoutNow"
+        );
+
+        router.restore_node(1);
+
+        transfer.await??;
+    }
+
+    tracing::info!("--- node 1 becomes leader by a resent TimeoutNow");
+    {
+        router
+            .wait(&1, Some(Duration::from_millis(1_000)))
+            .state(ServerState::Leader, "node 1 becomes leader")
+            .await?;
+
+        for id in [0, 2] {
+            router
+                .wait(&id, Some(Duration::from_millis(1_000)))
+                .current_leader(1, "node 1 is seen as the leader")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader steps down if it can not contact a quorum.
+///
+/// - Bring up a cluster of 3 voters, with heartbeat and check-quorum enabled.
+/// - The leader keeps leading as long as heartbeats are acknowledged.
+/// - Isolate the leader: it reverts to follower and the pending client write fails with `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn check_quorum() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_check_quorum: true,
+            enable_elect: false,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    // A heartbeat appends a blank log, thus it is enabled after the cluster is initialized.
+    for id in [0, 1, 2] {
+        router.get_raft_handle(&id)?.enable_heartbeat(true);
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- leader keeps leading with heartbeats acknowledged");
+    {
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 3)).await;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(Some(0), m.current_leader);
+    }
+
+    tracing::info!("--- isolate the leader, it steps down");
+    {
+        router.isolate_node(0);
+
+        let write = tokio::spawn({
+            let n0 = n0.clone();
+            async move { n0.client_write(ClientRequest::make_request("foo", 1)).await }
+        });
+
+        n0.wait(timeout()).state(ServerState::Follower, "node 0 steps down").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(None, m.current_leader);
+
+        let res = write.await?;
+        match &res {
+            Err(ClientWriteError::ForwardToLeader(fwd)) => {
+                assert!(fwd.leader_id.is_none());
+            }
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A partitioned node does not depose a healthy leader when it rejoins, with PreVote enabled.
+///
+/// - Bring up a cluster of 3 voters with PreVote enabled.
+/// - Isolate follower node 2 for several election timeouts, without writing any log. Its log is as up-to-date as the
+///   others' when it rejoins.
+/// - Restore node 2: the other follower refuses its PreVote because it still hears from the leader, thus the leader and
+///   the term do not change.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pre_vote_rejoin() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_pre_vote: true,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    // A heartbeat appends a blank log, thus it is enabled after the cluster is initialized.
+    for id in [0, 1, 2] {
+        router.get_raft_handle(&id)?.enable_heartbeat(true);
+    }
+
+    let term = router.get_metrics(&0)?.current_term;
+
+    tracing::info!("--- isolate node 2 for several election timeouts");
+    {
+        router.isolate_node(2);
+        let log_index = router.get_metrics(&2)?.last_log_index;
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 3)).await;
+
+        let m = router.get_metrics(&2)?;
+        assert_eq!(term, m.current_term, "an isolated node does not increase its term");
+        assert_eq!(log_index, m.last_log_index);
+    }
+
+    tracing::info!("--- restore node 2, the leader stays");
+    {
+        router.restore_node(2);
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 3)).await;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(term, m.current_term);
+
+        for id in [1, 2] {
+            let m = router.get_metrics(&id)?;
+            assert_eq!(ServerState::Follower, m.state, "node {}", id);
+            assert_eq!(Some(0), m.current_leader, "node {}", id);
+            assert_eq!(term, m.current_term, "node {}", id);
+        }
+    }
+
+    Ok(())
+}
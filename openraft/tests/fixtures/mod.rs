@@ -34,6 +34,7 @@ use openraft::error::InstallSnapshotError;
 use openraft::error::NetworkError;
 use openraft::error::RPCError;
 use openraft::error::RemoteError;
+use openraft::error::TimeoutNowError;
 use openraft::error::VoteError;
 use openraft::metrics::Wait;
 use openraft::raft::AddLearnerResponse;
@@ -41,8 +42,11 @@ use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
+use openraft::storage::Adaptor;
 use openraft::storage::RaftLogReader;
 use openraft::storage::RaftStorage;
 use openraft::Config;
@@ -71,7 +75,8 @@ pub mod logging;
 pub type StoreWithDefensive<C = MemConfig, S = Arc<MemStore>> = StoreExt<C, S>;
 
 /// A concrete Raft type used during testing.
-pub type MemRaft<C = MemConfig, S = Arc<MemStore>> = Raft<C, TypedRaftRouter<C, S>, StoreWithDefensive<C, S>>;
+pub type MemRaft<C = MemConfig, S = Arc<MemStore>> =
+    Raft<C, TypedRaftRouter<C, S>, Adaptor<C, StoreWithDefensive<C, S>>, Adaptor<C, StoreWithDefensive<C, S>>>;
 
 pub fn init_default_ut_tracing() {
     static START: Once = Once::new();
@@ -129,7 +134,7 @@ pub fn log_panic(panic: &PanicInfo) {
 }
 
 /// A type which emulates a network transport and implements the `RaftNetworkFactory` trait.
-pub struct TypedRaftRouter<C: RaftTypeConfig = memstore::Config, S: RaftStorage<C> = Arc<MemStore>>
+pub struct TypedRaftRouter<C: RaftTypeConfig = memstore::Config, S: RaftStorage<C> + Clone = Arc<MemStore>>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -147,6 +152,9 @@ where
     /// Nodes which could not be connected via RaftNetworkFactory::connect
     unconnectable: Arc<Mutex<HashSet<C::NodeId>>>,
 
+    /// Nodes which do not receive AppendEntries RPCs, while the other RPCs to them are delivered.
+    append_entries_blocked: Arc<Mutex<HashSet<C::NodeId>>>,
+
     /// To emulate network delay for sending, in milliseconds.
     /// 0 means no delay.
     send_delay: Arc<AtomicU64>,
@@ -155,13 +163,13 @@ where
 /// Default `RaftRouter` for memstore.
 pub type RaftRouter = TypedRaftRouter<memstore::Config, Arc<MemStore>>;
 
-pub struct Builder<C: RaftTypeConfig, S: RaftStorage<C>> {
+pub struct Builder<C: RaftTypeConfig, S: RaftStorage<C> + Clone> {
     config: Arc<Config>,
     send_delay: u64,
     _phantom: PhantomData<(C, S)>,
 }
 
-impl<C: RaftTypeConfig, S: RaftStorage<C>> Builder<C, S>
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> Builder<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -189,11 +197,12 @@ where
             isolated_nodes: Default::default(),
             send_delay: Arc::new(AtomicU64::new(send_delay)),
             unconnectable: Default::default(),
+            append_entries_blocked: Default::default(),
         }
     }
 }
 
-impl<C: RaftTypeConfig, S: RaftStorage<C>> Clone for TypedRaftRouter<C, S>
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> Clone for TypedRaftRouter<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -205,12 +214,13 @@ where
             routing_table: self.routing_table.clone(),
             isolated_nodes: self.isolated_nodes.clone(),
             unconnectable: self.unconnectable.clone(),
+            append_entries_blocked: self.append_entries_blocked.clone(),
             send_delay: self.send_delay.clone(),
         }
     }
 }
 
-impl<C: RaftTypeConfig, S: RaftStorage<C>> TypedRaftRouter<C, S>
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> TypedRaftRouter<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -313,8 +323,13 @@ where
             self.add_learner(C::NodeId::default(), id).await?;
             log_index += 1;
         }
+
+        // A learner may apply the logs before the leader does: wait for the leader too.
+        let mut learners_and_leader = learners.clone();
+        learners_and_leader.insert(leader_id);
+
         self.wait_for_log(
-            &learners,
+            &learners_and_leader,
             Some(log_index),
             timeout(),
             &format!("learners of {:?}", learners),
@@ -358,7 +373,8 @@ where
 
     #[tracing::instrument(level = "debug", skip(self, sto))]
     pub async fn new_raft_node_with_sto(&mut self, id: C::NodeId, sto: StoreWithDefensive<C, S>) {
-        let node = Raft::new(id, self.config.clone(), self.clone(), sto.clone()).await.unwrap();
+        let (log_store, state_machine) = Adaptor::new(sto.clone());
+        let node = Raft::new(id, self.config.clone(), self.clone(), log_store, state_machine).await.unwrap();
         let mut rt = self.routing_table.lock().unwrap();
         rt.insert(id, (node, sto));
     }
@@ -568,6 +584,17 @@ where
         }
     }
 
+    /// Block or unblock the AppendEntries RPCs to the specified node.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_append_entries_blocked(&self, id: C::NodeId, blocked: bool) {
+        let mut nodes = self.append_entries_blocked.lock().unwrap();
+        if blocked {
+            nodes.insert(id);
+        } else {
+            nodes.remove(&id);
+        }
+    }
+
     /// Bring up a new learner and add it to the leader's membership.
     pub async fn add_learner(
         &self,
@@ -587,6 +614,18 @@ where
         node.0.is_leader().await
     }
 
+    /// Send a read_index request to the target node.
+    pub async fn read_index(
+        &self,
+        target: C::NodeId,
+    ) -> Result<Option<LogId<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let node = {
+            let rt = self.routing_table.lock().unwrap();
+            rt.get(&target).unwrap_or_else(|| panic!("node with ID {} does not exist", target)).clone()
+        };
+        node.0.read_index().await
+    }
+
     /// Send a client request to the target node, causing test failure on error.
     pub async fn client_request(
         &self,
@@ -628,7 +667,9 @@ where
 
     /// Send external request to the particular node.
     pub fn external_request<
-        F: FnOnce(&RaftState<C::NodeId, C::Node>, &mut StoreExt<C, S>, &mut TypedRaftRouter<C, S>) + Send + 'static,
+        F: FnOnce(&RaftState<C::NodeId, C::Node>, &mut Adaptor<C, StoreExt<C, S>>, &mut TypedRaftRouter<C, S>)
+            + Send
+            + 'static,
     >(
         &self,
         target: C::NodeId,
@@ -943,7 +984,7 @@ where
 }
 
 #[async_trait]
-impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftNetworkFactory<C> for TypedRaftRouter<C, S>
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> RaftNetworkFactory<C> for TypedRaftRouter<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -967,7 +1008,7 @@ where
     }
 }
 
-pub struct RaftRouterNetwork<C: RaftTypeConfig, S: RaftStorage<C>>
+pub struct RaftRouterNetwork<C: RaftTypeConfig, S: RaftStorage<C> + Clone>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -978,7 +1019,7 @@ where
 }
 
 #[async_trait]
-impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftNetwork<C> for RaftRouterNetwork<C, S>
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> RaftNetwork<C> for RaftRouterNetwork<C, S>
 where
     C::D: Debug + IntoMemClientRequest<C::D>,
     C::R: Debug,
@@ -994,6 +1035,11 @@ where
     > {
         tracing::debug!("append_entries to id={} {:?}", self.target, rpc);
         self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+
+        if self.owner.append_entries_blocked.lock().unwrap().contains(&self.target) {
+            let e = NetworkError::new(&AnyError::error(format!("append_entries blocked: -> {}", self.target)));
+            return Err(e.into());
+        }
         self.owner.rand_send_delay().await;
 
         let node = self.owner.get_raft_handle(&self.target)?;
@@ -1037,6 +1083,37 @@ where
         let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
         Ok(resp)
     }
+
+    /// Send a PreVote RPC to the target Raft node.
+    async fn send_pre_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> std::result::Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.pre_vote(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
+
+    /// Send a TimeoutNow RPC to the target Raft node.
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> std::result::Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, C::Node, TimeoutNowError<C::NodeId>>>
+    {
+        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.timeout_now(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
 }
 
 pub enum ValueTest<T> {
@@ -11,4 +11,5 @@ mod fixtures;
 mod t20_initialization;
 mod t20_shutdown;
 mod t30_connect_error;
+mod t40_update_config;
 mod t90_issue_607_single_restart;
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::UpdateConfigError;
+use openraft::BasicNode;
+use openraft::Config;
+use openraft::ConfigError;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::LogStats;
+use openraft::RaftMetrics;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Update the config of a running node.
+///
+/// What does this test do?
+///
+/// - build a cluster of {0,1} that never builds a snapshot.
+/// - update the config with an invalid one or with a field that can not be changed at runtime: rejected.
+/// - update the snapshot policy of node 0 at runtime, a snapshot is built without restarting it.
+/// - the follower still receives logs, with the new config in use by the replication stream.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn update_config() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, no snapshot is built");
+    {
+        router.client_request_many(0, "0", 20).await?;
+        log_index += 20;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+        assert_eq!(None, router.get_metrics(&0)?.snapshot);
+    }
+
+    let raft0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- invalid config is rejected");
+    {
+        let res = raft0
+            .update_config(Config {
+                election_timeout_min: 300,
+                election_timeout_max: 200,
+                ..(*config).clone()
+            })
+            .await;
+
+        assert_eq!(
+            Err(UpdateConfigError::InvalidConfig(ConfigError::ElectionTimeout {
+                min: 300,
+                max: 200
+            })),
+            res
+        );
+
+        // A custom snapshot policy built for another type of node: RaftCore keeps running.
+        let res = raft0
+            .update_config(Config {
+                snapshot_policy: SnapshotPolicy::custom(|_: &LogStats, _: &RaftMetrics<u64, BasicNode>| true),
+                ..(*config).clone()
+            })
+            .await;
+
+        assert!(
+            matches!(
+                res,
+                Err(UpdateConfigError::InvalidConfig(
+                    ConfigError::SnapshotPolicyTypeMismatch { .. }
+                ))
+            ),
+            "{:?}",
+            res
+        );
+        assert!(Arc::ptr_eq(&config, &raft0.config()), "config is not changed");
+        raft0.is_leader().await?;
+    }
+
+    tracing::info!("--- changing cluster_name, max_inflight_append_requests or event_buffer_size is rejected");
+    {
+        let changes = [
+            Config {
+                cluster_name: "bar".to_string(),
+                ..(*config).clone()
+            },
+            Config {
+                max_inflight_append_requests: config.max_inflight_append_requests + 1,
+                ..(*config).clone()
+            },
+            Config {
+                event_buffer_size: config.event_buffer_size + 1,
+                ..(*config).clone()
+            },
+        ];
+
+        for (new_config, name) in
+            changes.into_iter().zip(["cluster_name", "max_inflight_append_requests", "event_buffer_size"])
+        {
+            let res = raft0.update_config(new_config).await;
+
+            assert!(
+                matches!(res, Err(UpdateConfigError::UnchangeableConfig(ref e)) if e.name == name),
+                "{:?}",
+                res
+            );
+            assert!(Arc::ptr_eq(&config, &raft0.config()), "config is not changed");
+        }
+    }
+
+    tracing::info!("--- update snapshot policy, a snapshot is built");
+    {
+        let new_config = Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(10),
+            max_payload_entries: 1,
+            ..(*config).clone()
+        };
+        raft0.update_config(new_config.clone()).await?;
+        let got = raft0.config();
+        assert_eq!(new_config.snapshot_policy, got.snapshot_policy);
+        assert_eq!(new_config.max_payload_entries, got.max_payload_entries);
+
+        router.client_request_many(0, "0", 1).await?;
+        log_index += 1;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(
+                LogId::new(LeaderId::new(1, 0), log_index),
+                "snapshot with the new policy",
+            )
+            .await?;
+    }
+
+    tracing::info!("--- replication works with the new config");
+    {
+        router.client_request_many(0, "0", 5).await?;
+        log_index += 5;
+
+        router.wait(&1, timeout()).log(Some(log_index), "follower receives logs").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -20,6 +20,9 @@ mod t30_commit_joint_config;
 mod t30_remove_leader;
 mod t40_removed_follower;
 mod t45_remove_unreachable_follower;
+mod t50_add_witness;
+mod t51_witness_catch_up;
+mod t60_force_reset_membership;
 mod t99_issue_471_adding_learner_uses_uninit_leader_id;
 mod t99_issue_584_replication_state_reverted;
 mod t99_new_leader_auto_commit_uniform_config;
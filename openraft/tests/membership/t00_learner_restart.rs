@@ -3,6 +3,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Raft;
 use openraft::ServerState;
@@ -48,7 +49,8 @@ async fn learner_restart() -> Result<()> {
     node1.shutdown().await?;
 
     // restart node-1, assert the state as expected.
-    let restarted = Raft::new(1, config.clone(), router.clone(), sto1).await?;
+    let (log_store, state_machine) = Adaptor::new(sto1);
+    let restarted = Raft::new(1, config.clone(), router.clone(), log_store, state_machine).await?;
     restarted.wait(timeout()).log(Some(log_index), "log after restart").await?;
     restarted.wait(timeout()).state(ServerState::Learner, "server state after restart").await?;
 
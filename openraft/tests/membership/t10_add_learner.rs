@@ -3,6 +3,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::LeaderId;
 use openraft::LogId;
@@ -178,8 +179,9 @@ async fn check_learner_after_leader_transferred() -> Result<()> {
 
     tracing::info!("--- check new cluster membership");
     {
-        let mut sto1 = router.get_storage_handle(&1)?;
-        let m = StorageHelper::new(&mut sto1).get_membership().await?;
+        let sto1 = router.get_storage_handle(&1)?;
+        let (mut log_store, mut state_machine) = Adaptor::new(sto1);
+        let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
         // new membership is applied, thus get_membership() only returns one entry.
 
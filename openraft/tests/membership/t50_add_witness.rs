@@ -0,0 +1,112 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::AlreadyVoter;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::error::IsWitness;
+use openraft::error::TransferLeaderError;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Add a witness to a cluster.
+///
+/// - Bring up a cluster of voters {0,1} and learner 2, then turn node 2 into a witness.
+/// - A voter can not be turned into a witness.
+/// - The witness receives log ids and membership but no application data.
+/// - The witness is counted in a quorum: logs are committed by {0,2} when node 1 is isolated.
+/// - Leadership can not be transferred to the witness.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_witness() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {2}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- add node 2 as witness");
+    {
+        n0.change_membership(ChangeMembers::AddWitness(btreeset! {2}), false, false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "add witness").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(&btreeset! {2}, m.membership_config.membership.witnesses());
+        assert_eq!(
+            btreeset! {0,1,2},
+            m.membership_config.voter_ids().collect::<BTreeSet<_>>()
+        );
+    }
+
+    tracing::info!("--- a voter can not be turned into a witness");
+    {
+        let res = n0.change_membership(ChangeMembers::AddWitness(btreeset! {1}), false, false).await;
+        match res {
+            Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::AlreadyVoter(e))) => {
+                assert_eq!(AlreadyVoter { node_id: 1 }, e);
+            }
+            _ => panic!("expect ChangeMembershipError::AlreadyVoter, got: {:?}", res),
+        }
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(&btreeset! {2}, m.membership_config.membership.witnesses());
+    }
+
+    let since = log_index + 1;
+
+    tracing::info!("--- witness receives no application data");
+    {
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 10 logs").await?;
+
+        let mut sto1 = router.get_storage_handle(&1)?;
+        let logs = sto1.try_get_log_entries(since..).await?;
+        assert!(logs.iter().all(|ent| matches!(ent.payload, EntryPayload::Normal(_))));
+
+        let mut sto2 = router.get_storage_handle(&2)?;
+        let logs = sto2.try_get_log_entries(since..).await?;
+        assert_eq!(10, logs.len());
+        assert!(logs.iter().all(|ent| matches!(ent.payload, EntryPayload::Blank)));
+    }
+
+    tracing::info!("--- witness is counted in a quorum");
+    {
+        router.isolate_node(1);
+
+        router.client_request_many(0, "foo", 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,2}, Some(log_index), timeout(), "committed by {0,2}").await?;
+    }
+
+    tracing::info!("--- can not transfer leadership to a witness");
+    {
+        let res = n0.transfer_leader(2).await;
+        assert_eq!(Err(TransferLeaderError::IsWitness(IsWitness { node_id: 2 })), res);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::ChangeMembers;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A witness that lags behind the purged logs catches up with a snapshot.
+///
+/// - Bring up a cluster of voters {0,1} and witness 2.
+/// - Isolate the witness, write logs, build a snapshot and purge the logs on the leader.
+/// - Restore the witness, it receives the snapshot and the following logs.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn witness_catch_up() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {2}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- add node 2 as witness");
+    {
+        n0.change_membership(ChangeMembers::AddWitness(btreeset! {2}), false, false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "add witness").await?;
+    }
+
+    tracing::info!("--- isolate the witness, write logs, build a snapshot and purge logs");
+    let snapshot_log_id = {
+        router.isolate_node(2);
+
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "write 10 logs").await?;
+
+        n0.trigger_snapshot().await?;
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+        n0.wait(timeout()).snapshot(want, "leader builds snapshot").await?;
+
+        let mut sto0 = router.get_storage_handle(&0)?;
+        loop {
+            let st = sto0.get_log_state().await?;
+            if st.last_purged_log_id == Some(want) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        want
+    };
+
+    tracing::info!("--- restore the witness, it catches up with the snapshot");
+    {
+        router.restore_node(2);
+
+        router.client_request_many(0, "foo", 1).await?;
+        log_index += 1;
+
+        router.wait(&2, timeout()).snapshot(snapshot_log_id, "witness receives snapshot").await?;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "witness catches up").await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(&btreeset! {2}, m.membership_config.membership.witnesses());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
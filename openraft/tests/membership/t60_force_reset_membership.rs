@@ -0,0 +1,224 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::error::ForceResetMembershipError;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Force a new membership on a surviving voter after a quorum of voters is permanently lost.
+///
+/// What does this test do?
+///
+/// - build a cluster of voters {0,1,2,3,4} and write some logs.
+/// - force reset membership while the leader is still acknowledged by a quorum: rejected.
+/// - lose voters {2,3,4}, then append a log on node 0 but not on node 1.
+/// - force reset membership on node 1: rejected because node 0 has more logs.
+/// - force reset membership with an unreachable, an unknown voter, or without node 0 itself: rejected.
+/// - force reset membership to {0,1} on node 0: node 0 becomes the leader, and all of its logs, including the one that
+///   was not committed, are committed on both survivors.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn force_reset_membership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            // No election is started by a node whose election timer has expired, e.g., node 1 after it is isolated.
+            enable_elect: false,
+            election_timeout_min: 500,
+            election_timeout_max: 1000,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2,3,4}, btreeset! {}).await?;
+
+    tracing::info!("--- write some logs");
+    {
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+
+        router.wait(&1, timeout()).log(Some(log_index), "node 1 receives logs").await?;
+    }
+
+    tracing::info!("--- the leader is acknowledged by a quorum: not allowed");
+    {
+        let res = router.get_raft_handle(&0)?.force_reset_membership(btreeset! {0,1}).await;
+        assert!(
+            matches!(res, Err(ForceResetMembershipError::LeaderAlive(_))),
+            "{:?}",
+            res
+        );
+    }
+
+    tracing::info!("--- lose voters 2,3,4, and append a log only on node 0");
+    {
+        router.isolate_node(2);
+        router.isolate_node(3);
+        router.isolate_node(4);
+
+        // Node 1 does not receive logs but it still answers the other RPCs.
+        router.set_append_entries_blocked(1, true);
+
+        let raft0 = router.get_raft_handle(&0)?;
+        tokio::spawn(async move {
+            // It can not be committed until the membership is reset.
+            let _ = raft0
+                .client_write(ClientRequest {
+                    client: "foo".to_string(),
+                    serial: 10,
+                    status: "not-yet-committed".to_string(),
+                })
+                .await;
+        });
+        log_index += 1;
+
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.last_log_index == Some(log_index), "node 0 appends a log")
+            .await?;
+
+        // Wait for the leader lease and the election timer on node 1 to expire.
+        // The election timeout of a follower is at most twice `election_timeout_max`.
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 2)).await;
+    }
+
+    tracing::info!("--- node 1 has fewer logs than node 0: not allowed");
+    {
+        let res = router.get_raft_handle(&1)?.force_reset_membership(btreeset! {0,1}).await;
+        match res {
+            Err(ForceResetMembershipError::NotMostUpToDate(e)) => {
+                assert_eq!(0, e.target);
+                assert_eq!(Some(log_index), e.target_last_log_id.index());
+                assert_eq!(Some(log_index - 1), e.last_log_id.index());
+            }
+            _ => panic!("expect NotMostUpToDate, got: {:?}", res),
+        }
+    }
+
+    tracing::info!("--- unreachable voter, unknown voter or this node is not in new membership: not allowed");
+    {
+        let raft0 = router.get_raft_handle(&0)?;
+
+        let res = raft0.force_reset_membership(btreeset! {0,2}).await;
+        assert!(
+            matches!(res, Err(ForceResetMembershipError::VoterUnreachable(ref e)) if e.target == 2),
+            "{:?}",
+            res
+        );
+
+        let res = raft0.force_reset_membership(btreeset! {0,5}).await;
+        assert!(
+            matches!(res, Err(ForceResetMembershipError::LearnerNotFound(_))),
+            "{:?}",
+            res
+        );
+
+        let res = raft0.force_reset_membership(btreeset! {1}).await;
+        assert!(
+            matches!(res, Err(ForceResetMembershipError::NotInMembers(_))),
+            "{:?}",
+            res
+        );
+
+        assert_eq!(
+            btreeset! {0,1,2,3,4},
+            router.get_metrics(&0)?.membership_config.nodes().map(|(id, _)| *id).collect::<BTreeSet<_>>(),
+            "membership is not changed"
+        );
+    }
+
+    tracing::info!("--- force reset membership to {{0,1}} on node 0");
+    {
+        router.set_append_entries_blocked(1, false);
+
+        let log_id = router.get_raft_handle(&0)?.force_reset_membership(btreeset! {0,1}).await?;
+        log_index += 1;
+        assert_eq!(log_index, log_id.index);
+
+        router.wait(&0, timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+
+        for id in [0, 1] {
+            router.wait(&id, timeout()).members(btreeset! {0,1}, "new membership").await?;
+            router.wait(&id, timeout()).log(Some(log_index), "membership log is committed").await?;
+
+            let mut sto = router.get_storage_handle(&id)?;
+            let sm = sto.get_state_machine().await;
+            assert_eq!(
+                Some(&"not-yet-committed".to_string()),
+                sm.client_status.get("foo"),
+                "the log only on node 0 survives on node {}",
+                id
+            );
+        }
+    }
+
+    tracing::info!("--- the survivors accept writes");
+    {
+        router.client_request_many(0, "bar", 5).await?;
+        log_index += 5;
+
+        for id in [0, 1] {
+            router.wait(&id, timeout()).log(Some(log_index), "write to the new membership").await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Force reset membership is refused while the leader is alive, even if the leader lease is disabled.
+///
+/// What does this test do?
+///
+/// - build a cluster of voters {0,1,2} with heartbeat on and leader lease off.
+/// - force reset membership to {1,2} on follower 1, which hears from leader 0: rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn force_reset_membership_leader_alive_without_lease() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_leader_lease: false,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+    router.wait(&1, timeout()).log(Some(log_index), "node 1 receives logs").await?;
+
+    // A heartbeat appends a blank log, thus it is enabled after the cluster is initialized.
+    for id in [0, 1, 2] {
+        router.get_raft_handle(&id)?.enable_heartbeat(true);
+    }
+
+    let res = router.get_raft_handle(&1)?.force_reset_membership(btreeset! {1,2}).await;
+    assert!(
+        matches!(res, Err(ForceResetMembershipError::LeaderAlive(ref e)) if e.leader_id == 0),
+        "{:?}",
+        res
+    );
+
+    assert_eq!(
+        btreeset! {0,1,2},
+        router.get_metrics(&1)?.membership_config.nodes().map(|(id, _)| *id).collect::<BTreeSet<_>>(),
+        "membership is not changed"
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
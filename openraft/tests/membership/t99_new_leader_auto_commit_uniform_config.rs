@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Entry;
 use openraft::EntryPayload;
@@ -59,7 +60,8 @@ async fn new_leader_auto_commit_uniform_config() -> Result<()> {
     router.new_raft_node(1).await;
     router.new_raft_node(2).await;
 
-    let node = Raft::new(0, config.clone(), router.clone(), sto.clone());
+    let (log_store, state_machine) = Adaptor::new(sto.clone());
+    let node = Raft::new(0, config.clone(), router.clone(), log_store, state_machine);
 
     let _ = node;
 
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The replication metrics on the leader tell a healthy follower from an unreachable one.
+///
+/// What does this test do?
+///
+/// - brings up a cluster of 3 voters and writes some logs.
+/// - isolate node 2 and write more logs.
+/// - wait for the RPC errors to node 2, which are reported to the metrics watchers without a change of the matched log.
+/// - asserts that node 1 is acknowledging recently without errors, while RPCs to node 2 fail.
+/// - restore node 2 and asserts the consecutive errors are reset, while the last error is kept.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_status() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- a healthy follower acknowledges without errors");
+    {
+        let m = router.get_metrics(&0)?;
+        let repl = m.replication.unwrap();
+        let t1 = repl.data().replication.get(&1).unwrap();
+
+        assert!(t1.last_ack_elapsed().is_some());
+        assert_eq!(None, t1.inflight());
+        assert_eq!(None, t1.snapshot_progress());
+        assert_eq!(0, t1.consecutive_errors());
+        assert_eq!(None, t1.last_error());
+    }
+
+    tracing::info!("--- isolate node 2, write logs");
+    {
+        router.isolate_node(2);
+
+        router.client_request_many(0, "0", 5).await?;
+        log_index += 5;
+
+        router.wait(&1, timeout()).log(Some(log_index), "node 1 receives logs").await?;
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    m.replication
+                        .as_ref()
+                        .and_then(|r| r.data().replication.get(&1).map(|t| t.inflight().is_none()))
+                        .unwrap_or(false)
+                },
+                "no logs are inflight to node 1",
+            )
+            .await?;
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    m.replication
+                        .as_ref()
+                        .and_then(|r| r.data().replication.get(&2).map(|t| t.consecutive_errors() > 0))
+                        .unwrap_or(false)
+                },
+                "RPCs to node 2 fail",
+            )
+            .await?;
+
+        let m = router.get_metrics(&0)?;
+        let repl = m.replication.unwrap();
+
+        let t1 = repl.data().replication.get(&1).unwrap();
+        assert_eq!(0, t1.consecutive_errors());
+        assert!(t1.last_ack_elapsed().unwrap() < Duration::from_millis(1_000));
+
+        let t2 = repl.data().replication.get(&2).unwrap();
+        assert!(t2.consecutive_errors() > 0);
+        assert!(t2.last_error().unwrap().contains("isolated"), "{:?}", t2.last_error());
+    }
+
+    tracing::info!("--- restore node 2, errors are reset");
+    {
+        router.restore_node(2);
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| {
+                    m.replication
+                        .as_ref()
+                        .and_then(|r| r.data().replication.get(&2).map(|t| t.matched().index == log_index))
+                        .unwrap_or(false)
+                },
+                "node 2 catches up",
+            )
+            .await?;
+
+        let m = router.get_metrics(&0)?;
+        let repl = m.replication.unwrap();
+        let t2 = repl.data().replication.get(&2).unwrap();
+
+        assert_eq!(0, t2.consecutive_errors());
+        assert!(t2.last_error().is_some(), "the last error is kept");
+        assert!(t2.last_ack_elapsed().unwrap() < Duration::from_millis(1_000));
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
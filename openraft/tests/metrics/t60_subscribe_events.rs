@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use openraft::error::RecvEventError;
+use openraft::event::EventReceiver;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftEvent;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Subscribers receive every state transition in the order they happen.
+///
+/// What does this test do?
+///
+/// - subscribe to a node before initializing it, assert it receives the term change, the leadership and the membership.
+/// - build a snapshot and assert the snapshot and the log purge are received in order.
+/// - shut down the node and assert the receiver is closed after the buffered events are received.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn subscribe_events() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_raft_node(0).await;
+    let mut rx = router.get_raft_handle(&0)?.subscribe();
+
+    tracing::info!("--- initialize, receive term change, leadership and membership");
+    let mut log_index = 0;
+    {
+        router.initialize_from_single_node(0).await?;
+        log_index += 1;
+
+        router.wait(&0, timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+        router.wait(&0, timeout()).log(Some(log_index), "leader blank log is committed").await?;
+
+        let events = drain(&mut rx)?;
+        let pos = |f: fn(&RaftEvent<u64, ()>) -> bool| events.iter().position(f);
+
+        let term = pos(|e| matches!(e, RaftEvent::TermChanged { term: 1 })).unwrap();
+        let leader = pos(|e| matches!(e, RaftEvent::BecameLeader { vote } if vote.term == 1)).unwrap();
+        assert!(term < leader, "term changes before becoming leader: {:?}", events);
+
+        let membership = pos(|e| matches!(e, RaftEvent::MembershipChanged { .. })).unwrap();
+        match &events[membership] {
+            RaftEvent::MembershipChanged { membership } => {
+                assert_eq!(vec![0], membership.voter_ids().collect::<Vec<_>>());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    tracing::info!("--- build a snapshot, receive the snapshot and the purge");
+    {
+        log_index += router.client_request_many(0, "0", 5).await?;
+
+        let n0 = router.get_raft_handle(&0)?;
+        n0.trigger_snapshot().await?;
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+        n0.wait(timeout()).snapshot(want, "build snapshot").await?;
+
+        // Receive until all of the logs in the snapshot are purged.
+        let mut events = vec![];
+        loop {
+            let ev = tokio::time::timeout(timeout().unwrap(), rx.recv()).await??;
+            events.push(ev.clone());
+            if ev == (RaftEvent::LogsPurged { upto: want }) {
+                break;
+            }
+        }
+
+        let built = events
+            .iter()
+            .position(|e| matches!(e, RaftEvent::SnapshotBuilt { meta } if meta.last_log_id == Some(want)));
+        assert!(built.is_some(), "snapshot is built before purging: {:?}", events);
+    }
+
+    tracing::info!("--- shut down, the receiver is closed");
+    {
+        let (n0, _sto) = router.remove_node(0).unwrap();
+        n0.shutdown().await?;
+
+        assert_eq!(Err(RecvEventError::Closed), rx.recv().await);
+
+        let mut late = n0.subscribe();
+        assert_eq!(Err(RecvEventError::Closed), late.recv().await);
+    }
+
+    Ok(())
+}
+
+/// Receive all of the buffered events.
+fn drain(rx: &mut EventReceiver<u64, ()>) -> Result<Vec<RaftEvent<u64, ()>>> {
+    let mut events = vec![];
+    while let Some(ev) = rx.try_recv()? {
+        events.push(ev);
+    }
+    Ok(events)
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The leader records the latency of every write stage and of the AppendEntries RPCs to every target.
+///
+/// What does this test do?
+///
+/// - brings up a cluster of 3 voters and writes some logs.
+/// - asserts the written logs are recorded in the append, commit and apply histograms of the leader.
+/// - asserts the AppendEntries RPCs to both followers are recorded.
+/// - asserts a follower does not record the write stages.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn latency_metrics() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, the write stages are recorded on the leader");
+    let n = 10;
+    {
+        log_index += router.client_request_many(0, "0", n).await?;
+        router.wait(&0, timeout()).log(Some(log_index), "logs are applied").await?;
+
+        let n0 = router.get_raft_handle(&0)?;
+
+        // The local flush may be notified after the logs are committed by the followers.
+        let deadline = Instant::now() + timeout().unwrap();
+        while n0.latency_metrics().append.count() < n as u64 && Instant::now() < deadline {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let latency = n0.latency_metrics();
+        tracing::info!("latency on leader: {:?}", latency);
+
+        assert!(latency.append.count() >= n as u64);
+        assert!(latency.commit.count() >= n as u64);
+        assert!(latency.apply.count() >= n as u64);
+
+        assert!(latency.apply.percentile(50.0).unwrap() >= latency.commit.percentile(0.0).unwrap());
+        assert!(latency.apply.max() <= Some(timeout().unwrap()));
+
+        assert_eq!(
+            vec![1, 2],
+            latency.append_entries.keys().copied().collect::<Vec<_>>(),
+            "a histogram for every target"
+        );
+        for (target, h) in latency.append_entries.iter() {
+            assert!(h.count() > 0, "target {}: {}", target, h);
+        }
+    }
+
+    tracing::info!("--- a follower does not record the write stages");
+    {
+        let latency = router.get_raft_handle(&1)?.latency_metrics();
+
+        assert_eq!(0, latency.commit.count());
+        assert_eq!(0, latency.apply.count());
+        assert!(latency.append_entries.is_empty());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
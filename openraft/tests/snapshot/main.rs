@@ -7,9 +7,13 @@ mod fixtures;
 
 mod t20_api_install_snapshot;
 mod t20_trigger_snapshot;
+mod t21_snapshot_policy;
 mod t23_snapshot_chunk_size;
+mod t23_snapshot_compression;
 mod t24_snapshot_when_lacking_log;
 mod t25_snapshot_line_rate_to_snapshot;
+mod t26_incompatible_snapshot;
+mod t27_export_import_snapshot;
 mod t40_after_snapshot_add_learner_and_request_a_log;
 mod t40_purge_in_snapshot_logs;
 mod t41_snapshot_overrides_membership;
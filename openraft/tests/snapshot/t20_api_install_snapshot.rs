@@ -8,6 +8,7 @@ use openraft::Config;
 use openraft::LeaderId;
 use openraft::LogId;
 use openraft::ServerState;
+use openraft::SnapshotCompression;
 use openraft::SnapshotMeta;
 use openraft::Vote;
 
@@ -20,6 +21,8 @@ use crate::fixtures::RaftRouter;
 ///
 /// - build a stable single node cluster.
 /// - send install_snapshot request with matched/mismatched id and offset
+/// - the number of bytes received is reported in the response
+/// - the received data is discarded if a checksum does not match
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn snapshot_arguments() -> Result<()> {
     let config = Arc::new(
@@ -58,9 +61,13 @@ async fn snapshot_arguments() -> Result<()> {
                 index: 0,
             }),
             last_membership: Default::default(),
+            metadata: Default::default(),
         },
         offset: 0,
         data: vec![1, 2, 3],
+        compression: SnapshotCompression::None,
+        checksum: None,
+        snapshot_checksum: None,
         done: false,
     };
 
@@ -78,7 +85,8 @@ async fn snapshot_arguments() -> Result<()> {
     tracing::info!("--- install and write ss1:[0,3)");
     {
         let req = req0.clone();
-        n.0.install_snapshot(req).await?;
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(3), resp.received);
     }
 
     tracing::info!("-- continue write with different id");
@@ -98,21 +106,127 @@ async fn snapshot_arguments() -> Result<()> {
         let mut req = req0.clone();
         req.offset = 0;
         req.meta.snapshot_id = "ss2".into();
-        n.0.install_snapshot(req).await?;
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(3), resp.received);
 
         let mut req = req0.clone();
         req.offset = 3;
         req.meta.snapshot_id = "ss2".into();
-        n.0.install_snapshot(req).await?;
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(6), resp.received);
     }
 
-    tracing::info!("-- continue write with mismatched offset is allowed");
+    tracing::info!("-- continue write with mismatched offset is allowed, but received bytes stop at the gap");
     {
         let mut req = req0.clone();
         req.offset = 8;
         req.meta.snapshot_id = "ss2".into();
-        n.0.install_snapshot(req).await?;
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(6), resp.received);
     }
+
+    tracing::info!("-- restart streaming the same snapshot from offset=0, received bytes are kept");
+    {
+        let mut req = req0.clone();
+        req.offset = 0;
+        req.meta.snapshot_id = "ss2".into();
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(6), resp.received);
+    }
+
+    tracing::info!("-- a lower vote is rejected without reporting received bytes");
+    {
+        let mut req = req0.clone();
+        req.vote = Vote::new_committed(0, 0);
+        req.meta.snapshot_id = "ss2".into();
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(None, resp.received);
+    }
+
+    tracing::info!("-- a chunk mismatching its checksum discards the received data");
+    {
+        let mut req = req0.clone();
+        req.offset = 6;
+        req.meta.snapshot_id = "ss2".into();
+        req.checksum = Some(0);
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot checksum mismatch, segment: ss2+6, len: 3, expect: 00000000, got: 55bc801d",
+            res.unwrap_err().to_string()
+        );
+
+        let mut req = req0.clone();
+        req.offset = 6;
+        req.meta.snapshot_id = "ss2".into();
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss2+0, got: ss2+6",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("-- the last chunk mismatching the snapshot checksum discards the received data");
+    {
+        let mut req = req0.clone();
+        req.meta.snapshot_id = "ss3".into();
+        req.checksum = Some(0x55bc801d);
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(3), resp.received);
+
+        let mut req = req0.clone();
+        req.offset = 3;
+        req.meta.snapshot_id = "ss3".into();
+        req.done = true;
+        req.snapshot_checksum = Some(0);
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot checksum mismatch, segment: ss3+0, len: 6, expect: 00000000, got: b816d787",
+            res.unwrap_err().to_string()
+        );
+
+        let mut req = req0.clone();
+        req.offset = 3;
+        req.meta.snapshot_id = "ss3".into();
+        let res = n.0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss3+0, got: ss3+3",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    tracing::info!("-- a chunk that can not be decoded is rejected without writing it");
+    {
+        let mut req = req0.clone();
+        req.meta.snapshot_id = "ss4".into();
+        req.compression = SnapshotCompression::Lz4;
+        let res = n.0.install_snapshot(req).await;
+        let err = res.unwrap_err().to_string();
+        assert!(
+            err.starts_with("failed to decode snapshot chunk, segment: ss4+0, compression: Lz4: "),
+            "{}",
+            err
+        );
+
+        tracing::info!("-- a chunk that declares a decoded size larger than the max decoded chunk size is rejected");
+        let mut req = req0.clone();
+        req.meta.snapshot_id = "ss4".into();
+        req.compression = SnapshotCompression::Lz4;
+        req.data = vec![0xff, 0xff, 0xff, 0xff, 0];
+        let res = n.0.install_snapshot(req).await;
+        let err = res.unwrap_err().to_string();
+        assert!(err.contains("more than the max decoded chunk size"), "{}", err);
+
+        let mut req = req0.clone();
+        req.meta.snapshot_id = "ss4".into();
+        let resp = n.0.install_snapshot(req).await?;
+        assert_eq!(Some(3), resp.received);
+        assert!(
+            resp.compressions.contains(&SnapshotCompression::Lz4),
+            "target reports it decodes lz4: {:?}",
+            resp.compressions
+        );
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::MemNodeId;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::LogStats;
+use openraft::RaftMetrics;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A snapshot is built when the size of logs applied since the last snapshot reaches the threshold.
+///
+/// - write a few logs whose total size is below the threshold, no snapshot is built.
+/// - write more logs, a snapshot is built.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_log_bytes_since_last() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogBytesSinceLast(4096),
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs below the threshold");
+    {
+        router.client_request_many(0, "0", 3).await?;
+        log_index += 3;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(
+            None,
+            router.get_metrics(&0)?.snapshot,
+            "no snapshot below the threshold"
+        );
+    }
+
+    tracing::info!("--- write logs until the threshold is reached");
+    {
+        router.client_request_many(0, "0", 200).await?;
+        log_index += 200;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+        router.wait(&0, timeout()).metrics(|m| m.snapshot.is_some(), "snapshot is built").await?;
+    }
+
+    Ok(())
+}
+
+/// A snapshot is built periodically if there are logs applied.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_interval() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Interval(Duration::from_millis(300)),
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- a snapshot is built after the interval");
+    {
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "snapshot after interval")
+            .await?;
+    }
+
+    tracing::info!("--- write a log, another snapshot is built after the interval");
+    {
+        router.client_request_many(0, "0", 1).await?;
+        log_index += 1;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "snapshot after interval")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// No snapshot is built automatically with `SnapshotPolicy::Never`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_never() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, no snapshot is built");
+    {
+        router.client_request_many(0, "0", 50).await?;
+        log_index += 50;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(None, router.get_metrics(&0)?.snapshot, "no snapshot with policy Never");
+    }
+
+    tracing::info!("--- trigger snapshot manually");
+    {
+        router.get_raft_handle(&0)?.trigger_snapshot().await?;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "triggered snapshot")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// A snapshot is built when the application defined hook returns `true`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_policy_custom() -> Result<()> {
+    let policy = SnapshotPolicy::custom(|stats: &LogStats, m: &RaftMetrics<MemNodeId, ()>| {
+        m.last_applied.map(|x| x.index) >= Some(10) && stats.logs_since_last > 0
+    });
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: policy,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write logs, the hook decides to build a snapshot");
+    {
+        router.client_request_many(0, "0", 10 - log_index as usize).await?;
+        log_index = 10;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs").await?;
+        router
+            .wait(&0, timeout())
+            .metrics(|m| m.snapshot.map(|x| x.index) >= Some(10), "snapshot is built by hook")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2000))
+}
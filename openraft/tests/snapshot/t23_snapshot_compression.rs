@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotCompression;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Transfer snapshot in compressed chunks.
+///
+/// What does this test do?
+///
+/// - build a stable single node cluster with `snapshot_compression=lz4`.
+/// - send enough requests to the node that log compaction will be triggered.
+/// - add learner and assert that it receives the snapshot, because the logs in the snapshot are purged. The first chunk
+///   is sent uncompressed, the following ones are compressed after the learner reports it is able to decode them.
+/// - assert the metrics count the election, the snapshot built and sent, and report fewer bytes on the wire than the
+///   raw snapshot data.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_compression() -> Result<()> {
+    let snapshot_threshold: u64 = 50;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            snapshot_max_chunk_size: 256,
+            snapshot_compression: SnapshotCompression::Lz4,
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- send just enough logs to trigger snapshot");
+    {
+        for i in 0..(snapshot_threshold - 1 - log_index) {
+            router.client_request(0, &format!("client-{}", i), 0).await?;
+        }
+        log_index = snapshot_threshold - 1;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot")
+            .await?;
+    }
+
+    tracing::info!("--- add learner to receive snapshot");
+    {
+        router.new_raft_node(1).await;
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait(&1, timeout()).log(Some(log_index), "learner receives snapshot and logs").await?;
+        router.wait(&1, timeout()).metrics(|m| m.snapshot.is_some(), "learner installed snapshot").await?;
+    }
+
+    tracing::info!("--- check snapshot transfer metrics");
+    {
+        let m = router
+            .wait(&0, timeout())
+            .metrics(|m| m.snapshot_transfer.sent_snapshots == 1, "leader sent snapshot")
+            .await?;
+        assert_eq!(1, m.elections);
+        assert_eq!(1, m.snapshots_built);
+
+        let sent = m.snapshot_transfer;
+        assert!(sent.sent_bytes < sent.sent_raw_bytes, "compressed: {:?}", sent);
+        assert_eq!(0, sent.received_bytes);
+        assert_eq!(0, sent.received_raw_bytes);
+
+        let m = router
+            .wait(&1, timeout())
+            .metrics(
+                |m| m.snapshot_transfer.received_raw_bytes > 0,
+                "learner received snapshot",
+            )
+            .await?;
+        let received = m.snapshot_transfer;
+        assert!(
+            received.received_bytes < received.received_raw_bytes,
+            "compressed: {:?}",
+            received
+        );
+        assert!(received.received_raw_bytes >= sent.sent_raw_bytes);
+        assert_eq!(0, received.sent_bytes);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1000))
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::SnapshotPolicy;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A learner rejects a snapshot it can not install, by checking the application-specific snapshot metadata.
+///
+/// What does this test do?
+///
+/// - build a single node cluster whose store builds snapshots of format version 2.
+/// - send enough requests to the node that log compaction will be triggered, and the logs are purged.
+/// - add a learner that supports only format version 1: it rejects the snapshot and receives nothing.
+/// - upgrade the learner to support version 2: the leader retries with a growing backoff and the learner installs the
+///   snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn incompatible_snapshot() -> Result<()> {
+    let snapshot_threshold: u64 = 20;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            install_snapshot_timeout: 200,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- build a snapshot of format version 2 on the leader");
+    {
+        router.get_storage_handle(&0)?.inner().set_snapshot_format_version(2);
+
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot")
+            .await?;
+    }
+
+    tracing::info!("--- add a learner that only supports format version 1");
+    {
+        router.new_raft_node(1).await;
+        router.get_raft_handle(&0)?.add_learner(1, (), false).await?;
+        log_index += 1;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let m = router.get_metrics(&1)?;
+        assert_eq!(None, m.snapshot, "learner rejects the snapshot");
+        assert_eq!(None, m.last_applied, "learner has nothing to apply");
+        assert_eq!(0, m.snapshot_transfer.received_bytes, "no snapshot data is received");
+    }
+
+    tracing::info!("--- upgrade the learner, it installs the snapshot");
+    {
+        router.get_storage_handle(&1)?.inner().set_snapshot_format_version(2);
+
+        router
+            .wait(&1, timeout())
+            .snapshot(
+                LogId::new(LeaderId::new(1, 0), log_index - 1),
+                "learner installs snapshot",
+            )
+            .await?;
+        router.wait(&1, timeout()).log(Some(log_index), "learner receives logs").await?;
+
+        let mut sto1 = router.get_storage_handle(&1)?;
+        let snap = sto1.get_current_snapshot().await?.unwrap();
+        assert_eq!(2, snap.meta.metadata.format_version);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2000))
+}
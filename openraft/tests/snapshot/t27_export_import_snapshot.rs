@@ -0,0 +1,130 @@
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ImportSnapshotError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use openraft::Wrapper;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Back up a cluster by exporting a snapshot, and restore it on a pristine node.
+///
+/// What does this test do?
+///
+/// - build a single node cluster, write some logs and build a snapshot.
+/// - export the snapshot into a buffer.
+/// - import the snapshot into a pristine node of another cluster, it becomes the leader without `initialize()`.
+/// - assert the restored state machine is the same as the original one.
+/// - assert importing into an initialized node or an outdated store fails.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn export_import_snapshot() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- no snapshot to export");
+    {
+        let mut buf = vec![];
+        let meta = router.get_raft_handle(&0)?.export_snapshot_to(&mut buf).await?;
+        assert!(meta.is_none());
+        assert!(buf.is_empty());
+    }
+
+    tracing::info!("--- write logs and build a snapshot");
+    {
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+
+        router.get_raft_handle(&0)?.trigger_snapshot().await?;
+        router
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot")
+            .await?;
+    }
+
+    tracing::info!("--- export the snapshot");
+    let mut buf = vec![];
+    let meta = router.get_raft_handle(&0)?.export_snapshot_to(&mut buf).await?.unwrap();
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), meta.last_log_id);
+    assert!(!buf.is_empty());
+
+    tracing::info!("--- importing into an initialized node is not allowed");
+    {
+        let res = router.get_raft_handle(&0)?.import_snapshot(meta.clone(), Cursor::new(buf.clone())).await;
+        assert!(matches!(res, Err(ImportSnapshotError::NotAllowed(_))), "{:?}", res);
+    }
+
+    tracing::info!("--- a store that does not support the snapshot format rejects it");
+    {
+        let mut restored = RaftRouter::new(config.clone());
+        restored.new_raft_node(0).await;
+        restored.get_storage_handle(&0)?.inner().set_snapshot_format_version(0);
+
+        let res = restored.get_raft_handle(&0)?.import_snapshot(meta.clone(), Cursor::new(buf.clone())).await;
+        assert!(
+            matches!(res, Err(ImportSnapshotError::IncompatibleSnapshot(_))),
+            "{:?}",
+            res
+        );
+        assert_eq!(None, restored.get_metrics(&0)?.snapshot);
+    }
+
+    tracing::info!("--- import the snapshot into a pristine node");
+    {
+        let mut restored = RaftRouter::new(config.clone());
+        restored.new_raft_node(0).await;
+
+        restored.get_raft_handle(&0)?.import_snapshot(meta.clone(), Cursor::new(buf)).await?;
+
+        restored
+            .wait(&0, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "snapshot is imported")
+            .await?;
+        restored.wait(&0, timeout()).state(ServerState::Leader, "restored node becomes leader").await?;
+        assert_eq!(
+            2,
+            restored.get_metrics(&0)?.current_term,
+            "the restored node elects with a term greater than the logs in the snapshot"
+        );
+        restored.wait(&0, timeout()).members(btreeset! {0}, "membership is restored").await?;
+
+        // The new leader appends a blank log.
+        log_index += 1;
+        restored.wait(&0, timeout()).log(Some(log_index), "blank log of the new leader").await?;
+
+        let want = router.get_storage_handle(&0)?.get_state_machine().await;
+        let got = restored.get_storage_handle(&0)?.get_state_machine().await;
+        assert_eq!(want.client_status, got.client_status);
+        assert_eq!(want.client_serial_responses, got.client_serial_responses);
+
+        tracing::info!("--- the restored cluster accepts writes");
+
+        restored.client_request_many(0, "bar", 1).await?;
+        log_index += 1;
+        restored.wait(&0, timeout()).log(Some(log_index), "write to restored cluster").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2000))
+}
@@ -5,6 +5,7 @@ use std::time::Duration;
 use anyhow::Result;
 use maplit::btreeset;
 use openraft::raft::AppendEntriesRequest;
+use openraft::storage::Adaptor;
 use openraft::storage::StorageHelper;
 use openraft::Config;
 use openraft::EffectiveMembership;
@@ -85,7 +86,7 @@ async fn snapshot_overrides_membership() -> Result<()> {
     {
         tracing::info!("--- create learner");
         router.new_raft_node(1).await;
-        let mut sto = router.get_storage_handle(&1)?;
+        let sto = router.get_storage_handle(&1)?;
 
         tracing::info!("--- add a membership config log to the learner");
         {
@@ -102,7 +103,8 @@ async fn snapshot_overrides_membership() -> Result<()> {
 
             tracing::info!("--- check that learner membership is affected");
             {
-                let m = StorageHelper::new(&mut sto).get_membership().await?;
+                let (mut log_store, mut state_machine) = Adaptor::new(sto.clone());
+                let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
                 assert_eq!(&EffectiveMembership::default(), m.committed.as_ref());
                 assert_eq!(Membership::new(vec![btreeset! {2,3}], None), m.effective.membership);
@@ -140,7 +142,8 @@ async fn snapshot_overrides_membership() -> Result<()> {
                 )
                 .await?;
 
-            let m = StorageHelper::new(&mut sto).get_membership().await?;
+            let (mut log_store, mut state_machine) = Adaptor::new(sto.clone());
+            let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
             assert_eq!(
                 Membership::new(vec![btreeset! {0}], Some(btreeset! {1})),
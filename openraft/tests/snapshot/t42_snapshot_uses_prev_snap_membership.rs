@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::LeaderId;
 use openraft::LogId;
@@ -75,7 +76,8 @@ async fn snapshot_uses_prev_snap_membership() -> Result<()> {
             let logs = sto0.get_log_entries(..).await?;
             assert_eq!(3, logs.len(), "only one applied log is kept");
         }
-        let m = StorageHelper::new(&mut sto0).get_membership().await?;
+        let (mut log_store, mut state_machine) = Adaptor::new(sto0.clone());
+        let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
         assert_eq!(
             Membership::new(vec![btreeset! {0,1}], None),
@@ -111,7 +113,8 @@ async fn snapshot_uses_prev_snap_membership() -> Result<()> {
             let logs = sto0.get_log_entries(..).await?;
             assert_eq!(3, logs.len(), "only one applied log");
         }
-        let m = StorageHelper::new(&mut sto0).get_membership().await?;
+        let (mut log_store, mut state_machine) = Adaptor::new(sto0.clone());
+        let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
         assert_eq!(
             Membership::new(vec![btreeset! {0,1}], None),
@@ -6,6 +6,7 @@ use anyhow::Result;
 use maplit::btreeset;
 use openraft::raft::AppendEntriesRequest;
 use openraft::raft::InstallSnapshotRequest;
+use openraft::storage::Adaptor;
 use openraft::Config;
 use openraft::Entry;
 use openraft::EntryPayload;
@@ -18,6 +19,7 @@ use openraft::RaftNetworkFactory;
 use openraft::RaftSnapshotBuilder;
 use openraft::RaftStorage;
 use openraft::ServerState;
+use openraft::SnapshotCompression;
 use openraft::SnapshotPolicy;
 use openraft::StorageHelper;
 use openraft::Vote;
@@ -119,8 +121,9 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
 
         tracing::info!("--- check that learner membership is affected");
         {
-            let mut sto1 = router.get_storage_handle(&1)?;
-            let m = StorageHelper::new(&mut sto1).get_membership().await?;
+            let sto1 = router.get_storage_handle(&1)?;
+            let (mut log_store, mut state_machine) = Adaptor::new(sto1);
+            let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
             tracing::info!("got membership of node-1: {:?}", m);
             assert_eq!(Membership::new(vec![btreeset! {2,3}], None), m.committed.membership);
@@ -142,6 +145,9 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
             meta: snap.meta.clone(),
             offset: 0,
             data: snap.snapshot.into_inner(),
+            compression: SnapshotCompression::None,
+            checksum: None,
+            snapshot_checksum: None,
             done: true,
         };
 
@@ -159,7 +165,8 @@ async fn snapshot_delete_conflicting_logs() -> Result<()> {
     {
         let mut sto1 = router.get_storage_handle(&1)?;
 
-        let m = StorageHelper::new(&mut sto1).get_membership().await?;
+        let (mut log_store, mut state_machine) = Adaptor::new(sto1.clone());
+        let m = StorageHelper::new(&mut log_store, &mut state_machine).get_membership().await?;
 
         tracing::info!("got membership of node-1: {:?}", m);
         assert_eq!(
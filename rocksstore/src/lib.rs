@@ -420,6 +420,7 @@ impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<RocksStore> {
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            metadata: (),
         };
 
         let snapshot = RocksSnapshot {
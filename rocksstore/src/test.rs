@@ -1,13 +1,38 @@
+use std::collections::BTreeSet;
 use std::future::Future;
+use std::io::Cursor;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use openraft::error::AppendEntriesError;
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::TimeoutNowError;
+use openraft::error::VoteError;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::storage::Adaptor;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::AnyError;
+use openraft::BasicNode;
+use openraft::Raft;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::ServerState;
 use openraft::StorageError;
 
 use crate::Config;
 use crate::RocksNodeId;
+use crate::RocksRequest;
 use crate::RocksStore;
 
 struct RocksBuilder {}
@@ -53,3 +78,127 @@ pub fn test_mem_store() -> Result<(), StorageError<RocksNodeId>> {
     Suite::test_all(RocksBuilder {})?;
     Ok(())
 }
+
+/// A network for a single node cluster: there is no other node to send RPC to.
+struct NoNetwork {}
+
+fn unreachable<E: std::error::Error>() -> RPCError<RocksNodeId, BasicNode, E> {
+    RPCError::Network(NetworkError::new(&AnyError::error("single node cluster")))
+}
+
+#[async_trait]
+impl RaftNetworkFactory<Config> for NoNetwork {
+    type Network = NoNetwork;
+    type ConnectionError = NetworkError;
+
+    async fn new_client(&mut self, _target: RocksNodeId, _node: &BasicNode) -> Result<NoNetwork, NetworkError> {
+        Ok(NoNetwork {})
+    }
+}
+
+#[async_trait]
+impl RaftNetwork<Config> for NoNetwork {
+    async fn send_append_entries(
+        &mut self,
+        _rpc: AppendEntriesRequest<Config>,
+    ) -> Result<AppendEntriesResponse<RocksNodeId>, RPCError<RocksNodeId, BasicNode, AppendEntriesError<RocksNodeId>>>
+    {
+        Err(unreachable())
+    }
+
+    async fn send_install_snapshot(
+        &mut self,
+        _rpc: InstallSnapshotRequest<Config>,
+    ) -> Result<InstallSnapshotResponse<RocksNodeId>, RPCError<RocksNodeId, BasicNode, InstallSnapshotError<RocksNodeId>>>
+    {
+        Err(unreachable())
+    }
+
+    async fn send_vote(
+        &mut self,
+        _rpc: VoteRequest<RocksNodeId>,
+    ) -> Result<VoteResponse<RocksNodeId>, RPCError<RocksNodeId, BasicNode, VoteError<RocksNodeId>>> {
+        Err(unreachable())
+    }
+
+    async fn send_pre_vote(
+        &mut self,
+        _rpc: VoteRequest<RocksNodeId>,
+    ) -> Result<VoteResponse<RocksNodeId>, RPCError<RocksNodeId, BasicNode, VoteError<RocksNodeId>>> {
+        Err(unreachable())
+    }
+
+    async fn send_timeout_now(
+        &mut self,
+        _rpc: TimeoutNowRequest<RocksNodeId>,
+    ) -> Result<TimeoutNowResponse<RocksNodeId>, RPCError<RocksNodeId, BasicNode, TimeoutNowError<RocksNodeId>>> {
+        Err(unreachable())
+    }
+}
+
+type RocksRaft = Raft<Config, NoNetwork, Adaptor<Config, Arc<RocksStore>>, Adaptor<Config, Arc<RocksStore>>>;
+
+async fn new_raft(store: Arc<RocksStore>) -> anyhow::Result<RocksRaft> {
+    let config = openraft::Config {
+        snapshot_policy: openraft::SnapshotPolicy::Never,
+        ..Default::default()
+    };
+    let (log_store, state_machine) = Adaptor::new(store);
+    let raft = Raft::new(0, Arc::new(config.validate()?), NoNetwork {}, log_store, state_machine).await?;
+    Ok(raft)
+}
+
+/// Export a snapshot from one `RocksStore` and restore it into a new one.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_export_import_snapshot() -> anyhow::Result<()> {
+    let timeout = Some(Duration::from_millis(2_000));
+
+    let src_dir = tempdir::TempDir::new("RocksExport")?;
+    let dst_dir = tempdir::TempDir::new("RocksImport")?;
+
+    // Build a snapshot on a single node cluster and export it.
+    let (meta, buf) = {
+        let raft = new_raft(RocksStore::new(src_dir.path()).await).await?;
+        raft.initialize(BTreeSet::from([0])).await?;
+        raft.wait(timeout).state(ServerState::Leader, "become leader").await?;
+
+        for i in 0..10 {
+            raft.client_write(RocksRequest::Set {
+                key: format!("foo-{}", i),
+                value: format!("bar-{}", i),
+            })
+            .await?;
+        }
+
+        raft.trigger_snapshot().await?;
+        let last_log_id = raft.metrics().borrow().last_applied.unwrap();
+        raft.wait(timeout).snapshot(last_log_id, "build snapshot").await?;
+
+        let mut buf = vec![];
+        let meta = raft.export_snapshot_to(&mut buf).await?.unwrap();
+        assert_eq!(Some(last_log_id), meta.last_log_id);
+
+        raft.shutdown().await?;
+        (meta, buf)
+    };
+
+    // Restore the snapshot into a pristine node.
+    {
+        let store = RocksStore::new(dst_dir.path()).await;
+        let raft = new_raft(store.clone()).await?;
+        raft.import_snapshot(meta.clone(), Cursor::new(buf)).await?;
+
+        raft.wait(timeout).snapshot(meta.last_log_id.unwrap(), "import snapshot").await?;
+        raft.wait(timeout).state(ServerState::Leader, "restored node becomes leader").await?;
+
+        let sm = store.state_machine.read().await;
+        for i in 0..10 {
+            assert_eq!(Some(format!("bar-{}", i)), sm.get(&format!("foo-{}", i))?);
+        }
+        drop(sm);
+
+        raft.shutdown().await?;
+    }
+
+    Ok(())
+}
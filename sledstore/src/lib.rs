@@ -466,6 +466,7 @@ impl RaftSnapshotBuilder<ExampleTypeConfig, Cursor<Vec<u8>>> for Arc<SledStore>
             last_log_id: last_applied_log,
             last_membership,
             snapshot_id,
+            metadata: (),
         };
 
         let snapshot = ExampleSnapshot {